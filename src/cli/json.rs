@@ -0,0 +1,225 @@
+// cli/json.rs
+
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::ExitStatus;
+use crate::{
+    models::FileFormModel,
+    pipeline::{
+        BatchItem, BatchJob, ImportReport, PipelineError, SheetReport, report::serialize_millis,
+    },
+};
+
+/// Machine-readable summary of a headless run, printed with `--json`.
+#[derive(Debug, Serialize)]
+pub struct RunSummary<'a> {
+    pub status: ExitStatus,
+    pub exit_code: u8,
+    /// The model the run was built from, after applying all arguments.
+    pub model: &'a FileFormModel,
+    pub validation_errors: Vec<String>,
+    /// The failure message for runs that stopped on a non-validation error.
+    pub error: Option<String>,
+    /// One entry per sheet imported, or a single entry for CSV sources.
+    pub tables: Vec<&'a SheetReport>,
+    /// One entry per file of a batch run; empty for single imports.
+    pub files: Vec<&'a BatchItem>,
+    pub rows_read: usize,
+    pub rows_written: usize,
+    pub rows_rejected: usize,
+    #[serde(rename = "elapsed_ms", serialize_with = "serialize_millis")]
+    pub elapsed: Duration,
+}
+
+impl<'a> RunSummary<'a> {
+    pub fn new(
+        model: &'a FileFormModel,
+        result: &'a Result<ImportReport, PipelineError>,
+        status: ExitStatus,
+        elapsed: Duration,
+    ) -> Self {
+        let (tables, validation_errors, error) = match result {
+            Ok(report) => (report.sheets.iter().collect(), Vec::new(), None),
+            Err(PipelineError::Validation(errors)) => (Vec::new(), errors.clone(), None),
+            Err(error @ PipelineError::SheetFailed { report, .. }) => (
+                report.sheets.iter().collect(),
+                Vec::new(),
+                Some(error.to_string()),
+            ),
+            Err(other) => (Vec::new(), Vec::new(), Some(other.to_string())),
+        };
+
+        Self {
+            status,
+            exit_code: status.code(),
+            model,
+            validation_errors,
+            error,
+            rows_read: tables.iter().map(|t| t.rows_read).sum(),
+            rows_written: tables.iter().map(|t| t.rows_written).sum(),
+            rows_rejected: tables.iter().map(|t| t.rows_rejected).sum(),
+            tables,
+            files: Vec::new(),
+            elapsed,
+        }
+    }
+
+    /// Summarizes a finished batch run, listing every file's status and the
+    /// tables of the files that were imported.
+    pub fn batch(
+        model: &'a FileFormModel,
+        job: &'a BatchJob,
+        status: ExitStatus,
+        elapsed: Duration,
+    ) -> Self {
+        let tables: Vec<&SheetReport> = job
+            .reports()
+            .flat_map(|report| report.sheets.iter())
+            .collect();
+        let error = match job.failures() {
+            0 => None,
+            failures => Some(format!("{failures} of {} files failed.", job.items.len())),
+        };
+
+        Self {
+            status,
+            exit_code: status.code(),
+            model,
+            validation_errors: Vec::new(),
+            error,
+            rows_read: tables.iter().map(|t| t.rows_read).sum(),
+            rows_written: tables.iter().map(|t| t.rows_written).sum(),
+            rows_rejected: tables.iter().map(|t| t.rows_rejected).sum(),
+            tables,
+            files: job.items.iter().collect(),
+            elapsed,
+        }
+    }
+
+    /// Summarizes a run that stopped before the import started, e.g.
+    /// because its profile could not be loaded.
+    pub fn failed(
+        model: &'a FileFormModel,
+        status: ExitStatus,
+        error: String,
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            status,
+            exit_code: status.code(),
+            model,
+            validation_errors: Vec::new(),
+            error: Some(error),
+            tables: Vec::new(),
+            files: Vec::new(),
+            rows_read: 0,
+            rows_written: 0,
+            rows_rejected: 0,
+            elapsed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::MalformedRow;
+    use pretty_assertions::assert_eq;
+    use serde_json::{Value, json};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_summary_of_partial_import() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("hours.csv"),
+            database_file: PathBuf::from("out.db"),
+            ..FileFormModel::default()
+        };
+        let result = Ok(ImportReport {
+            sheets: vec![
+                SheetReport {
+                    sheet: None,
+                    table: "hours".to_string(),
+                    rows_read: 3,
+                    rows_written: 2,
+                    rows_rejected: 1,
+                    malformed_rows: vec![
+                        MalformedRow {
+                            line: 4,
+                            message: "has 3 fields; most rows have 2".to_string(),
+                        },
+                    ],
+                    elapsed: Duration::from_millis(4),
+                },
+            ],
+            elapsed: Duration::from_millis(5),
+            ..ImportReport::default()
+        });
+
+        let summary = RunSummary::new(
+            &model,
+            &result,
+            ExitStatus::Partial,
+            Duration::from_millis(7),
+        );
+        let value = serde_json::to_value(&summary).expect("serializable");
+
+        assert_eq!(value["status"], json!("partial"));
+        assert_eq!(value["exit_code"], json!(5));
+        assert_eq!(value["model"]["db_backend"], json!("SQLite"));
+        assert_eq!(value["model"]["source_file"], json!("hours.csv"));
+        assert_eq!(value["tables"][0]["rows_rejected"], json!(1));
+        assert_eq!(value["tables"][0]["malformed_rows"][0]["line"], json!(4));
+        assert_eq!(value["tables"][0]["elapsed_ms"], json!(4.0));
+        assert_eq!(value["rows_written"], json!(2));
+        assert_eq!(value["elapsed_ms"], json!(7.0));
+    }
+
+    #[test]
+    fn test_summary_lists_each_sheet() {
+        let model = FileFormModel::default();
+        let sheet = |name: &str, rows: usize| SheetReport {
+            sheet: Some(name.to_string()),
+            table: name.to_lowercase(),
+            rows_read: rows,
+            rows_written: rows,
+            rows_rejected: 0,
+            malformed_rows: Vec::new(),
+            elapsed: Duration::from_millis(rows as u64),
+        };
+        let result = Ok(ImportReport {
+            sheets: vec![
+                sheet("Hours", 3),
+                sheet("Archive", 1),
+            ],
+            ..ImportReport::default()
+        });
+
+        let summary = RunSummary::new(&model, &result, ExitStatus::Success, Duration::ZERO);
+        let value = serde_json::to_value(&summary).expect("serializable");
+
+        assert_eq!(value["tables"][1]["sheet"], json!("Archive"));
+        assert_eq!(value["tables"][1]["table"], json!("archive"));
+        assert_eq!(value["tables"][1]["elapsed_ms"], json!(1.0));
+        assert_eq!(value["rows_written"], json!(4));
+    }
+
+    #[test]
+    fn test_summary_of_validation_failure() {
+        let model = FileFormModel::default();
+        let result = Err(PipelineError::Validation(vec![
+            "Source file is required.".to_string(),
+        ]));
+
+        let summary = RunSummary::new(&model, &result, ExitStatus::Validation, Duration::ZERO);
+        let value = serde_json::to_value(&summary).expect("serializable");
+
+        assert_eq!(
+            value["validation_errors"],
+            json!(["Source file is required."])
+        );
+        assert_eq!(value["tables"], Value::Array(Vec::new()));
+    }
+}
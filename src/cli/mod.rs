@@ -0,0 +1,943 @@
+// cli
+
+pub mod json;
+
+use std::{path::PathBuf, process::ExitCode, time::Instant};
+
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::{
+    db::DbError,
+    logging::{configure_logging, set_stdout_enabled},
+    models::{
+        CsvDelimiter, CsvQuote, DbBackend, FailurePolicy, FileFormModel, FixedWidthColumn,
+        LogLevel, SheetTarget, TextEncoding, WriteMode,
+    },
+    pipeline::{BatchJob, PipelineError, run_import},
+    profiles::{ProfileError, ProfileLibrary, resolve_profile},
+    sources::{SourceError, archive::clear_extracted_entries},
+};
+
+pub use json::RunSummary;
+
+/// Command-line interface for the loader.
+///
+/// With no subcommand the graphical window is opened.
+#[derive(Debug, Parser)]
+#[command(name = "gpui_demo", version, about = "TimeKeeper Loader")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Imports a sheet or CSV file into a database without opening a window.
+    Import(ImportArgs),
+}
+
+/// Arguments of the headless `import` command. Each one mirrors a field of
+/// the main form.
+///
+/// With `--profile` the saved form values are used as the starting point and
+/// any other argument given overrides them.
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// Saved profile to start from: a profile name or a path to a profile
+    /// `.toml` file.
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    /// Source workbook or CSV file.
+    #[arg(long, required_unless_present_any = ["profile", "batch"])]
+    pub source: Option<PathBuf>,
+
+    /// File or glob pattern (e.g. "timesheets/*.xlsx") to import as part of
+    /// one batch; repeat to add more.
+    #[arg(long)]
+    pub batch: Vec<String>,
+
+    /// Skip the rest of a batch once a file fails, instead of going on.
+    #[arg(long)]
+    pub stop_on_error: bool,
+
+    /// Sheet, or top-level array of a JSON document, to import; repeat to
+    /// import several. Required for workbook sources (Excel or
+    /// OpenDocument) unless `--all-sheets` is given.
+    #[arg(long = "sheet")]
+    pub sheets: Vec<String>,
+
+    /// Import every visible sheet of the workbook, or every top-level array
+    /// of a JSON document.
+    #[arg(long, conflicts_with = "sheets")]
+    pub all_sheets: bool,
+
+    /// Where the rows of several sheets go: "one-table-per-sheet" or
+    /// "one-table-for-all-sheets" [default: one-table-per-sheet].
+    #[arg(long, value_parser = parse_sheet_target)]
+    pub sheet_target: Option<SheetTarget>,
+
+    /// Target database file.
+    #[arg(long = "db", required_unless_present = "profile")]
+    pub database: Option<PathBuf>,
+
+    /// Database backend, e.g. "sqlite" [default: sqlite].
+    #[arg(long, value_parser = parse_backend)]
+    pub backend: Option<DbBackend>,
+
+    /// Folder for the log file; file logging is off when omitted.
+    #[arg(long)]
+    pub log_dir: Option<PathBuf>,
+
+    /// Log level: error, warn, info, debug or trace [default: info].
+    #[arg(long, value_parser = parse_log_level)]
+    pub log_level: Option<LogLevel>,
+
+    /// Treat row 1 as data instead of column names.
+    #[arg(long)]
+    pub no_headers: bool,
+
+    /// Treat row 1 as column names, as by default; overrides a profile
+    /// saved with headers off.
+    #[arg(long, conflicts_with = "no_headers")]
+    pub headers: bool,
+
+    /// Row holding the column names, counting from 1 at the top of the
+    /// sheet [default: first row of the range].
+    #[arg(long)]
+    pub header_row: Option<usize>,
+
+    /// First data row, counting from 1 [default: the row below the header].
+    #[arg(long)]
+    pub data_start_row: Option<usize>,
+
+    /// Rows to drop from the end of the data, such as a totals row.
+    #[arg(long)]
+    pub skip_trailing_rows: Option<usize>,
+
+    /// Block of cells holding the table, e.g. "B4:K200".
+    #[arg(long = "range")]
+    pub cell_range: Option<String>,
+
+    /// CSV delimiter: comma, semicolon, tab or pipe [default: detected].
+    #[arg(long, value_parser = parse_csv_delimiter)]
+    pub delimiter: Option<CsvDelimiter>,
+
+    /// CSV quote character: double-quote, single-quote or no-quoting
+    /// [default: detected].
+    #[arg(long, value_parser = parse_csv_quote)]
+    pub quote: Option<CsvQuote>,
+
+    /// Text encoding: utf-8, utf-16-le, utf-16-be, windows-1252 or latin-1
+    /// [default: detected].
+    #[arg(long, value_parser = parse_text_encoding)]
+    pub encoding: Option<TextEncoding>,
+
+    /// Column of a fixed-width text source as "name:start:width" or
+    /// "name:start:width:type", e.g. "hours:14:5:Real"; repeat for each
+    /// column.
+    #[arg(long = "fixed-width", value_parser = parse_fixed_width_column)]
+    pub fixed_width_columns: Vec<FixedWidthColumn>,
+
+    /// Do not log to stdout.
+    #[arg(long)]
+    pub quiet: bool,
+
+    /// What to do with an existing table, e.g. "append" or "create-only"
+    /// [default: append].
+    #[arg(long, value_parser = parse_write_mode)]
+    pub write_mode: Option<WriteMode>,
+
+    /// Key column for "upsert-by-key"; repeat for composite keys.
+    #[arg(long = "key-column")]
+    pub key_columns: Vec<String>,
+
+    /// Number of rows sampled for column type inference.
+    #[arg(long)]
+    pub sample_rows: Option<usize>,
+
+    /// Print a JSON summary on stdout; stdout logging is turned off.
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl ImportArgs {
+    /// Builds the same [`FileFormModel`] the form would for these arguments,
+    /// ignoring `--profile`.
+    pub fn to_model(&self) -> FileFormModel {
+        self.apply_to(FileFormModel {
+            has_headers: true,
+            ..FileFormModel::default()
+        })
+    }
+
+    /// Loads the `--profile`, if any, from a file or from `library`, and
+    /// applies the other arguments on top of it.
+    pub fn resolve_model(
+        &self,
+        library: Option<&ProfileLibrary>,
+    ) -> Result<FileFormModel, ProfileError> {
+        match &self.profile {
+            Some(spec) => Ok(self.apply_to(resolve_profile(spec, library)?.form)),
+            None => Ok(self.to_model()),
+        }
+    }
+
+    /// Overrides the fields of `base` with the arguments that were given.
+    ///
+    /// Stdout logging always follows `--quiet` and `--json`, since a saved
+    /// form's choice is meant for the window, not the terminal. Column
+    /// mappings and the watched folder have no flag and always come from
+    /// `base`.
+    pub fn apply_to(
+        &self,
+        base: FileFormModel,
+    ) -> FileFormModel {
+        FileFormModel {
+            source_file: self.source.clone().unwrap_or(base.source_file),
+            batch_sources: if self.batch.is_empty() {
+                base.batch_sources
+            } else {
+                self.batch.clone()
+            },
+            failure_policy: if self.stop_on_error {
+                FailurePolicy::StopOnFirstFailure
+            } else {
+                base.failure_policy
+            },
+            watch_folder: base.watch_folder,
+            watch_pattern: base.watch_pattern,
+            database_file: self.database.clone().unwrap_or(base.database_file),
+            log_directory: self.log_dir.clone().unwrap_or(base.log_directory),
+            db_backend: self.backend.unwrap_or(base.db_backend),
+            log_level: self.log_level.unwrap_or(base.log_level),
+            selected_sheet: self.sheets.first().cloned().or(base.selected_sheet),
+            import_sheets: match self.sheets.len() {
+                0 => base.import_sheets,
+                1 => Vec::new(),
+                _ => self.sheets.clone(),
+            },
+            all_visible_sheets: self.all_sheets
+                || (self.sheets.is_empty() && base.all_visible_sheets),
+            sheet_target: self.sheet_target.unwrap_or(base.sheet_target),
+            log_stdout: !self.quiet && !self.json,
+            has_headers: self.headers || (base.has_headers && !self.no_headers),
+            header_row: self.header_row.or(base.header_row),
+            data_start_row: self.data_start_row.or(base.data_start_row),
+            skip_trailing_rows: self.skip_trailing_rows.unwrap_or(base.skip_trailing_rows),
+            cell_range: self.cell_range.clone().or(base.cell_range),
+            csv_delimiter: self.delimiter.unwrap_or(base.csv_delimiter),
+            csv_quote: self.quote.unwrap_or(base.csv_quote),
+            encoding: self.encoding.unwrap_or(base.encoding),
+            write_mode: self.write_mode.unwrap_or(base.write_mode),
+            key_columns: if self.key_columns.is_empty() {
+                base.key_columns
+            } else {
+                self.key_columns.clone()
+            },
+            sample_rows: self.sample_rows.or(base.sample_rows),
+            column_mappings: base.column_mappings,
+            fixed_width_columns: if self.fixed_width_columns.is_empty() {
+                base.fixed_width_columns
+            } else {
+                self.fixed_width_columns.clone()
+            },
+            source_format: None,
+        }
+    }
+}
+
+/// Process exit status of a headless run.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitStatus {
+    Success,
+    /// An unexpected failure not covered by a more specific status, or a
+    /// batch in which some files failed.
+    Failure,
+    /// The arguments or the target table do not allow the import.
+    Validation,
+    /// The source or database could not be read or written.
+    Io,
+    /// The import finished but some rows were rejected or malformed, or
+    /// it stopped after some of its sheets were imported.
+    Partial,
+}
+
+impl ExitStatus {
+    /// Returns the numeric exit code. `2` is left to clap's usage errors.
+    pub fn code(self) -> u8 {
+        match self {
+            Self::Success => 0,
+            Self::Failure => 1,
+            Self::Validation => 3,
+            Self::Io => 4,
+            Self::Partial => 5,
+        }
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        ExitCode::from(status.code())
+    }
+}
+
+impl From<&ProfileError> for ExitStatus {
+    fn from(error: &ProfileError) -> Self {
+        match error {
+            ProfileError::Io { .. } => Self::Io,
+            _ => Self::Validation,
+        }
+    }
+}
+
+impl From<&PipelineError> for ExitStatus {
+    fn from(error: &PipelineError) -> Self {
+        match error {
+            PipelineError::Validation(_) => Self::Validation,
+            PipelineError::Source(SourceError::SheetNotFound { .. }) => Self::Validation,
+            PipelineError::Source(_) => Self::Io,
+            PipelineError::Database(DbError::Sqlite { .. }) => Self::Io,
+            PipelineError::Database(_) => Self::Validation,
+            PipelineError::SheetFailed { .. } => Self::Partial,
+        }
+    }
+}
+
+/// Runs the `import` command and returns the status to exit with.
+///
+/// A form with batch sources imports each file in turn. With `--json` a
+/// [`RunSummary`] is printed on stdout once the run ends.
+pub fn run_import_command(args: &ImportArgs) -> ExitStatus {
+    let started = Instant::now();
+    // Set before the profile is loaded, so nothing logged while loading it
+    // reaches stdout ahead of a `--json` summary.
+    if let Err(e) = set_stdout_enabled(!args.quiet && !args.json) {
+        warn!("Could not configure stdout logging: {e}");
+    }
+    let model = match args.resolve_model(ProfileLibrary::open_default().as_ref()) {
+        Ok(model) => model,
+        Err(error) => {
+            error!(%error, "Cannot load profile");
+            let status = ExitStatus::from(&error);
+            if args.json {
+                let model = args.to_model();
+                print_summary(&RunSummary::failed(
+                    &model,
+                    status,
+                    error.to_string(),
+                    started.elapsed(),
+                ));
+            }
+            return status;
+        }
+    };
+    configure_logging(&model);
+    info!(%model, profile = args.profile.as_deref(), "Starting headless import");
+    let status = run_model(args, &model, started);
+    clear_extracted_entries();
+    status
+}
+
+/// Imports the resolved `model` as [`run_import_command`] does, leaving the
+/// logging setup as it is.
+fn run_model(
+    args: &ImportArgs,
+    model: &FileFormModel,
+    started: Instant,
+) -> ExitStatus {
+    if model.is_batch() {
+        return run_batch_command(args, model, started);
+    }
+
+    let result = run_import(model);
+    let status = match &result {
+        Ok(report) if report.rows_rejected() > 0 || report.malformed_rows() > 0 => {
+            warn!(
+                rows_rejected = report.rows_rejected(),
+                malformed_rows = report.malformed_rows(),
+                "Import finished with rejected or malformed rows"
+            );
+            ExitStatus::Partial
+        }
+        Ok(_) => ExitStatus::Success,
+        Err(PipelineError::Validation(errors)) => {
+            for error in errors {
+                error!(%error, "validation error");
+            }
+            ExitStatus::Validation
+        }
+        Err(error) => {
+            error!(%error, "Import failed");
+            ExitStatus::from(error)
+        }
+    };
+
+    if args.json {
+        print_summary(&RunSummary::new(model, &result, status, started.elapsed()));
+    }
+    status
+}
+
+/// Imports every file of a batch, logging a status line per file.
+///
+/// The run is partial if any rows were rejected, and fails if any file
+/// failed.
+fn run_batch_command(
+    args: &ImportArgs,
+    model: &FileFormModel,
+    started: Instant,
+) -> ExitStatus {
+    let mut job = match BatchJob::new(model) {
+        Ok(job) => job,
+        Err(error) => {
+            error!(%error, "Cannot start batch");
+            let status = ExitStatus::from(&error);
+            if args.json {
+                let result = Err(error);
+                print_summary(&RunSummary::new(model, &result, status, started.elapsed()));
+            }
+            return status;
+        }
+    };
+
+    info!(files = job.items.len(), "Starting batch");
+    job.run();
+    for item in &job.items {
+        info!(%item, "Batch status");
+    }
+
+    let status = if job.failures() > 0 {
+        ExitStatus::Failure
+    } else if job
+        .reports()
+        .any(|report| report.rows_rejected() > 0 || report.malformed_rows() > 0)
+    {
+        ExitStatus::Partial
+    } else {
+        ExitStatus::Success
+    };
+    if args.json {
+        print_summary(&RunSummary::batch(model, &job, status, started.elapsed()));
+    }
+    status
+}
+
+fn print_summary(summary: &RunSummary<'_>) {
+    match serde_json::to_string_pretty(summary) {
+        Ok(json) => println!("{json}"),
+        Err(error) => error!(%error, "Could not serialize run summary"),
+    }
+}
+
+/// Matches `value` against the display labels of `options`, ignoring case,
+/// spaces, dashes and underscores, so that "create-only" selects
+/// "Create only".
+fn find_by_label<T: Copy + ToString>(
+    value: &str,
+    options: &[T],
+) -> Result<T, String> {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '_'))
+            .collect::<String>()
+            .to_ascii_lowercase()
+    };
+    let wanted = normalize(value);
+    options
+        .iter()
+        .find(|option| normalize(&option.to_string()) == wanted)
+        .copied()
+        .ok_or_else(|| {
+            let labels: Vec<String> = options.iter().map(ToString::to_string).collect();
+            format!("expected one of: {}", labels.join(", "))
+        })
+}
+
+fn parse_backend(value: &str) -> Result<DbBackend, String> {
+    find_by_label(value, &DbBackend::ALL)
+}
+
+fn parse_log_level(value: &str) -> Result<LogLevel, String> {
+    find_by_label(value, &LogLevel::ALL)
+}
+
+fn parse_write_mode(value: &str) -> Result<WriteMode, String> {
+    find_by_label(value, &WriteMode::ALL)
+}
+
+fn parse_sheet_target(value: &str) -> Result<SheetTarget, String> {
+    find_by_label(value, &SheetTarget::ALL)
+}
+
+fn parse_csv_delimiter(value: &str) -> Result<CsvDelimiter, String> {
+    find_by_label(value, &CsvDelimiter::ALL)
+}
+
+fn parse_csv_quote(value: &str) -> Result<CsvQuote, String> {
+    find_by_label(value, &CsvQuote::ALL)
+}
+
+fn parse_text_encoding(value: &str) -> Result<TextEncoding, String> {
+    find_by_label(value, &TextEncoding::ALL)
+}
+
+fn parse_fixed_width_column(value: &str) -> Result<FixedWidthColumn, String> {
+    value.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::SqlType;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn parse(args: &[&str]) -> ImportArgs {
+        let cli = Cli::try_parse_from(args).expect("arguments should parse");
+        match cli.command {
+            Some(Command::Import(args)) => args,
+            None => panic!("expected import command"),
+        }
+    }
+
+    /// Runs `args` like the `import` command, without touching the
+    /// process-wide logging setup.
+    fn run(args: &ImportArgs) -> ExitStatus {
+        run_model(args, &args.to_model(), Instant::now())
+    }
+
+    #[test]
+    fn test_import_args_build_model() {
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--source",
+            "x.xlsx",
+            "--sheet",
+            "Sheet1",
+            "--db",
+            "out.db",
+            "--backend",
+            "sqlite",
+            "--log-dir",
+            "logs",
+            "--log-level",
+            "debug",
+            "--write-mode",
+            "upsert-by-key",
+            "--key-column",
+            "employee_id",
+        ]);
+
+        let model = args.to_model();
+        assert_eq!(model.source_file, PathBuf::from("x.xlsx"));
+        assert_eq!(model.selected_sheet.as_deref(), Some("Sheet1"));
+        assert_eq!(model.database_file, PathBuf::from("out.db"));
+        assert_eq!(model.db_backend, DbBackend::Sqlite);
+        assert_eq!(model.log_directory, PathBuf::from("logs"));
+        assert_eq!(model.log_level, LogLevel::Debug);
+        assert_eq!(model.write_mode, WriteMode::UpsertByKey);
+        assert_eq!(model.key_columns, vec!["employee_id".to_string()]);
+        assert!(model.has_headers);
+        assert!(model.log_stdout);
+    }
+
+    #[test]
+    fn test_layout_arguments() {
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--source",
+            "x.xlsx",
+            "--db",
+            "out.db",
+            "--header-row",
+            "4",
+            "--skip-trailing-rows",
+            "1",
+            "--range",
+            "B4:K200",
+        ]);
+
+        let model = args.to_model();
+        assert_eq!(model.header_row, Some(4));
+        assert_eq!(model.data_start_row, None);
+        assert_eq!(model.skip_trailing_rows, 1);
+        assert_eq!(model.cell_range.as_deref(), Some("B4:K200"));
+    }
+
+    #[test]
+    fn test_json_turns_off_stdout_logging() {
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--source",
+            "x.csv",
+            "--db",
+            "out.db",
+            "--json",
+        ]);
+        assert!(!args.to_model().log_stdout);
+    }
+
+    #[test]
+    fn test_source_and_db_required_without_profile() {
+        let result = Cli::try_parse_from([
+            "gpui_demo",
+            "import",
+            "--db",
+            "out.db",
+        ]);
+        assert!(result.is_err());
+
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--profile",
+            "weekly",
+        ]);
+        assert_eq!(args.profile.as_deref(), Some("weekly"));
+    }
+
+    #[test]
+    fn test_arguments_override_profile_values() {
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--profile",
+            "weekly",
+            "--db",
+            "other.db",
+            "--no-headers",
+        ]);
+        let base = FileFormModel {
+            source_file: PathBuf::from("hours.xlsx"),
+            database_file: PathBuf::from("payroll.db"),
+            selected_sheet: Some("Hours".to_string()),
+            log_level: LogLevel::Debug,
+            has_headers: true,
+            write_mode: WriteMode::UpsertByKey,
+            key_columns: vec!["employee_id".to_string()],
+            ..FileFormModel::default()
+        };
+
+        let model = args.apply_to(base);
+
+        assert_eq!(model.source_file, PathBuf::from("hours.xlsx"));
+        assert_eq!(model.database_file, PathBuf::from("other.db"));
+        assert_eq!(model.selected_sheet.as_deref(), Some("Hours"));
+        assert_eq!(model.log_level, LogLevel::Debug);
+        assert_eq!(model.write_mode, WriteMode::UpsertByKey);
+        assert_eq!(model.key_columns, vec!["employee_id".to_string()]);
+        assert!(!model.has_headers);
+    }
+
+    #[test]
+    fn test_repeated_sheets_are_all_imported() {
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--source",
+            "hours.xlsx",
+            "--db",
+            "out.db",
+            "--sheet",
+            "Week 1",
+            "--sheet",
+            "Week 2",
+            "--sheet-target",
+            "one-table-for-all-sheets",
+        ]);
+        let base = FileFormModel {
+            all_visible_sheets: true,
+            ..FileFormModel::default()
+        };
+
+        let model = args.apply_to(base);
+
+        assert_eq!(model.selected_sheet.as_deref(), Some("Week 1"));
+        assert_eq!(
+            model.import_sheets,
+            vec![
+                "Week 1".to_string(),
+                "Week 2".to_string(),
+            ]
+        );
+        assert!(!model.all_visible_sheets);
+        assert_eq!(model.sheet_target, SheetTarget::SingleTable);
+    }
+
+    #[test]
+    fn test_csv_dialect_overrides() {
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--source",
+            "hours.csv",
+            "--db",
+            "out.db",
+            "--delimiter",
+            "semicolon",
+            "--quote",
+            "no-quoting",
+            "--encoding",
+            "windows-1252",
+        ]);
+
+        let model = args.to_model();
+
+        assert_eq!(model.csv_delimiter, CsvDelimiter::Semicolon);
+        assert_eq!(model.csv_quote, CsvQuote::NoQuoting);
+        assert_eq!(model.encoding, TextEncoding::Windows1252);
+        assert!(
+            Cli::try_parse_from([
+                "gpui_demo",
+                "import",
+                "--source",
+                "hours.csv",
+                "--db",
+                "out.db",
+                "--delimiter",
+                "colon",
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_fixed_width_columns() {
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--source",
+            "punches.txt",
+            "--db",
+            "out.db",
+            "--fixed-width",
+            "employee:1:5",
+            "--fixed-width",
+            "hours:14:5:real",
+        ]);
+
+        assert_eq!(
+            args.to_model().fixed_width_columns,
+            vec![
+                FixedWidthColumn::new("employee", 1, 5),
+                FixedWidthColumn::new("hours", 14, 5).with_type(SqlType::Real),
+            ]
+        );
+        assert!(
+            Cli::try_parse_from([
+                "gpui_demo",
+                "import",
+                "--source",
+                "punches.txt",
+                "--db",
+                "out.db",
+                "--fixed-width",
+                "employee:1",
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_batch_replaces_source() {
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--batch",
+            "timesheets/*.xlsx",
+            "--batch",
+            "late/march.xlsx",
+            "--stop-on-error",
+            "--db",
+            "out.db",
+        ]);
+
+        let model = args.to_model();
+
+        assert_eq!(
+            model.batch_sources,
+            vec![
+                "timesheets/*.xlsx".to_string(),
+                "late/march.xlsx".to_string(),
+            ]
+        );
+        assert_eq!(model.failure_policy, FailurePolicy::StopOnFirstFailure);
+        assert!(model.is_batch());
+    }
+
+    #[test]
+    fn test_run_import_command_batch_with_failure() {
+        let dir = TempDir::new().expect("temp dir");
+        fs::write(dir.path().join("week_1.csv"), "Employee,Hours\nAda,7.5\n").expect("write csv");
+        let pattern = dir.path().join("*.csv").display().to_string();
+        let missing = dir.path().join("week_2.csv").display().to_string();
+        let database = dir.path().join("out.db").display().to_string();
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--batch",
+            &pattern,
+            "--batch",
+            &missing,
+            "--db",
+            &database,
+            "--quiet",
+        ]);
+
+        assert_eq!(run(&args), ExitStatus::Failure);
+    }
+
+    #[test]
+    fn test_unknown_profile_is_a_validation_error() {
+        let dir = TempDir::new().expect("temp dir");
+        let library = ProfileLibrary::new(dir.path().to_path_buf());
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--profile",
+            "/definitely/not/here.toml",
+        ]);
+
+        let error = args
+            .resolve_model(Some(&library))
+            .expect_err("no such profile");
+
+        assert!(matches!(error, ProfileError::NotFound(_)), "got: {error:?}");
+        assert_eq!(ExitStatus::from(&error), ExitStatus::Validation);
+    }
+
+    #[test]
+    fn test_headers_override_profile_without_headers() {
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--profile",
+            "weekly",
+            "--headers",
+        ]);
+        let base = FileFormModel {
+            has_headers: false,
+            ..FileFormModel::default()
+        };
+
+        assert!(args.apply_to(base.clone()).has_headers);
+        assert!(
+            !parse(&[
+                "gpui_demo",
+                "import",
+                "--profile",
+                "weekly"
+            ])
+            .apply_to(base)
+            .has_headers
+        );
+        assert!(
+            Cli::try_parse_from([
+                "gpui_demo",
+                "import",
+                "--profile",
+                "weekly",
+                "--headers",
+                "--no-headers",
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_no_subcommand_opens_window() {
+        let cli = Cli::try_parse_from(["gpui_demo"]).expect("should parse");
+        assert!(cli.command.is_none());
+    }
+
+    #[test]
+    fn test_unknown_backend_is_rejected() {
+        let result = Cli::try_parse_from([
+            "gpui_demo",
+            "import",
+            "--source",
+            "x.csv",
+            "--db",
+            "out.db",
+            "--backend",
+            "oracle",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_backend_ignores_case_and_spaces() {
+        assert_eq!(parse_backend("google-cloud"), Ok(DbBackend::GoogleCloud));
+    }
+
+    #[test]
+    fn test_exit_status_for_source_error() {
+        let error = PipelineError::Source(SourceError::NotFound(PathBuf::from("x.csv")));
+        assert_eq!(ExitStatus::from(&error), ExitStatus::Io);
+    }
+
+    #[test]
+    fn test_exit_status_for_missing_sheet() {
+        let error = PipelineError::Source(SourceError::SheetNotFound {
+            path: PathBuf::from("x.xlsx"),
+            sheet: "Week 9".to_string(),
+        });
+        assert_eq!(ExitStatus::from(&error), ExitStatus::Validation);
+    }
+
+    #[test]
+    fn test_exit_status_for_table_exists() {
+        let error = PipelineError::Database(DbError::TableExists("hours".to_string()));
+        assert_eq!(ExitStatus::from(&error), ExitStatus::Validation);
+    }
+
+    #[test]
+    fn test_exit_status_for_failed_sheet_is_partial() {
+        let error = PipelineError::SheetFailed {
+            report: Box::default(),
+            sheet: "Week 2".to_string(),
+            error: Box::new(PipelineError::Source(SourceError::NotFound(PathBuf::from(
+                "x.xlsx",
+            )))),
+        };
+        assert_eq!(ExitStatus::from(&error), ExitStatus::Partial);
+    }
+
+    #[test]
+    fn test_run_import_command_validation_failure() {
+        let dir = TempDir::new().expect("temp dir");
+        let database = dir.path().join("out.db").display().to_string();
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--source",
+            "x.xlsx",
+            "--db",
+            &database,
+        ]);
+        assert_eq!(run(&args), ExitStatus::Validation);
+    }
+
+    #[test]
+    fn test_run_import_command_missing_source() {
+        let dir = TempDir::new().expect("temp dir");
+        let database = dir.path().join("out.db").display().to_string();
+        let args = parse(&[
+            "gpui_demo",
+            "import",
+            "--source",
+            "/definitely/not/here.csv",
+            "--db",
+            &database,
+        ]);
+        assert_eq!(run(&args), ExitStatus::Io);
+    }
+}
@@ -19,6 +19,29 @@ pub async fn get_file_path(
     Some(file.path().to_path_buf())
 }
 
+/// Opens an async file picker dialog that accepts several files at once.
+///
+/// Returns an empty list when the dialog is cancelled.
+pub async fn get_file_paths(
+    location: String,
+    filters: Vec<(String, Vec<String>)>,
+) -> Vec<PathBuf> {
+    let mut dialog = AsyncFileDialog::new().set_directory(&location);
+
+    for (name, extensions) in &filters {
+        let ext_refs: Vec<&str> = extensions.iter().map(|s| s.as_str()).collect();
+        dialog = dialog.add_filter(name, &ext_refs);
+    }
+
+    dialog
+        .pick_files()
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|file| file.path().to_path_buf())
+        .collect()
+}
+
 /// Opens an async file picker dialog to select a directory.
 ///
 pub async fn get_folder_path(location: String) -> Option<PathBuf> {
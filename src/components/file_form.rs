@@ -1,90 +1,788 @@
-use std::path::PathBuf;
+use std::{
+    num::ParseIntError,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use gpui::{
-    App, AppContext, ClickEvent, Context, Div, Entity, IntoElement, ParentElement, Render,
-    RenderOnce, SharedString, Styled, TextAlign, Window, div, px,
+    App, AppContext, ClickEvent, Context, Div, Entity, InteractiveElement, IntoElement,
+    ParentElement, Render, RenderOnce, SharedString, StatefulInteractiveElement, Styled,
+    Subscription, Task, TextAlign, Window, div, px,
 };
 use gpui_component::{
-    IndexPath,
+    ActiveTheme, IndexPath,
     checkbox::Checkbox,
     h_flex,
-    input::{Input, InputState},
-    select::{Select, SelectState},
+    input::{Input, InputEvent, InputState},
+    select::{Select, SelectEvent, SelectState},
+    table::{Table, TableState},
     v_flex,
 };
-use tracing::debug;
+use tracing::{debug, info, warn};
 
 use crate::{
-    components::{dialogs::get_folder_path, get_file_path, make_button, owned_filters},
+    components::{
+        ColumnMappingPanel, PreviewTableDelegate, dialogs::get_folder_path, get_file_path,
+        get_file_paths, make_button, owned_filters,
+    },
+    db::{TargetColumn, sqlite::read_table_columns},
     logging::log_task_error,
-    models::{DbBackend, FileFormModel, LogLevel},
+    models::{
+        CsvDelimiter, CsvQuote, DbBackend, FailurePolicy, FileFormModel, LogLevel, SheetTarget,
+        TextEncoding, WriteMode, format_layout, parse_layout,
+    },
+    pipeline::{
+        BatchItem, BatchJob, DataPreview, FileStatus, FolderWatch, batch::path_entry,
+        import_watched_file, preview::DEFAULT_PREVIEW_ROWS, preview_source, run_import, table_name,
+    },
+    preferences::{DefaultDirectories, PreferencesStore, current_preferences, update_preferences},
+    profiles::{Profile, ProfileLibrary},
+    sources::{SheetInfo, SourceError, SourceFormat, archive::clear_extracted_entries, registry},
 };
 
+/// Folder the file pickers open in when no default has been remembered.
+const FALLBACK_DIALOG_DIR: &str = "~/Desktop";
+
+/// Separates the entries of the batch input: one file or pattern per line,
+/// since any other character may be part of a file name.
+const BATCH_SEPARATOR: char = '\n';
+
+/// How often a watched folder is checked for settled files.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Number of watched-file status lines kept in the form.
+const WATCH_HISTORY: usize = 20;
+
 pub struct FileSelectionForm {
+    profile_select: Entity<SelectState<Vec<SharedString>>>,
+    profile_name: Entity<InputState>,
+    /// Folder profiles are saved in; `None` without a config directory.
+    profile_library: Option<ProfileLibrary>,
+    profiles: Vec<Profile>,
+    profile_error: Option<SharedString>,
     source_file: Entity<InputState>,
+    /// The running or last finished single-file import.
+    import_status: Option<BatchItem>,
+    _import_task: Option<Task<()>>,
+    batch_sources: Entity<InputState>,
+    failure_policy_select: Entity<SelectState<Vec<SharedString>>>,
+    /// The running or last finished batch, shown as one line per file.
+    batch: Option<BatchJob>,
+    batch_error: Option<SharedString>,
+    _batch_task: Option<Task<()>>,
+    watch_folder: Entity<InputState>,
+    watch_pattern: Entity<InputState>,
+    watch: Option<FolderWatch>,
+    /// Status of the most recent watched files, newest first.
+    watch_items: Vec<BatchItem>,
+    watch_error: Option<SharedString>,
+    _watch_task: Option<Task<()>>,
     database_file: Entity<InputState>,
     log_directory: Entity<InputState>,
+    source_history: Entity<SelectState<Vec<SharedString>>>,
+    database_history: Entity<SelectState<Vec<SharedString>>>,
+    log_history: Entity<SelectState<Vec<SharedString>>>,
     db_backend_select: Entity<SelectState<Vec<SharedString>>>,
     log_level_select: Entity<SelectState<Vec<SharedString>>>,
     sheets_select: Entity<SelectState<Vec<SharedString>>>,
+    sheet_target_select: Entity<SelectState<Vec<SharedString>>>,
+    write_mode_select: Entity<SelectState<Vec<SharedString>>>,
+    key_columns: Entity<InputState>,
+    header_row: Entity<InputState>,
+    data_start_row: Entity<InputState>,
+    skip_trailing_rows: Entity<InputState>,
+    cell_range: Entity<InputState>,
+    /// Data rows sampled for type inference; blank uses the default and
+    /// `0` samples every row.
+    sample_rows: Entity<InputState>,
+    csv_delimiter_select: Entity<SelectState<Vec<SharedString>>>,
+    csv_quote_select: Entity<SelectState<Vec<SharedString>>>,
+    encoding_select: Entity<SelectState<Vec<SharedString>>>,
+    /// Columns of a fixed-width text source, as `name:start:width[:type]`
+    /// separated by commas.
+    fixed_width: Entity<InputState>,
+    sheets: Vec<SheetInfo>,
+    /// Sheets ticked for import; empty imports the sheet in the dropdown.
+    import_sheets: Vec<String>,
+    all_visible_sheets: bool,
+    sheet_error: Option<SharedString>,
+    /// The running sheet list load; replacing it cancels a stale load.
+    _sheets_task: Option<Task<()>>,
+    /// Format of the source file, detected whenever the source or the
+    /// fixed-width layout changes.
+    source_format: Option<SourceFormat>,
+    /// The running format detection; replacing it cancels a stale one.
+    _format_task: Option<Task<()>>,
+    /// Warnings for restored paths that no longer exist.
+    missing_paths: Vec<SharedString>,
+    directories: DefaultDirectories,
     log_stdout: bool,
     has_headers: bool,
+    _release_subscription: Subscription,
+    _quit_subscription: Subscription,
+    _history_subscriptions: Vec<Subscription>,
+    preview_table: Entity<TableState<PreviewTableDelegate>>,
+    /// Row and error counts shown above the preview table.
+    preview_status: Option<SharedString>,
+    preview_has_invalid_cells: bool,
+    preview_error: Option<SharedString>,
+    /// The fixed-width layout drawn over the first lines of the source.
+    preview_ruler: Option<SharedString>,
+    preview_raw_lines: Vec<SharedString>,
+    /// The running preview load; replacing it cancels a stale load.
+    _preview_task: Option<Task<()>>,
+    _preview_subscriptions: Vec<Subscription>,
+    mapping_panel: Entity<ColumnMappingPanel>,
+    /// The running target column load; replacing it cancels a stale load.
+    _targets_task: Option<Task<()>>,
 }
 
 impl FileSelectionForm {
+    /// Builds the form, restoring the values saved by the last submit or
+    /// window close.
+    ///
+    /// Without a saved form the backend and logging choices come from the
+    /// preferences. The form snapshots itself again when it is closed.
     pub fn new(
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> Self {
+        let preferences = current_preferences(cx);
+        let last = preferences
+            .last_form
+            .clone()
+            .unwrap_or_else(|| FileFormModel {
+                db_backend: preferences.default_backend,
+                log_level: preferences.log_level,
+                log_stdout: preferences.log_stdout,
+                has_headers: true,
+                ..FileFormModel::default()
+            });
+
+        let profile_library = ProfileLibrary::open_default();
+        let profiles = profile_library
+            .as_ref()
+            .map(ProfileLibrary::list)
+            .unwrap_or_default();
+        let profile_select =
+            cx.new(|cx| SelectState::new(profile_labels(&profiles), None, window, cx));
+
+        let release_subscription = cx.on_release(|form, cx| form.snapshot(cx));
+        let quit_subscription = cx.on_app_quit(|form, cx| {
+            form.snapshot(cx);
+            clear_extracted_entries();
+            async {}
+        });
+
         let source_file = make_input_state("Source file path...", window, cx);
         let database_file = make_input_state("Database file path...", window, cx);
-        let log_file = make_input_state("Log folder path...", window, cx);
-
-        let db_options = vec![
-            SharedString::from("SQLite"),
-            SharedString::from("MySQL"),
-            SharedString::from("DB2"),
-            SharedString::from("Postgresql"),
-            SharedString::from("MariaDB"),
-            SharedString::from("MSSQL"),
-            SharedString::from("Redis"),
-            SharedString::from("AWS"),
-            SharedString::from("Azure"),
-            SharedString::from("Google Cloud"),
-            SharedString::from("Apache"),
-        ];
-        let initial_index = db_options
-            .iter()
-            .position(|s| s.as_ref() == "SQLite")
-            .map(|i| IndexPath::default().row(i));
-        let db_backend_select =
-            cx.new(|cx| SelectState::new(db_options, initial_index, window, cx));
-
-        let log_levels = vec![
-            SharedString::from("ERROR"),
-            SharedString::from("WARN"),
-            SharedString::from("INFO"),
-            SharedString::from("DEBUG"),
-            SharedString::from("TRACE"),
-        ];
-        let initial_index = log_levels
-            .iter()
-            .position(|s| s.as_ref() == "INFO")
-            .map(|i| IndexPath::default().row(i));
-        let log_level_select = cx.new(|cx| SelectState::new(log_levels, initial_index, window, cx));
+        let log_directory = make_input_state("Log folder path...", window, cx);
+        let (source_history, source_subscription) = make_history_select(&source_file, window, cx);
+        let (database_history, database_subscription) =
+            make_history_select(&database_file, window, cx);
+        let (log_history, log_subscription) = make_history_select(&log_directory, window, cx);
+        let preferences_subscription =
+            cx.observe_global_in::<PreferencesStore>(window, |form, window, cx| {
+                form.refresh_history(window, cx);
+            });
+        // Recent files deleted while the app was in the background leave
+        // the lists when it comes back to the front.
+        let activation_subscription = cx.observe_window_activation(window, |_, window, cx| {
+            if !window.is_window_active() {
+                return;
+            }
+            let recent = current_preferences(cx).recent;
+            let mut pruned = recent.clone();
+            pruned.prune_missing();
+            if pruned != recent {
+                update_preferences(cx, |prefs| prefs.recent = pruned);
+            }
+        });
+
         let sheets_select =
             cx.new(|cx| SelectState::new(Vec::<SharedString>::new(), None, window, cx));
+        let sheet_target_select = make_label_select(&SheetTarget::ALL, window, cx);
+        let header_row = make_input_state("Header row...", window, cx);
+        let data_start_row = make_input_state("Data starts at row...", window, cx);
+        let skip_trailing_rows = make_input_state("Footer rows to skip...", window, cx);
+        let cell_range = make_input_state("Cell range, e.g. B4:K200...", window, cx);
+        let sample_rows = make_input_state("Rows sampled for types, 0 for all...", window, cx);
+        let csv_delimiter_select = make_label_select(&CsvDelimiter::ALL, window, cx);
+        let csv_quote_select = make_label_select(&CsvQuote::ALL, window, cx);
+        let encoding_select = make_label_select(&TextEncoding::ALL, window, cx);
+        let fixed_width = make_input_state(
+            "Fixed-width columns, e.g. employee:1:5, hours:14:5:Real...",
+            window,
+            cx,
+        );
+        let layout_subscriptions = [
+            &header_row,
+            &data_start_row,
+            &skip_trailing_rows,
+            &cell_range,
+            &sample_rows,
+        ]
+        .map(|input| {
+            cx.subscribe_in(input, window, |form, _, event: &InputEvent, window, cx| {
+                if matches!(event, InputEvent::Change) {
+                    form.refresh_preview(window, cx);
+                }
+            })
+        });
+        let preview_subscriptions = vec![
+            cx.subscribe_in(
+                &source_file,
+                window,
+                |form, _, event: &InputEvent, window, cx| {
+                    if matches!(event, InputEvent::Change) {
+                        form.detect_source_format(window, cx);
+                    }
+                },
+            ),
+            // A layout can make a text file fixed-width, so it is detected
+            // again before the preview reloads.
+            cx.subscribe_in(
+                &fixed_width,
+                window,
+                |form, _, event: &InputEvent, window, cx| {
+                    if matches!(event, InputEvent::Change) {
+                        form.detect_source_format(window, cx);
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &sheets_select,
+                window,
+                |form, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+                    if let SelectEvent::Confirm(Some(_)) = event {
+                        form.refresh_preview(window, cx);
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &csv_delimiter_select,
+                window,
+                |form, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+                    if let SelectEvent::Confirm(Some(_)) = event {
+                        form.refresh_preview(window, cx);
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &csv_quote_select,
+                window,
+                |form, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+                    if let SelectEvent::Confirm(Some(_)) = event {
+                        form.refresh_preview(window, cx);
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &encoding_select,
+                window,
+                |form, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+                    if let SelectEvent::Confirm(Some(_)) = event {
+                        form.refresh_preview(window, cx);
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &sheet_target_select,
+                window,
+                |form, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+                    if let SelectEvent::Confirm(Some(_)) = event {
+                        form.refresh_targets(window, cx);
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &database_file,
+                window,
+                |form, _, event: &InputEvent, window, cx| {
+                    if matches!(event, InputEvent::Change) {
+                        form.refresh_targets(window, cx);
+                    }
+                },
+            ),
+        ];
+        let preview_table =
+            cx.new(|cx| TableState::new(PreviewTableDelegate::default(), window, cx));
 
-        Self {
+        let mut form = Self {
+            profile_select,
+            profile_name: make_input_state("Profile name...", window, cx),
+            profile_library,
+            profiles,
+            profile_error: None,
             source_file,
+            import_status: None,
+            _import_task: None,
+            batch_sources: cx.new(|cx| {
+                InputState::new(window, cx)
+                    .auto_grow(1, 5)
+                    .placeholder("Files or patterns such as timesheets/*.xlsx, one per line")
+            }),
+            failure_policy_select: make_label_select(&FailurePolicy::ALL, window, cx),
+            batch: None,
+            batch_error: None,
+            _batch_task: None,
+            watch_folder: make_input_state("Folder to watch...", window, cx),
+            watch_pattern: make_input_state("File pattern, e.g. *.xlsx...", window, cx),
+            watch: None,
+            watch_items: Vec::new(),
+            watch_error: None,
+            _watch_task: None,
             database_file,
-            log_directory: log_file,
-            db_backend_select,
-            log_level_select,
+            log_directory,
+            source_history,
+            database_history,
+            log_history,
+            db_backend_select: make_label_select(&DbBackend::ALL, window, cx),
+            log_level_select: make_label_select(&LogLevel::ALL, window, cx),
             sheets_select,
+            sheet_target_select,
+            write_mode_select: make_label_select(&WriteMode::ALL, window, cx),
+            key_columns: make_input_state("Key columns for upsert, comma separated...", window, cx),
+            header_row,
+            data_start_row,
+            skip_trailing_rows,
+            cell_range,
+            sample_rows,
+            csv_delimiter_select,
+            csv_quote_select,
+            encoding_select,
+            fixed_width,
+            sheets: Vec::new(),
+            import_sheets: Vec::new(),
+            all_visible_sheets: false,
+            sheet_error: None,
+            _sheets_task: None,
+            source_format: None,
+            _format_task: None,
+            missing_paths: Vec::new(),
+            directories: preferences.directories,
             log_stdout: false,
             has_headers: true,
+            _release_subscription: release_subscription,
+            _quit_subscription: quit_subscription,
+            _history_subscriptions: vec![
+                source_subscription,
+                database_subscription,
+                log_subscription,
+                preferences_subscription,
+                activation_subscription,
+            ],
+            preview_table,
+            preview_status: None,
+            preview_has_invalid_cells: false,
+            preview_error: None,
+            preview_ruler: None,
+            preview_raw_lines: Vec::new(),
+            _preview_task: None,
+            _preview_subscriptions: preview_subscriptions
+                .into_iter()
+                .chain(layout_subscriptions)
+                .collect(),
+            mapping_panel: cx.new(|_| ColumnMappingPanel::default()),
+            _targets_task: None,
+        };
+        form.refresh_history(window, cx);
+        form.apply_model(&last, window, cx);
+        form
+    }
+
+    /// Fills every input, dropdown and checkbox from `model`.
+    ///
+    /// The sheet list is re-read from the source workbook so the saved sheet
+    /// can be selected, paths that no longer exist are flagged and the
+    /// preview is reloaded once the sheets are known.
+    pub fn apply_model(
+        &mut self,
+        model: &FileFormModel,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        set_input_value(&self.source_file, &model.source_file, window, cx);
+        set_input_value(&self.database_file, &model.database_file, window, cx);
+        set_input_value(&self.log_directory, &model.log_directory, window, cx);
+        set_input_value(&self.watch_folder, &model.watch_folder, window, cx);
+        let watch_pattern = model.watch_pattern.clone().unwrap_or_default();
+        self.watch_pattern.update(cx, |state, cx| {
+            state.set_value(watch_pattern, window, cx);
+        });
+        let batch_sources = model.batch_sources.join(&BATCH_SEPARATOR.to_string());
+        self.batch_sources.update(cx, |state, cx| {
+            state.set_value(batch_sources, window, cx);
+        });
+        self.key_columns.update(cx, |state, cx| {
+            state.set_value(model.key_columns.join(", "), window, cx);
+        });
+        let layout_values = [
+            (&self.header_row, model.header_row),
+            (&self.data_start_row, model.data_start_row),
+            (
+                &self.skip_trailing_rows,
+                Some(model.skip_trailing_rows).filter(|rows| *rows > 0),
+            ),
+            (&self.sample_rows, model.sample_rows),
+        ];
+        for (input, value) in layout_values {
+            let text = value.map(|row| row.to_string()).unwrap_or_default();
+            input.update(cx, |state, cx| state.set_value(text, window, cx));
+        }
+        let cell_range = model.cell_range.clone().unwrap_or_default();
+        self.cell_range.update(cx, |state, cx| {
+            state.set_value(cell_range, window, cx);
+        });
+        let fixed_width = format_layout(&model.fixed_width_columns);
+        self.fixed_width.update(cx, |state, cx| {
+            state.set_value(fixed_width, window, cx);
+        });
+
+        select_label(
+            &self.db_backend_select,
+            &DbBackend::ALL,
+            model.db_backend,
+            window,
+            cx,
+        );
+        select_label(
+            &self.log_level_select,
+            &LogLevel::ALL,
+            model.log_level,
+            window,
+            cx,
+        );
+        select_label(
+            &self.write_mode_select,
+            &WriteMode::ALL,
+            model.write_mode,
+            window,
+            cx,
+        );
+        select_label(
+            &self.failure_policy_select,
+            &FailurePolicy::ALL,
+            model.failure_policy,
+            window,
+            cx,
+        );
+        select_label(
+            &self.sheet_target_select,
+            &SheetTarget::ALL,
+            model.sheet_target,
+            window,
+            cx,
+        );
+        select_label(
+            &self.csv_delimiter_select,
+            &CsvDelimiter::ALL,
+            model.csv_delimiter,
+            window,
+            cx,
+        );
+        select_label(
+            &self.csv_quote_select,
+            &CsvQuote::ALL,
+            model.csv_quote,
+            window,
+            cx,
+        );
+        select_label(
+            &self.encoding_select,
+            &TextEncoding::ALL,
+            model.encoding,
+            window,
+            cx,
+        );
+
+        self.import_sheets = model.import_sheets.clone();
+        self.all_visible_sheets = model.all_visible_sheets;
+
+        self.log_stdout = model.log_stdout;
+        self.has_headers = model.has_headers;
+        self.missing_paths = to_shared(model.missing_paths());
+        self.mapping_panel.update(cx, |panel, cx| {
+            panel.set_mappings(&model.column_mappings, window, cx);
+        });
+        self.load_sheets(model.selected_sheet.clone(), window, cx);
+        cx.notify();
+    }
+
+    /// Puts `path` in the source input and reloads its sheet list, as when a
+    /// file is picked from "File > Open Recent".
+    pub fn open_source(
+        &mut self,
+        path: &Path,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        set_input_value(&self.source_file, path, window, cx);
+        self.refresh_missing_paths(cx);
+        self.load_sheets(None, window, cx);
+    }
+
+    /// Reads the sheet list of the source in the source input on the
+    /// background executor, then selects `selected` if the list has it and
+    /// reloads the preview.
+    ///
+    /// A load still running from an earlier call is cancelled. A source
+    /// that is not a workbook (for example a CSV file) has no sheets.
+    pub fn load_sheets(
+        &mut self,
+        selected: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let source = PathBuf::from(self.source_file.read(cx).value().as_str().trim());
+        let load = cx.background_executor().spawn({
+            let source = source.clone();
+            async move { read_sheet_options(&source) }
+        });
+        self._sheets_task = Some(cx.spawn_in(window, async move |form, cx| {
+            let sheets = load.await;
+            let result = form.update_in(cx, |form, window, cx| {
+                match sheets {
+                    Ok(sheets) => {
+                        info!(
+                            source_file = %source.display(),
+                            sheet_count = sheets.len(),
+                            "Loaded sheet options"
+                        );
+                        form.set_sheet_options(sheets, window, cx);
+                        form.select_sheet(selected.as_deref(), window, cx);
+                    }
+                    Err(error) => {
+                        warn!(%error, "Could not load sheet options");
+                        form.set_sheet_error(&error, window, cx);
+                    }
+                }
+                form.refresh_preview(window, cx);
+            });
+            log_task_error("sheets", result);
+        }));
+    }
+
+    /// Selects the sheet called `name` in the sheet dropdown, if listed.
+    fn select_sheet(
+        &mut self,
+        name: Option<&str>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let index = name.and_then(|name| self.sheets.iter().position(|sheet| sheet.name == name));
+        if let Some(index) = index {
+            self.sheets_select.update(cx, |state, cx| {
+                state.set_selected_index(Some(IndexPath::default().row(index)), window, cx);
+            });
+        }
+    }
+
+    /// Reloads the per-field history dropdowns from the recent paths in the
+    /// preferences.
+    pub fn refresh_history(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let recent = current_preferences(cx).recent;
+        set_history(&self.source_history, &recent.source_files, window, cx);
+        set_history(&self.database_history, &recent.database_files, window, cx);
+        set_history(&self.log_history, &recent.log_folders, window, cx);
+    }
+
+    /// Reloads the preview table and the mapping panel from the current
+    /// source and sheet.
+    ///
+    /// The source and the target table's columns are read on the
+    /// background executor; a load still running from an earlier call is
+    /// cancelled.
+    pub fn refresh_preview(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let model = self.to_model(cx);
+        if model.source_file.as_os_str().is_empty() {
+            return;
+        }
+
+        let load = cx.background_executor().spawn(async move {
+            (
+                preview_source(&model, DEFAULT_PREVIEW_ROWS),
+                read_targets(&model),
+            )
+        });
+        self._preview_task = Some(cx.spawn_in(window, async move |form, cx| {
+            let (preview, targets) = load.await;
+            let result = form.update_in(cx, |form, window, cx| {
+                form.mapping_panel.update(cx, |panel, cx| {
+                    panel.set_targets(targets, window, cx);
+                });
+                form.set_preview(preview, window, cx);
+            });
+            log_task_error("preview", result);
+        }));
+    }
+
+    /// Reloads the target table columns offered in the mapping panel, as
+    /// when another database is chosen.
+    pub fn refresh_targets(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let model = self.to_model(cx);
+        let load = cx
+            .background_executor()
+            .spawn(async move { read_targets(&model) });
+        self._targets_task = Some(cx.spawn_in(window, async move |form, cx| {
+            let targets = load.await;
+            let result = form.update_in(cx, |form, window, cx| {
+                form.mapping_panel.update(cx, |panel, cx| {
+                    panel.set_targets(targets, window, cx);
+                });
+            });
+            log_task_error("target columns", result);
+        }));
+    }
+
+    /// Shows a loaded preview, or the error that stopped it.
+    fn set_preview(
+        &mut self,
+        preview: Result<DataPreview, SourceError>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let preview = match preview {
+            Ok(preview) => {
+                self.preview_error = None;
+                self.preview_status = Some(preview_status(&preview));
+                self.preview_has_invalid_cells =
+                    preview.invalid_cells() > 0 || !preview.malformed.is_empty();
+                self.preview_ruler = preview.ruler.clone().map(SharedString::from);
+                self.preview_raw_lines = preview
+                    .raw_lines
+                    .iter()
+                    .cloned()
+                    .map(SharedString::from)
+                    .collect();
+                preview
+            }
+            Err(error) => {
+                warn!(%error, "Could not load preview");
+                // A source that cannot be opened already shows the error
+                // below the sheet list.
+                let message = SharedString::from(error.to_string());
+                self.preview_error =
+                    Some(message).filter(|message| self.sheet_error.as_ref() != Some(message));
+                self.preview_status = None;
+                self.preview_has_invalid_cells = false;
+                self.preview_ruler = None;
+                self.preview_raw_lines.clear();
+                DataPreview::default()
+            }
+        };
+
+        self.mapping_panel.update(cx, |panel, cx| {
+            panel.set_columns(preview.schema.clone(), window, cx);
+        });
+        self.preview_table.update(cx, |table, cx| {
+            *table.delegate_mut() = PreviewTableDelegate::new(preview);
+            table.refresh(cx);
+        });
+        cx.notify();
+    }
+
+    /// Detects the format of the source file in the background, then
+    /// reloads the preview when the file can be previewed without choosing
+    /// a sheet, such as a CSV file or a JSON document.
+    fn detect_source_format(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let model = self.to_model(cx);
+        let detect = cx.background_executor().spawn(async move {
+            let model = model.with_detected_format();
+            let previewable = model.source_file.is_file()
+                && model.source_format.is_some()
+                && !model.is_workbook();
+            (model.source_format, previewable)
+        });
+        self._format_task = Some(cx.spawn_in(window, async move |form, cx| {
+            let (format, previewable) = detect.await;
+            let result = form.update_in(cx, |form, window, cx| {
+                form.source_format = format;
+                if previewable {
+                    form.refresh_preview(window, cx);
+                }
+            });
+            log_task_error("format", result);
+        }));
+    }
+
+    /// Applies the profile chosen in the profile dropdown.
+    pub fn load_selected_profile(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let selected: Option<&SharedString> = self.profile_select.read(cx).selected_value();
+        let Some(profile) = selected
+            .and_then(|name| self.profiles.iter().find(|p| p.name == name.as_str()))
+            .cloned()
+        else {
+            self.profile_error = Some("Choose a profile to load.".into());
+            cx.notify();
+            return;
+        };
+
+        info!(profile = %profile.name, "Loading profile");
+        self.profile_error = None;
+        self.profile_name.update(cx, |state, cx| {
+            state.set_value(profile.name.clone(), window, cx);
+        });
+        self.apply_model(&profile.form, window, cx);
+    }
+
+    /// Saves the current form values under the name in the profile name
+    /// input, replacing a profile of the same name.
+    pub fn save_profile(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(library) = &self.profile_library else {
+            self.profile_error = Some("No config folder is available for profiles.".into());
+            cx.notify();
+            return;
+        };
+
+        let profile = Profile::new(
+            self.profile_name.read(cx).value().as_str(),
+            self.to_model(cx),
+        );
+        match library.save(&profile) {
+            Ok(_) => {
+                self.profile_error = None;
+                self.profiles = library.list();
+                let labels = profile_labels(&self.profiles);
+                let index = labels
+                    .iter()
+                    .position(|label| label.as_str() == profile.name)
+                    .map(|i| IndexPath::default().row(i));
+                self.profile_select.update(cx, |state, cx| {
+                    state.set_items(labels, window, cx);
+                    state.set_selected_index(index, window, cx);
+                });
+            }
+            Err(error) => {
+                warn!(%error, "Could not save profile");
+                self.profile_error = Some(SharedString::from(error.to_string()));
+            }
         }
+        cx.notify();
     }
 
     /// Collects the current form values into a [`FileFormModel`].
@@ -106,18 +804,374 @@ impl FileSelectionForm {
             .sheets_select
             .read(cx)
             .selected_value()
-            .map(ToString::to_string);
+            .map(|label| self.sheet_name_for_label(label.as_ref()));
+
+        let policy: Option<&SharedString> = self.failure_policy_select.read(cx).selected_value();
+        let failure_policy = policy
+            .and_then(|value| FailurePolicy::from_label(value.as_ref()))
+            .unwrap_or_default();
+
+        let batch_sources: Vec<String> = self
+            .batch_sources
+            .read(cx)
+            .value()
+            .split(BATCH_SEPARATOR)
+            .map(str::trim)
+            .filter(|source| !source.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        let target: Option<&SharedString> = self.sheet_target_select.read(cx).selected_value();
+        let sheet_target = target
+            .and_then(|value| SheetTarget::from_label(value.as_ref()))
+            .unwrap_or_default();
+
+        let delimiter: Option<&SharedString> = self.csv_delimiter_select.read(cx).selected_value();
+        let csv_delimiter = delimiter
+            .and_then(|value| CsvDelimiter::from_label(value.as_ref()))
+            .unwrap_or_default();
+
+        let quote: Option<&SharedString> = self.csv_quote_select.read(cx).selected_value();
+        let csv_quote = quote
+            .and_then(|value| CsvQuote::from_label(value.as_ref()))
+            .unwrap_or_default();
+
+        let encoding: Option<&SharedString> = self.encoding_select.read(cx).selected_value();
+        let encoding = encoding
+            .and_then(|value| TextEncoding::from_label(value.as_ref()))
+            .unwrap_or_default();
+
+        let mode: Option<&SharedString> = self.write_mode_select.read(cx).selected_value();
+        let write_mode = mode
+            .and_then(|value| WriteMode::from_label(value.as_ref()))
+            .unwrap_or_default();
+
+        let key_columns: Vec<String> = self
+            .key_columns
+            .read(cx)
+            .value()
+            .split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(str::to_string)
+            .collect();
 
         FileFormModel {
             source_file: PathBuf::from(self.source_file.read(cx).value().as_str().trim()),
+            batch_sources,
+            failure_policy,
+            watch_folder: PathBuf::from(self.watch_folder.read(cx).value().as_str().trim()),
+            watch_pattern: Some(
+                self.watch_pattern
+                    .read(cx)
+                    .value()
+                    .as_str()
+                    .trim()
+                    .to_string(),
+            )
+            .filter(|pattern| !pattern.is_empty()),
             database_file: PathBuf::from(self.database_file.read(cx).value().as_str().trim()),
             log_directory: PathBuf::from(self.log_directory.read(cx).value().as_str().trim()),
             db_backend,
             log_level,
             selected_sheet,
+            import_sheets: self.import_sheets.clone(),
+            all_visible_sheets: self.all_visible_sheets,
+            sheet_target,
             log_stdout: self.log_stdout,
             has_headers: self.has_headers,
+            header_row: parse_row_number(&self.header_row, cx).unwrap_or(None),
+            data_start_row: parse_row_number(&self.data_start_row, cx).unwrap_or(None),
+            skip_trailing_rows: parse_row_number(&self.skip_trailing_rows, cx)
+                .unwrap_or(None)
+                .unwrap_or(0),
+            cell_range: Some(self.cell_range.read(cx).value().as_str().trim().to_string())
+                .filter(|range| !range.is_empty()),
+            csv_delimiter,
+            csv_quote,
+            encoding,
+            write_mode,
+            key_columns,
+            sample_rows: parse_row_number(&self.sample_rows, cx).unwrap_or(None),
+            column_mappings: self.mapping_panel.read(cx).mappings(cx),
+            fixed_width_columns: parse_layout(self.fixed_width.read(cx).value().as_str())
+                .unwrap_or_default(),
+            source_format: self.source_format,
+        }
+    }
+
+    /// Describes the row inputs that do not hold a whole number.
+    ///
+    /// Such inputs are left out of [`FileSelectionForm::to_model`], so the
+    /// form says why they have no effect.
+    fn row_input_errors(
+        &self,
+        cx: &App,
+    ) -> Vec<SharedString> {
+        [
+            (&self.header_row, "Header row"),
+            (&self.data_start_row, "Data start row"),
+            (&self.skip_trailing_rows, "Footer rows to skip"),
+            (&self.sample_rows, "Rows sampled for types"),
+        ]
+        .into_iter()
+        .filter(|(input, _)| parse_row_number(input, cx).is_err())
+        .map(|(_, label)| SharedString::from(format!("{label} must be a whole number.")))
+        .collect()
+    }
+
+    /// Validates `model`, collected from this form, for submit (see
+    /// [`FileFormModel::validate_for_submit`]), also failing while the
+    /// fixed-width input cannot be read as a layout, since `model` leaves
+    /// that layout out rather than import the file without it.
+    pub fn validate_for_submit(
+        &self,
+        model: &FileFormModel,
+        cx: &App,
+    ) -> Result<(), Vec<String>> {
+        let mut errors: Vec<String> = self
+            .fixed_width_error(cx)
+            .map(String::from)
+            .into_iter()
+            .collect();
+        if let Err(model_errors) = model.validate_for_submit() {
+            errors.extend(model_errors);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Describes why the fixed-width input cannot be read as a layout,
+    /// which leaves it out of [`FileSelectionForm::to_model`].
+    fn fixed_width_error(
+        &self,
+        cx: &App,
+    ) -> Option<SharedString> {
+        parse_layout(self.fixed_width.read(cx).value().as_str())
+            .err()
+            .map(SharedString::from)
+    }
+
+    /// Imports the source of `model` on the background executor, showing
+    /// its row counts below the source input once it finishes.
+    pub fn start_import(
+        &mut self,
+        model: FileFormModel,
+        cx: &mut Context<Self>,
+    ) {
+        self.import_status = Some(BatchItem {
+            source: model.source_file.clone(),
+            status: FileStatus::Running,
+        });
+
+        self._import_task = Some(cx.spawn(async move |form, cx| {
+            let result: anyhow::Result<()> = async {
+                let outcome = cx
+                    .background_executor()
+                    .spawn(async move { run_import(&model) })
+                    .await;
+                let status = match outcome {
+                    Ok(report) => FileStatus::Imported { report },
+                    Err(error) => FileStatus::Failed {
+                        error: error.to_string(),
+                    },
+                };
+                form.update(cx, |form, cx| {
+                    if let Some(item) = form.import_status.as_mut() {
+                        item.status = status;
+                    }
+                    cx.notify();
+                })?;
+                Ok(())
+            }
+            .await;
+
+            log_task_error("import", result);
+        }));
+        cx.notify();
+    }
+
+    /// Queues every file of the batch in `model` and imports them one at a
+    /// time on the background executor, updating each file's status line as
+    /// it finishes.
+    ///
+    /// A batch still running is cancelled; files it already imported stay
+    /// imported.
+    pub fn start_batch(
+        &mut self,
+        model: FileFormModel,
+        cx: &mut Context<Self>,
+    ) {
+        match BatchJob::new(&model) {
+            Ok(job) => {
+                info!(files = job.items.len(), "Starting batch");
+                self.batch = Some(job);
+                self.batch_error = None;
+            }
+            Err(error) => {
+                warn!(%error, "Cannot start batch");
+                self.batch = None;
+                self.batch_error = Some(SharedString::from(error.to_string()));
+                self._batch_task = None;
+                cx.notify();
+                return;
+            }
+        }
+
+        self._batch_task = Some(cx.spawn(async move |form, cx| {
+            let result: anyhow::Result<()> = async {
+                loop {
+                    let next = form.update(cx, |form, cx| {
+                        cx.notify();
+                        form.batch.as_mut().and_then(BatchJob::start_next)
+                    })?;
+                    let Some((index, model)) = next else {
+                        break;
+                    };
+
+                    let outcome = cx
+                        .background_executor()
+                        .spawn(async move { run_import(&model) })
+                        .await;
+                    form.update(cx, |form, cx| {
+                        if let Some(batch) = form.batch.as_mut() {
+                            batch.record(index, outcome);
+                        }
+                        cx.notify();
+                    })?;
+                }
+                Ok(())
+            }
+            .await;
+
+            log_task_error("batch", result);
+        }));
+        cx.notify();
+    }
+
+    /// Starts watching the folder in the watch input with the current form
+    /// values, or stops a running watch.
+    ///
+    /// Every [`WATCH_POLL_INTERVAL`] the settled files are imported one at a
+    /// time on the background executor, each adding a status line.
+    pub fn toggle_watch(
+        &mut self,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(watch) = self.watch.take() {
+            info!(folder = %watch.folder().display(), "Stopped watching folder");
+            self._watch_task = None;
+            cx.notify();
+            return;
+        }
+
+        match FolderWatch::start(&self.to_model(cx)) {
+            Ok(watch) => {
+                self.watch = Some(watch);
+                self.watch_error = None;
+            }
+            Err(error) => {
+                warn!(%error, "Cannot watch folder");
+                self.watch_error = Some(SharedString::from(error.to_string()));
+                cx.notify();
+                return;
+            }
         }
+
+        self._watch_task = Some(cx.spawn(async move |form, cx| {
+            let result: anyhow::Result<()> = async {
+                loop {
+                    cx.background_executor().timer(WATCH_POLL_INTERVAL).await;
+                    let ready =
+                        form.update(cx, |form, _| form.watch.as_mut().map(FolderWatch::poll))?;
+                    let Some(ready) = ready else {
+                        break;
+                    };
+
+                    for model in ready {
+                        let running = BatchItem {
+                            source: model.source_file.clone(),
+                            status: FileStatus::Running,
+                        };
+                        form.update(cx, |form, cx| {
+                            form.watch_items.insert(0, running);
+                            form.watch_items.truncate(WATCH_HISTORY);
+                            cx.notify();
+                        })?;
+
+                        let item = cx
+                            .background_executor()
+                            .spawn(async move { import_watched_file(&model) })
+                            .await;
+                        form.update(cx, |form, cx| {
+                            let line = form.watch_items.iter_mut().find(|line| {
+                                line.source == item.source && line.status == FileStatus::Running
+                            });
+                            if let Some(line) = line {
+                                *line = item;
+                            }
+                            cx.notify();
+                        })?;
+                    }
+                }
+                Ok(())
+            }
+            .await;
+
+            log_task_error("watch", result);
+        }));
+        cx.notify();
+    }
+
+    /// Renders one status line per batch file.
+    fn render_batch_status(
+        &self,
+        cx: &App,
+    ) -> Vec<Div> {
+        let Some(batch) = &self.batch else {
+            return Vec::new();
+        };
+
+        batch
+            .items
+            .iter()
+            .map(|item| status_line(item, cx))
+            .collect()
+    }
+
+    /// Renders the watched folder controls and the status of the most
+    /// recent watched files.
+    fn render_watch(
+        &self,
+        cx: &mut Context<Self>,
+    ) -> Div {
+        let (button_id, button_label) = match &self.watch {
+            Some(_) => ("watch-stop", "Stop Watching"),
+            None => ("watch-start", "Watch Folder"),
+        };
+
+        v_flex()
+            .gap_2()
+            .child(
+                make_labeled_row("Watch:")
+                    .child(Input::new(&self.watch_folder).flex_grow())
+                    .child(Input::new(&self.watch_pattern).w(px(180.)))
+                    .child(make_button(
+                        button_id,
+                        button_label,
+                        cx.listener(|form, _, _, cx| form.toggle_watch(cx)),
+                    )),
+            )
+            .children(self.watch_error.clone().map(|error| {
+                div()
+                    .pl(px(120.))
+                    .text_color(cx.theme().danger)
+                    .child(error)
+            }))
+            .children(self.watch_items.iter().map(|item| status_line(item, cx)))
     }
 
     /// Returns the source file input state.
@@ -145,57 +1199,150 @@ impl FileSelectionForm {
         self.has_headers
     }
 
-    /// Returns sheet options derived from the current source input value.
-    ///
-    /// This is called by the "Load Sheets" button and can be replaced later
-    /// with real workbook parsing.
-    pub fn load_sheet_options(
-        &self,
-        cx: &App,
-    ) -> Vec<SharedString> {
-        let source = self
-            .source_file
-            .read(cx)
-            .value()
-            .as_str()
-            .trim()
-            .to_string();
-        if source.is_empty() {
-            return Vec::new();
-        }
+    /// Re-checks the current paths and updates the missing-path warnings.
+    pub fn refresh_missing_paths(
+        &mut self,
+        cx: &mut Context<Self>,
+    ) {
+        self.missing_paths = to_shared(self.to_model(cx).missing_paths());
+        cx.notify();
+    }
 
-        match PathBuf::from(source)
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| ext.to_ascii_lowercase())
-            .as_deref()
-        {
-            Some("xlsx" | "xlsm" | "xlsb" | "xls") => vec![
-                SharedString::from("Sheet1"),
-                SharedString::from("Sheet2"),
-                SharedString::from("Sheet3"),
-            ],
-            _ => Vec::new(),
-        }
+    /// Saves the current values so the next launch starts from them.
+    fn snapshot(
+        &self,
+        cx: &mut App,
+    ) {
+        let model = self.to_model(cx);
+        update_preferences(cx, |prefs| prefs.remember_form(&model));
     }
 
     /// Replaces the sheet dropdown options and selects the first item if present.
+    ///
+    /// Ticked sheets the new list does not have are unticked, and any error
+    /// shown from a previous load is cleared.
     pub fn set_sheet_options(
         &mut self,
-        options: Vec<SharedString>,
+        sheets: Vec<SheetInfo>,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        let options: Vec<SharedString> = sheets
+            .iter()
+            .map(|sheet| SharedString::from(sheet.label()))
+            .collect();
         let selected_index = if options.is_empty() {
             None
         } else {
             Some(IndexPath::default())
         };
 
+        self.import_sheets
+            .retain(|name| sheets.iter().any(|sheet| sheet.name == *name));
+        self.sheets = sheets;
+        self.sheet_error = None;
         self.sheets_select.update(cx, |state, cx| {
             state.set_items(options, window, cx);
             state.set_selected_index(selected_index, window, cx);
         });
+        cx.notify();
+    }
+
+    /// Clears the sheet dropdown and shows `error` below it.
+    pub fn set_sheet_error(
+        &mut self,
+        error: &SourceError,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.set_sheet_options(Vec::new(), window, cx);
+        self.sheet_error = Some(SharedString::from(error.to_string()));
+        cx.notify();
+    }
+
+    /// Ticks or unticks `sheet` for import, keeping workbook order.
+    fn toggle_import_sheet(
+        &mut self,
+        sheet: &str,
+        checked: bool,
+        cx: &mut Context<Self>,
+    ) {
+        self.import_sheets = self
+            .sheets
+            .iter()
+            .map(|info| info.name.clone())
+            .filter(|name| {
+                if name == sheet {
+                    checked
+                } else {
+                    self.import_sheets.contains(name)
+                }
+            })
+            .collect();
+        cx.notify();
+    }
+
+    /// Renders the sheets to import and where they go, for workbooks (or
+    /// JSON documents and zip archives) with more than one sheet.
+    fn render_import_sheets(
+        &self,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Option<Div> {
+        if self.sheets.len() < 2 {
+            return None;
+        }
+
+        let sheet_checkboxes = self
+            .sheets
+            .iter()
+            .enumerate()
+            .filter(|_| !self.all_visible_sheets)
+            .map(|(index, sheet)| {
+                let name = sheet.name.clone();
+                Checkbox::new(("import-sheet", index))
+                    .label(sheet.label())
+                    .checked(self.import_sheets.contains(&sheet.name))
+                    .on_click(cx.listener(move |form, checked, _, cx| {
+                        form.toggle_import_sheet(&name, *checked, cx);
+                    }))
+            });
+
+        Some(
+            make_labeled_row("Import:")
+                .child(
+                    h_flex()
+                        .flex_grow()
+                        .flex_wrap()
+                        .gap_3()
+                        .child(
+                            Checkbox::new("import-all-sheets")
+                                .label("All visible sheets")
+                                .checked(self.all_visible_sheets)
+                                .on_click(cx.listener(|form, checked, _, cx| {
+                                    form.all_visible_sheets = *checked;
+                                    cx.notify();
+                                })),
+                        )
+                        .children(sheet_checkboxes),
+                )
+                .child(
+                    Select::new(&self.sheet_target_select)
+                        .w(px(220.))
+                        .render(window, cx),
+                ),
+        )
+    }
+
+    /// Maps a dropdown label back to the sheet name it was built from.
+    fn sheet_name_for_label(
+        &self,
+        label: &str,
+    ) -> String {
+        self.sheets
+            .iter()
+            .find(|sheet| sheet.label() == label)
+            .map_or_else(|| label.to_string(), |sheet| sheet.name.clone())
     }
 }
 
@@ -208,34 +1355,100 @@ impl Render for FileSelectionForm {
         v_flex()
             .gap_2()
             .size_full()
+            .child(
+                make_labeled_row("Profile:")
+                    .child(
+                        Select::new(&self.profile_select)
+                            .flex_grow()
+                            .render(window, cx),
+                    )
+                    .child(make_button(
+                        "profile-load",
+                        "Load Profile",
+                        cx.listener(|form, _, window, cx| form.load_selected_profile(window, cx)),
+                    )),
+            )
+            .child(make_input_row(
+                &self.profile_name,
+                None,
+                "Save As:",
+                "profile-save",
+                "Save Profile",
+                cx.listener(|form, _, window, cx| form.save_profile(window, cx)),
+            ))
+            .children(self.profile_error.clone().map(|error| {
+                div()
+                    .pl(px(120.))
+                    .text_color(cx.theme().danger)
+                    .child(error)
+            }))
+            .children(
+                self.missing_paths
+                    .iter()
+                    .map(|warning| div().text_color(cx.theme().danger).child(warning.clone())),
+            )
             .child(make_input_row(
                 &self.source_file,
+                Some(&self.source_history),
                 "Source File:",
                 "source-select",
                 "Select File",
                 file_select_handler(
                     &self.source_file,
-                    "~/Desktop",
-                    &[
-                        (
-                            "Excel",
-                            &[
-                                "xlsx", "xlsm",
-                            ] as &[_],
-                        ),
-                        ("CSV", &["csv"] as &[_]),
-                    ],
+                    &dialog_dir(self.directories.source.as_deref()),
+                    &registry().file_filters(),
                     false,
                 ),
             ))
+            .children(
+                self.import_status
+                    .as_ref()
+                    .map(|item| status_line(item, cx)),
+            )
+            .child(
+                make_labeled_row("Batch:")
+                    .child(Input::new(&self.batch_sources).flex_grow())
+                    .child(
+                        Select::new(&self.failure_policy_select)
+                            .w(px(180.))
+                            .render(window, cx),
+                    )
+                    .child(make_button(
+                        "batch-files",
+                        "Add Files",
+                        batch_select_handler(
+                            &self.batch_sources,
+                            &dialog_dir(self.directories.source.as_deref()),
+                            false,
+                        ),
+                    ))
+                    .child(make_button(
+                        "batch-folder",
+                        "Add Folder",
+                        batch_select_handler(
+                            &self.batch_sources,
+                            &dialog_dir(self.directories.source.as_deref()),
+                            true,
+                        ),
+                    )),
+            )
+            .children(self.batch_error.clone().map(|error| {
+                div()
+                    .pl(px(120.))
+                    .text_color(cx.theme().danger)
+                    .child(error)
+            }))
+            .children(self.render_batch_status(cx))
+            .child(self.render_watch(cx))
             .child(make_input_row(
                 &self.database_file,
+                Some(&self.database_history),
                 "Database:",
                 "db-select",
                 "Select Database",
                 file_select_handler(
                     &self.database_file,
-                    "~/Desktop",
+                    &dialog_dir(self.directories.database.as_deref()),
                     &[(
                         "SQLite",
                         &[
@@ -247,10 +1460,16 @@ impl Render for FileSelectionForm {
             ))
             .child(make_input_row(
                 &self.log_directory,
+                Some(&self.log_history),
                 "Log Folder:",
                 "log-select",
                 "Select Log Folder",
-                file_select_handler(&self.log_directory, "~/Desktop", &[], true),
+                file_select_handler(
+                    &self.log_directory,
+                    &dialog_dir(self.directories.log.as_deref()),
+                    &[],
+                    true,
+                ),
             ))
             .child(make_select_row(
                 "Log Level:",
@@ -266,10 +1485,63 @@ impl Render for FileSelectionForm {
             ))
             .child(make_select_row(
                 "Sheets:",
-                Select::new(&self.sheets_select)
+                Select::new(&self.sheets_select).w_full().render(window, cx),
+            ))
+            .children(self.render_import_sheets(window, cx))
+            .children(self.sheet_error.clone().map(|error| {
+                div()
+                    .pl(px(120.))
+                    .text_color(cx.theme().danger)
+                    .child(error)
+            }))
+            .child(
+                make_labeled_row("Rows:")
+                    .child(Input::new(&self.header_row).flex_grow())
+                    .child(Input::new(&self.data_start_row).flex_grow())
+                    .child(Input::new(&self.skip_trailing_rows).flex_grow()),
+            )
+            .child(
+                make_labeled_row("Type sample:").child(Input::new(&self.sample_rows).flex_grow()),
+            )
+            .children(self.row_input_errors(cx).into_iter().map(|error| {
+                div()
+                    .pl(px(120.))
+                    .text_color(cx.theme().danger)
+                    .child(error)
+            }))
+            .child(make_text_row(&self.cell_range, "Cell Range:"))
+            .child(make_text_row(&self.fixed_width, "Fixed Width:"))
+            .children(self.fixed_width_error(cx).map(|error| {
+                div()
+                    .pl(px(120.))
+                    .text_color(cx.theme().danger)
+                    .child(error)
+            }))
+            .child(
+                make_labeled_row("CSV:")
+                    .child(
+                        Select::new(&self.csv_delimiter_select)
+                            .flex_grow()
+                            .render(window, cx),
+                    )
+                    .child(
+                        Select::new(&self.csv_quote_select)
+                            .flex_grow()
+                            .render(window, cx),
+                    )
+                    .child(
+                        Select::new(&self.encoding_select)
+                            .flex_grow()
+                            .render(window, cx),
+                    ),
+            )
+            .child(make_select_row(
+                "Write Mode:",
+                Select::new(&self.write_mode_select)
                     .w_full()
                     .render(window, cx),
             ))
+            .child(make_text_row(&self.key_columns, "Key Columns:"))
             .child(
                 v_flex()
                     .gap_4()
@@ -289,15 +1561,226 @@ impl Render for FileSelectionForm {
                             .label("Input Has Headers")
                             .border_2()
                             .checked(self.has_headers)
-                            .on_click(cx.listener(|view, checked, _, cx| {
+                            .on_click(cx.listener(|view, checked, window, cx| {
                                 view.has_headers = *checked;
+                                view.refresh_preview(window, cx);
                                 cx.notify();
                             })),
                     ),
             )
+            .children(self.preview_status.clone().map(|status| {
+                let color = if self.preview_has_invalid_cells {
+                    cx.theme().danger
+                } else {
+                    cx.theme().muted_foreground
+                };
+                div().text_color(color).child(status)
+            }))
+            .children(
+                self.preview_error
+                    .clone()
+                    .map(|error| div().text_color(cx.theme().danger).child(error)),
+            )
+            .children(self.preview_ruler.clone().map(|ruler| {
+                v_flex()
+                    .id("fixed-width-preview")
+                    .max_h(px(160.))
+                    .w_full()
+                    .overflow_scroll()
+                    .font_family(cx.theme().mono_font_family.clone())
+                    .whitespace_nowrap()
+                    .child(div().text_color(cx.theme().primary).child(ruler))
+                    .children(
+                        self.preview_raw_lines
+                            .iter()
+                            .map(|line| div().child(line.clone())),
+                    )
+            }))
+            .child(
+                div()
+                    .h(px(240.))
+                    .w_full()
+                    .child(Table::new(&self.preview_table)),
+            )
+            .child(self.mapping_panel.clone())
     }
 }
 
+/// Returns the folder a picker should open in, falling back to
+/// [`FALLBACK_DIALOG_DIR`] when none has been remembered.
+fn dialog_dir(remembered: Option<&Path>) -> String {
+    remembered
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_else(|| FALLBACK_DIALOG_DIR.to_string())
+}
+
+/// Creates a dropdown listing the display labels of `options`.
+fn make_label_select<T: ToString>(
+    options: &[T],
+    window: &mut Window,
+    cx: &mut Context<FileSelectionForm>,
+) -> Entity<SelectState<Vec<SharedString>>> {
+    let labels: Vec<SharedString> = options
+        .iter()
+        .map(|option| SharedString::from(option.to_string()))
+        .collect();
+
+    cx.new(|cx| SelectState::new(labels, None, window, cx))
+}
+
+/// Selects `selected` in a dropdown built by [`make_label_select`].
+fn select_label<T: PartialEq>(
+    select: &Entity<SelectState<Vec<SharedString>>>,
+    options: &[T],
+    selected: T,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let index = options
+        .iter()
+        .position(|option| *option == selected)
+        .map(|i| IndexPath::default().row(i));
+    select.update(cx, |state, cx| state.set_selected_index(index, window, cx));
+}
+
+/// Shows `path` in a text input, leaving it empty for an empty path.
+fn set_input_value(
+    input: &Entity<InputState>,
+    path: &Path,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let value = path.display().to_string();
+    input.update(cx, |state, cx| state.set_value(value, window, cx));
+}
+
+/// Creates an empty dropdown of recently used paths that copies the chosen
+/// entry into `input`.
+fn make_history_select(
+    input: &Entity<InputState>,
+    window: &mut Window,
+    cx: &mut Context<FileSelectionForm>,
+) -> (Entity<SelectState<Vec<SharedString>>>, Subscription) {
+    let select = cx.new(|cx| SelectState::new(Vec::<SharedString>::new(), None, window, cx));
+    let input = input.clone();
+    let subscription = cx.subscribe_in(
+        &select,
+        window,
+        move |_, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+            if let SelectEvent::Confirm(Some(path)) = event {
+                input.update(cx, |state, cx| state.set_value(path.clone(), window, cx));
+            }
+        },
+    );
+    (select, subscription)
+}
+
+/// Replaces the entries of a history dropdown built by
+/// [`make_history_select`].
+fn set_history(
+    select: &Entity<SelectState<Vec<SharedString>>>,
+    paths: &[PathBuf],
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let items: Vec<SharedString> = paths
+        .iter()
+        .map(|path| SharedString::from(path.display().to_string()))
+        .collect();
+    select.update(cx, |state, cx| {
+        state.set_items(items, window, cx);
+        state.set_selected_index(None, window, cx);
+    });
+}
+
+/// Summarizes a preview, e.g. "Showing 50 of 1200 rows, 3 cells fail type
+/// parsing".
+fn preview_status(preview: &DataPreview) -> SharedString {
+    let shown = match preview.total_rows {
+        Some(total) => format!("Showing {} of {total} rows", preview.rows.len()),
+        None => format!("Showing the first {} rows", preview.rows.len()),
+    };
+    let mut status = match preview.invalid_cells() {
+        0 => shown,
+        1 => format!("{shown}, 1 cell fails type parsing"),
+        n => format!("{shown}, {n} cells fail type parsing"),
+    };
+    if !preview.malformed.is_empty() {
+        let lines: Vec<String> = preview
+            .malformed
+            .iter()
+            .map(|row| row.line.to_string())
+            .collect();
+        status.push_str(&format!(", malformed rows at lines {}", lines.join(", ")));
+    }
+    if let Some(dialect) = &preview.dialect {
+        status.push_str(&format!(" (read as {dialect})"));
+    }
+    status.into()
+}
+
+/// Renders the status line of one batch or watched file.
+fn status_line(
+    item: &BatchItem,
+    cx: &App,
+) -> Div {
+    let color = match item.status {
+        FileStatus::Failed { .. } => cx.theme().danger,
+        _ => cx.theme().muted_foreground,
+    };
+    div().pl(px(120.)).text_color(color).child(item.to_string())
+}
+
+/// Reads the columns of the table the form would import into, or nothing
+/// for a new table or a backend other than SQLite.
+fn read_targets(model: &FileFormModel) -> Vec<TargetColumn> {
+    if model.db_backend != DbBackend::Sqlite || model.database_file.as_os_str().is_empty() {
+        return Vec::new();
+    }
+
+    read_table_columns(&model.database_file, &table_name(model)).unwrap_or_else(|error| {
+        warn!(%error, "Could not read target table columns");
+        Vec::new()
+    })
+}
+
+fn profile_labels(profiles: &[Profile]) -> Vec<SharedString> {
+    profiles
+        .iter()
+        .map(|profile| SharedString::from(profile.name.clone()))
+        .collect()
+}
+
+/// Lists the sheets of `source`, or nothing when it is blank or has no
+/// sheets.
+fn read_sheet_options(source: &Path) -> Result<Vec<SheetInfo>, SourceError> {
+    if source.as_os_str().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    match registry().guess(source) {
+        Some(reader) => reader.list_sheets(source),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Reads a one-based row number from `input`; a blank input is `None`.
+fn parse_row_number(
+    input: &Entity<InputState>,
+    cx: &App,
+) -> Result<Option<usize>, ParseIntError> {
+    let text = input.read(cx).value().as_str().trim().to_string();
+    if text.is_empty() {
+        Ok(None)
+    } else {
+        text.parse().map(Some)
+    }
+}
+
+fn to_shared(messages: Vec<String>) -> Vec<SharedString> {
+    messages.into_iter().map(SharedString::from).collect()
+}
+
 fn make_input_state(
     label: impl Into<SharedString>,
     window: &mut Window,
@@ -315,8 +1798,19 @@ fn make_select_row(
     make_labeled_row(label).child(select_element)
 }
 
+/// Creates a labeled row containing a free-text input with no button.
+fn make_text_row(
+    state: &Entity<InputState>,
+    label: impl Into<SharedString>,
+) -> Div {
+    make_labeled_row(label).child(Input::new(state).flex_grow())
+}
+
+/// Creates a labeled row containing a text input, an optional dropdown of
+/// recently used values and a button.
 fn make_input_row(
     state: &Entity<InputState>,
+    history: Option<&Entity<SelectState<Vec<SharedString>>>>,
     input_label: impl Into<SharedString>,
     button_id: impl Into<SharedString>,
     button_label: impl Into<SharedString>,
@@ -324,6 +1818,7 @@ fn make_input_row(
 ) -> Div {
     make_labeled_row(input_label)
         .child(Input::new(state).flex_grow())
+        .children(history.map(|history| Select::new(history).placeholder("Recent").w(px(120.))))
         .child(make_button(button_id, button_label, button_callback))
 }
 
@@ -344,6 +1839,74 @@ fn make_labeled_row(label: impl Into<SharedString>) -> Div {
         )
 }
 
+/// Creates a click handler that adds files, or a whole folder, to the batch
+/// input.
+///
+/// Picked files are appended escaped, so their names are not read as
+/// patterns; a picked folder is appended as a `folder/*` pattern, which
+/// matches the source files in it.
+fn batch_select_handler(
+    input: &Entity<InputState>,
+    directory: &str,
+    select_dir: bool,
+) -> impl Fn(&ClickEvent, &mut Window, &mut App) + 'static {
+    let input = input.clone();
+    let directory = directory.to_string();
+    let filters = owned_filters(&registry().file_filters());
+
+    move |_, window, cx| {
+        let input = input.clone();
+        let filters = filters.clone();
+        let directory = directory.clone();
+        let mut async_window = window.to_async(cx);
+        cx.spawn(async move |_async_cx| {
+            let result: anyhow::Result<()> = async {
+                let entries: Vec<String> = if select_dir {
+                    get_folder_path(directory)
+                        .await
+                        .map(|folder| {
+                            Path::new(&path_entry(&folder))
+                                .join("*")
+                                .display()
+                                .to_string()
+                        })
+                        .into_iter()
+                        .collect()
+                } else {
+                    get_file_paths(directory, filters)
+                        .await
+                        .iter()
+                        .map(|path| path_entry(path))
+                        .collect()
+                };
+                if entries.is_empty() {
+                    debug!("No batch files selected");
+                    return Ok(());
+                }
+
+                async_window.update(|window, cx| {
+                    input.update(cx, |state, cx| {
+                        let current = state.value().as_str().trim().to_string();
+                        let value = std::iter::once(current)
+                            .filter(|current| !current.is_empty())
+                            .chain(entries)
+                            .collect::<Vec<_>>()
+                            .join(&BATCH_SEPARATOR.to_string());
+                        state.set_value(value, window, cx);
+                    });
+                })?;
+
+                Ok(())
+            }
+            .await;
+
+            log_task_error("batch_select_handler", result);
+            Ok::<_, anyhow::Error>(())
+        })
+        .detach();
+    }
+}
+
 /// Creates a click handler that opens an async file dialog and populates the
 /// given input field with the selected path.
 ///
@@ -0,0 +1,311 @@
+// components/mapping_panel.rs
+
+use gpui::{
+    App, AppContext, Context, Entity, IntoElement, ParentElement, Render, SharedString, Styled,
+    Subscription, Window, div, px,
+};
+use gpui_component::{
+    ActiveTheme, IndexPath,
+    checkbox::Checkbox,
+    h_flex,
+    input::{Input, InputEvent, InputState},
+    select::{Select, SelectEvent, SelectState},
+    v_flex,
+};
+
+use crate::{
+    db::{TargetColumn, unmapped_required},
+    models::ColumnMapping,
+    schema::{ColumnSchema, MappedSchema, SqlType, TableSchema},
+};
+
+/// Type dropdown entry that keeps the inferred type.
+const INFERRED_TYPE: &str = "Inferred";
+
+/// The editors for one source column.
+struct MappingRow {
+    source: ColumnSchema,
+    target: Entity<InputState>,
+    existing: Entity<SelectState<Vec<SharedString>>>,
+    sql_type: Entity<SelectState<Vec<SharedString>>>,
+    exclude: bool,
+}
+
+impl MappingRow {
+    /// Reads the row's editors back into a mapping.
+    ///
+    /// An excluded column keeps no target, so a name typed before
+    /// excluding it does not count as a conflict.
+    fn mapping(
+        &self,
+        cx: &App,
+    ) -> ColumnMapping {
+        let target = self.target.read(cx).value().as_str().trim().to_string();
+        let selected: Option<&SharedString> = self.sql_type.read(cx).selected_value();
+        ColumnMapping {
+            source: self.source.name.clone(),
+            target: (!target.is_empty() && !self.exclude).then_some(target),
+            sql_type: selected.and_then(|label| SqlType::from_label(label.as_str())),
+            exclude: self.exclude,
+        }
+    }
+}
+
+/// Lets each previewed source column be renamed, retyped, excluded or
+/// mapped onto a column of the existing target table.
+///
+/// Required target columns that no source column is imported into are
+/// flagged below the rows.
+#[derive(Default)]
+pub struct ColumnMappingPanel {
+    source: TableSchema,
+    rows: Vec<MappingRow>,
+    /// Mappings for columns the current source does not have. They are
+    /// kept so a profile covering several header spellings keeps them all.
+    unmatched: Vec<ColumnMapping>,
+    targets: Vec<TargetColumn>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl ColumnMappingPanel {
+    /// Replaces every mapping, as when a saved form or profile is applied.
+    pub fn set_mappings(
+        &mut self,
+        mappings: &[ColumnMapping],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.unmatched = mappings.to_vec();
+        let source = std::mem::take(&mut self.source);
+        self.rebuild(source, window, cx);
+    }
+
+    /// Shows one row per column of a newly previewed source, keeping the
+    /// mapping of every column that is still there.
+    pub fn set_columns(
+        &mut self,
+        source: TableSchema,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.unmatched = self.mappings(cx);
+        self.rebuild(source, window, cx);
+    }
+
+    /// Offers the columns of the existing target table as mapping targets.
+    pub fn set_targets(
+        &mut self,
+        targets: Vec<TargetColumn>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.targets = targets;
+        let items = self.target_labels();
+        for row in &self.rows {
+            row.existing.update(cx, |state, cx| {
+                state.set_items(items.clone(), window, cx);
+                state.set_selected_index(None, window, cx);
+            });
+        }
+        cx.notify();
+    }
+
+    /// Returns the mappings that change a column, followed by the kept
+    /// mappings for columns the current source does not have.
+    pub fn mappings(
+        &self,
+        cx: &App,
+    ) -> Vec<ColumnMapping> {
+        self.rows
+            .iter()
+            .map(|row| row.mapping(cx))
+            .filter(|mapping| !mapping.is_identity())
+            .chain(self.unmatched.iter().cloned())
+            .collect()
+    }
+
+    /// Describes what would stop the mapped columns from being imported:
+    /// name clashes, and required target columns left without a source.
+    pub fn problems(
+        &self,
+        cx: &App,
+    ) -> Vec<String> {
+        if self.rows.is_empty() {
+            return Vec::new();
+        }
+
+        match MappedSchema::new(&self.source, &self.mappings(cx)) {
+            Ok(mapped) => {
+                let unmapped = unmapped_required(&self.targets, &mapped.schema);
+                if unmapped.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![
+                        format!(
+                            "Required target column(s) {} have no source column.",
+                            unmapped.join(", ")
+                        ),
+                    ]
+                }
+            }
+            Err(errors) => errors,
+        }
+    }
+
+    fn rebuild(
+        &mut self,
+        source: TableSchema,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let target_labels = self.target_labels();
+        let mut subscriptions = Vec::new();
+        let mut rows = Vec::with_capacity(source.columns.len());
+        for column in &source.columns {
+            let mapping = self
+                .unmatched
+                .iter()
+                .position(|mapping| mapping.source.trim().eq_ignore_ascii_case(&column.name))
+                .map(|index| self.unmatched.remove(index))
+                .unwrap_or_else(|| ColumnMapping::new(column.name.clone()));
+            let (row, row_subscriptions) =
+                make_mapping_row(column, &mapping, &target_labels, window, cx);
+            rows.push(row);
+            subscriptions.extend(row_subscriptions);
+        }
+
+        self.source = source;
+        self.rows = rows;
+        self._subscriptions = subscriptions;
+        cx.notify();
+    }
+
+    fn target_labels(&self) -> Vec<SharedString> {
+        self.targets
+            .iter()
+            .map(|target| {
+                let marker = if target.required { " *" } else { "" };
+                SharedString::from(format!("{}{marker}", target.name))
+            })
+            .collect()
+    }
+}
+
+impl Render for ColumnMappingPanel {
+    fn render(
+        &mut self,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let problems = self.problems(cx);
+
+        v_flex()
+            .gap_1()
+            .children(self.rows.iter().enumerate().map(|(index, row)| {
+                h_flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .w(px(200.))
+                            .child(format!("{} ({})", row.source.name, row.source.sql_type)),
+                    )
+                    .child(Input::new(&row.target).flex_grow())
+                    .child(
+                        Select::new(&row.existing)
+                            .placeholder("Existing column")
+                            .w(px(160.)),
+                    )
+                    .child(Select::new(&row.sql_type).w(px(120.)))
+                    .child(
+                        Checkbox::new(("mapping-exclude", index))
+                            .label("Exclude")
+                            .checked(row.exclude)
+                            .on_click(cx.listener(move |panel, checked, _, cx| {
+                                if let Some(row) = panel.rows.get_mut(index) {
+                                    row.exclude = *checked;
+                                }
+                                cx.notify();
+                            })),
+                    )
+            }))
+            .children(
+                problems
+                    .into_iter()
+                    .map(|problem| div().text_color(cx.theme().danger).child(problem)),
+            )
+    }
+}
+
+/// Creates the editors for `column`, filled in from `mapping`.
+///
+/// Picking an existing target column copies its name into the target
+/// input, and editing the input re-checks the required columns.
+fn make_mapping_row(
+    column: &ColumnSchema,
+    mapping: &ColumnMapping,
+    target_labels: &[SharedString],
+    window: &mut Window,
+    cx: &mut Context<ColumnMappingPanel>,
+) -> (MappingRow, Vec<Subscription>) {
+    let target_value = mapping.target.clone().unwrap_or_default();
+    let placeholder = SharedString::from(column.name.clone());
+    let target = cx.new(|cx| {
+        let mut state = InputState::new(window, cx).placeholder(placeholder);
+        state.set_value(target_value, window, cx);
+        state
+    });
+
+    let existing = cx.new(|cx| SelectState::new(target_labels.to_vec(), None, window, cx));
+
+    let type_labels: Vec<SharedString> = std::iter::once(SharedString::from(INFERRED_TYPE))
+        .chain(
+            SqlType::ALL
+                .iter()
+                .map(|sql_type| SharedString::from(sql_type.to_string())),
+        )
+        .collect();
+    let type_index = mapping
+        .sql_type
+        .and_then(|sql_type| SqlType::ALL.iter().position(|t| *t == sql_type))
+        .map_or(0, |i| i + 1);
+    let sql_type = cx.new(|cx| {
+        SelectState::new(
+            type_labels,
+            Some(IndexPath::default().row(type_index)),
+            window,
+            cx,
+        )
+    });
+
+    let copy_target = target.clone();
+    let subscriptions = vec![
+        cx.subscribe(&target, |_, _, event: &InputEvent, cx| {
+            if matches!(event, InputEvent::Change) {
+                cx.notify();
+            }
+        }),
+        cx.subscribe_in(
+            &existing,
+            window,
+            move |_, _, event: &SelectEvent<Vec<SharedString>>, window, cx| {
+                if let SelectEvent::Confirm(Some(label)) = event {
+                    let name = label.trim_end_matches(" *").to_string();
+                    copy_target.update(cx, |state, cx| state.set_value(name, window, cx));
+                }
+            },
+        ),
+        cx.subscribe(&sql_type, |_, _, _: &SelectEvent<Vec<SharedString>>, cx| {
+            cx.notify()
+        }),
+    ];
+
+    let row = MappingRow {
+        source: column.clone(),
+        target,
+        existing,
+        sql_type,
+        exclude: mapping.exclude,
+    };
+    (row, subscriptions)
+}
@@ -2,12 +2,16 @@
 
 pub mod dialogs;
 pub mod file_form;
+pub mod mapping_panel;
+pub mod preview_table;
 pub mod window;
 
 use gpui_component::Sizable;
 
-pub use dialogs::{get_file_path, owned_filters};
+pub use dialogs::{get_file_path, get_file_paths, owned_filters};
 pub use file_form::FileSelectionForm;
+pub use mapping_panel::ColumnMappingPanel;
+pub use preview_table::PreviewTableDelegate;
 pub use window::AppWindow;
 
 use gpui::{App, SharedString, Window};
@@ -0,0 +1,81 @@
+// components/preview_table.rs
+
+use gpui::{App, Context, IntoElement, ParentElement, Styled, Window, div, px};
+use gpui_component::{
+    ActiveTheme,
+    table::{Column, TableDelegate, TableState},
+};
+
+use crate::pipeline::DataPreview;
+
+/// Width of each preview column.
+const COLUMN_WIDTH: f32 = 140.;
+
+/// Feeds a [`DataPreview`] to a gpui-component table.
+///
+/// Headers show each column's inferred type, and cells that fail type
+/// parsing are highlighted.
+#[derive(Default)]
+pub struct PreviewTableDelegate {
+    preview: DataPreview,
+    columns: Vec<Column>,
+}
+
+impl PreviewTableDelegate {
+    pub fn new(preview: DataPreview) -> Self {
+        let columns = (0..preview.width())
+            .map(|i| Column::new(format!("col-{i}"), preview.header(i)).width(px(COLUMN_WIDTH)))
+            .collect();
+
+        Self { preview, columns }
+    }
+}
+
+impl TableDelegate for PreviewTableDelegate {
+    fn columns_count(
+        &self,
+        _: &App,
+    ) -> usize {
+        self.columns.len()
+    }
+
+    fn rows_count(
+        &self,
+        _: &App,
+    ) -> usize {
+        self.preview.rows.len()
+    }
+
+    fn column(
+        &self,
+        col_ix: usize,
+        _: &App,
+    ) -> &Column {
+        &self.columns[col_ix]
+    }
+
+    fn render_td(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        cx: &mut Context<TableState<Self>>,
+    ) -> impl IntoElement {
+        let cell = self
+            .preview
+            .rows
+            .get(row_ix)
+            .and_then(|row| row.get(col_ix));
+
+        match cell {
+            Some(cell) if !cell.valid => div()
+                .size_full()
+                .px_1()
+                .bg(cx.theme().danger)
+                .text_color(cx.theme().danger_foreground)
+                .child(cell.text.clone()),
+            Some(cell) => div().size_full().px_1().child(cell.text.clone()),
+            None => div(),
+        }
+    }
+}
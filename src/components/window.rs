@@ -1,12 +1,13 @@
 // components
 
 use gpui::{
-    AnyElement, App, Context, IntoElement, ParentElement, Render, Styled, Subscription, Window,
-    div,
+    AnyElement, App, Context, IntoElement, ParentElement, Render, Styled, Subscription, Window, div,
 };
 use gpui_component::StyledExt;
 use tracing::info;
 
+use crate::preferences::{WindowSettings, update_preferences, window_settings};
+
 #[cfg(not(target_os = "linux"))]
 use crate::Quit;
 #[cfg(not(target_os = "linux"))]
@@ -14,11 +15,34 @@ use crate::quit;
 
 pub struct AppWindow {
     _window_close_subscription: Subscription,
+    _bounds_subscription: Subscription,
+    _app_quit_subscription: Subscription,
+    /// Latest placement of the window, saved when the app quits.
+    placement: WindowSettings,
     content: Option<Box<dyn Fn() -> AnyElement>>,
 }
 
 impl AppWindow {
-    pub fn new(cx: &mut Context<Self>) -> Self {
+    pub fn new(
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let bounds_subscription = cx.observe_window_bounds(window, |this, window, cx| {
+            this.placement = window_settings(window, cx);
+        });
+
+        // Quitting from the menu may not close the window first, so save on
+        // both paths. Saving twice is cheap: unchanged preferences are not
+        // rewritten.
+        window.on_window_should_close(cx, |window, cx| {
+            save_placement(window_settings(window, cx), cx);
+            true
+        });
+        let app_quit_subscription = cx.on_app_quit(|this, cx| {
+            save_placement(this.placement.clone(), cx);
+            async {}
+        });
+
         let subscription = cx.on_window_closed(|_cx: &mut App| {
             info!("Window closed callback");
             #[cfg(not(target_os = "linux"))]
@@ -28,6 +52,9 @@ impl AppWindow {
         info!("Window constructed");
         Self {
             _window_close_subscription: subscription,
+            _bounds_subscription: bounds_subscription,
+            _app_quit_subscription: app_quit_subscription,
+            placement: window_settings(window, cx),
             content: None,
         }
     }
@@ -44,6 +71,13 @@ impl AppWindow {
     }
 }
 
+fn save_placement(
+    placement: WindowSettings,
+    cx: &mut App,
+) {
+    update_preferences(cx, |prefs| prefs.window = placement);
+}
+
 impl Render for AppWindow {
     fn render(
         &mut self,
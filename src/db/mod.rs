@@ -0,0 +1,82 @@
+// db
+
+pub mod sqlite;
+
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::{models::DbBackend, schema::TableSchema};
+
+/// Errors raised while writing imported rows to a target database.
+#[derive(Debug, Error)]
+pub enum DbError {
+    #[error("SQLite error on '{}': {source}", path.display())]
+    Sqlite {
+        path: PathBuf,
+        #[source]
+        source: rusqlite::Error,
+    },
+
+    #[error("The {0} backend is not supported yet.")]
+    UnsupportedBackend(DbBackend),
+
+    #[error("Table '{0}' already exists and the write mode is \"Create only\".")]
+    TableExists(String),
+
+    #[error("Table '{table}' has no column(s) {} needed by the import.", columns.join(", "))]
+    MissingColumns { table: String, columns: Vec<String> },
+
+    #[error("Key column(s) {} are not in the imported data.", .0.join(", "))]
+    UnknownKeyColumns(Vec<String>),
+
+    #[error(
+        "Table '{table}' requires column(s) {} that no source column is mapped to.",
+        columns.join(", ")
+    )]
+    UnmappedRequiredColumns { table: String, columns: Vec<String> },
+}
+
+/// A column of an existing target table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TargetColumn {
+    pub name: String,
+    /// Type as declared in the table definition, e.g. `INTEGER`.
+    pub declared_type: String,
+    /// `true` if an insert must supply a value: the column is `NOT NULL`
+    /// without a default and is not filled in by the database.
+    pub required: bool,
+}
+
+/// Returns the required target columns that `schema` has no column for,
+/// matching names case-insensitively.
+pub fn unmapped_required(
+    targets: &[TargetColumn],
+    schema: &TableSchema,
+) -> Vec<String> {
+    targets
+        .iter()
+        .filter(|target| target.required)
+        .filter(|target| {
+            !schema
+                .columns
+                .iter()
+                .any(|column| column.name.eq_ignore_ascii_case(&target.name))
+        })
+        .map(|target| target.name.clone())
+        .collect()
+}
+
+/// Quotes `name` as an SQL identifier, doubling any embedded quotes.
+///
+/// # Examples
+///
+/// ```
+/// use gpui_demo::db::quote_identifier;
+///
+/// assert_eq!(quote_identifier("Hours"), "\"Hours\"");
+/// assert_eq!(quote_identifier("a\"b"), "\"a\"\"b\"");
+/// ```
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
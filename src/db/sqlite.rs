@@ -0,0 +1,774 @@
+// db/sqlite.rs
+
+use std::path::{Path, PathBuf};
+
+use rusqlite::{
+    Connection, OpenFlags, OptionalExtension, Statement, Transaction, params_from_iter,
+    types::Value,
+};
+
+use super::{DbError, TargetColumn, quote_identifier, unmapped_required};
+use crate::{
+    models::WriteMode,
+    schema::TableSchema,
+    sources::{CellValue, Row},
+};
+
+/// Rows inserted per prepared `INSERT` statement unless configured otherwise.
+pub const DEFAULT_BATCH_SIZE: usize = 500;
+
+/// Upper bound on bound parameters per statement. SQLite's compile-time
+/// default has been 32766 since 3.32; staying below it keeps wide tables
+/// from failing with "too many SQL variables".
+const MAX_PARAMETERS: usize = 32_766;
+
+/// Writes imported rows into a SQLite database file.
+///
+/// Each call to [`SqliteWriter::write`] runs in its own transaction: the
+/// table is prepared according to the [`WriteMode`] and every batch
+/// inserted, or nothing is changed at all.
+pub struct SqliteWriter {
+    conn: Connection,
+    path: PathBuf,
+    batch_size: usize,
+    mode: WriteMode,
+    key_columns: Vec<String>,
+}
+
+impl SqliteWriter {
+    /// Opens the database at `path`, creating the file if it does not exist.
+    ///
+    /// The parent directory must already exist.
+    pub fn open(path: &Path) -> Result<Self, DbError> {
+        let conn = Connection::open(path).map_err(|source| DbError::Sqlite {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        Ok(Self {
+            conn,
+            path: path.to_path_buf(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            mode: WriteMode::default(),
+            key_columns: Vec::new(),
+        })
+    }
+
+    /// Sets how an existing target table is treated.
+    pub fn with_mode(
+        mut self,
+        mode: WriteMode,
+    ) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the columns that identify a row for [`WriteMode::UpsertByKey`].
+    pub fn with_key_columns(
+        mut self,
+        key_columns: &[String],
+    ) -> Self {
+        self.key_columns = key_columns
+            .iter()
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+            .collect();
+        self
+    }
+
+    /// Sets the number of rows bound into each `INSERT` statement.
+    ///
+    /// The effective size is capped so a statement never exceeds SQLite's
+    /// parameter limit, and is never less than one.
+    pub fn with_batch_size(
+        mut self,
+        batch_size: usize,
+    ) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Prepares `table` for the configured [`WriteMode`] and inserts `rows`.
+    ///
+    /// Rows are inserted in batches of prepared multi-row statements inside
+    /// a single transaction. Any error, including a mode check such as
+    /// [`DbError::TableExists`], rolls the whole write back. Returns the
+    /// number of rows written.
+    pub fn write(
+        &mut self,
+        table: &str,
+        schema: &TableSchema,
+        rows: &[Row],
+    ) -> Result<usize, DbError> {
+        let path = self.path.clone();
+        let sqlite_error = |source| DbError::Sqlite {
+            path: path.clone(),
+            source,
+        };
+
+        let width = schema.columns.len().max(1);
+        let batch_size = self.batch_size.min(MAX_PARAMETERS / width).max(1);
+        let upsert_keys = match self.mode {
+            WriteMode::UpsertByKey => Some(checked_keys(schema, &self.key_columns)?),
+            _ => None,
+        };
+
+        // Dropping the transaction on an early return rolls it back.
+        let tx = self.conn.transaction().map_err(sqlite_error)?;
+        let existing = existing_columns(&tx, table).map_err(sqlite_error)?;
+
+        match (self.mode, &existing) {
+            (WriteMode::CreateOnly, Some(_)) => {
+                return Err(DbError::TableExists(table.to_string()));
+            }
+            (WriteMode::DropAndRecreate, Some(_)) => {
+                tx.execute(&format!("DROP TABLE {}", quote_identifier(table)), [])
+                    .map_err(sqlite_error)?;
+            }
+            (_, Some(columns)) => {
+                check_columns(table, schema, columns)?;
+                if self.mode == WriteMode::TruncateAndReplace {
+                    tx.execute(&format!("DELETE FROM {}", quote_identifier(table)), [])
+                        .map_err(sqlite_error)?;
+                }
+            }
+            (_, None) => {}
+        }
+
+        create_table(&tx, table, schema).map_err(sqlite_error)?;
+        if let Some(keys) = &upsert_keys {
+            create_key_index(&tx, table, keys).map_err(sqlite_error)?;
+        }
+        let conflict = upsert_keys
+            .as_deref()
+            .map(|keys| conflict_clause(schema, keys))
+            .unwrap_or_default();
+        insert_batches(&tx, table, schema, rows, batch_size, &conflict).map_err(sqlite_error)?;
+        tx.commit().map_err(sqlite_error)?;
+
+        Ok(rows.len())
+    }
+}
+
+/// Reads the columns of `table` in the database at `path`.
+///
+/// Returns an empty list when the file or the table does not exist yet.
+/// The database is opened read-only, so a missing file is not created.
+pub fn read_table_columns(
+    path: &Path,
+    table: &str,
+) -> Result<Vec<TargetColumn>, DbError> {
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let sqlite_error = |source| DbError::Sqlite {
+        path: path.to_path_buf(),
+        source,
+    };
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(sqlite_error)?;
+    let columns = existing_columns(&conn, table).map_err(sqlite_error)?;
+    Ok(columns.unwrap_or_default())
+}
+
+/// Returns the columns of `table`, or `None` if it does not exist.
+fn existing_columns(
+    conn: &Connection,
+    table: &str,
+) -> rusqlite::Result<Option<Vec<TargetColumn>>> {
+    let mut stmt =
+        conn.prepare("SELECT name, type, \"notnull\", dflt_value, pk FROM pragma_table_info(?1)")?;
+    let columns = stmt
+        .query_map([table], |row| {
+            let not_null: bool = row.get(2)?;
+            let default: Option<String> = row.get(3)?;
+            let primary_key: i64 = row.get(4)?;
+            Ok((
+                TargetColumn {
+                    name: row.get(0)?,
+                    declared_type: row.get(1)?,
+                    required: not_null && default.is_none(),
+                },
+                primary_key > 0,
+            ))
+        })?
+        .collect::<rusqlite::Result<Vec<(TargetColumn, bool)>>>()?;
+    if columns.is_empty() {
+        return Ok(None);
+    }
+
+    // A single-column INTEGER PRIMARY KEY of a rowid table aliases the
+    // rowid and is filled in by SQLite when omitted.
+    let without_rowid: bool = conn
+        .query_row("SELECT wr FROM pragma_table_list(?1)", [table], |row| {
+            row.get(0)
+        })
+        .optional()?
+        .unwrap_or(false);
+    let key_columns = columns.iter().filter(|(_, key)| *key).count();
+    let aliases_rowid = |column: &TargetColumn, key: bool| {
+        key && key_columns == 1
+            && !without_rowid
+            && column.declared_type.eq_ignore_ascii_case("INTEGER")
+    };
+    Ok(Some(
+        columns
+            .into_iter()
+            .map(|(mut column, key)| {
+                column.required &= !aliases_rowid(&column, key);
+                column
+            })
+            .collect(),
+    ))
+}
+
+/// Fails if the existing table lacks any column of the imported schema, or
+/// requires a column the schema does not provide.
+fn check_columns(
+    table: &str,
+    schema: &TableSchema,
+    existing: &[TargetColumn],
+) -> Result<(), DbError> {
+    let missing: Vec<String> = schema
+        .columns
+        .iter()
+        .filter(|column| {
+            !existing
+                .iter()
+                .any(|target| target.name.eq_ignore_ascii_case(&column.name))
+        })
+        .map(|column| column.name.clone())
+        .collect();
+    if !missing.is_empty() {
+        return Err(DbError::MissingColumns {
+            table: table.to_string(),
+            columns: missing,
+        });
+    }
+
+    let unmapped = unmapped_required(existing, schema);
+    if unmapped.is_empty() {
+        Ok(())
+    } else {
+        Err(DbError::UnmappedRequiredColumns {
+            table: table.to_string(),
+            columns: unmapped,
+        })
+    }
+}
+
+/// Resolves upsert key names against the schema, matching case-insensitively.
+fn checked_keys(
+    schema: &TableSchema,
+    key_columns: &[String],
+) -> Result<Vec<String>, DbError> {
+    let mut keys = Vec::with_capacity(key_columns.len());
+    let mut unknown = Vec::new();
+    for key in key_columns {
+        match schema
+            .columns
+            .iter()
+            .find(|column| column.name.eq_ignore_ascii_case(key))
+        {
+            Some(column) => keys.push(column.name.clone()),
+            None => unknown.push(key.clone()),
+        }
+    }
+
+    if keys.is_empty() || !unknown.is_empty() {
+        return Err(DbError::UnknownKeyColumns(unknown));
+    }
+    Ok(keys)
+}
+
+/// Ensures a unique index exists over the key columns, which SQLite needs
+/// before it accepts an `ON CONFLICT` target.
+///
+/// The index is named after the table, so an index left by an upsert over
+/// other key columns is dropped and built again over `keys`.
+fn create_key_index(
+    tx: &Transaction<'_>,
+    table: &str,
+    keys: &[String],
+) -> rusqlite::Result<()> {
+    let name = format!("{table}_upsert_key");
+    let index = quote_identifier(&name);
+    let indexed: Vec<String> = tx
+        .prepare("SELECT name FROM pragma_index_info(?1) ORDER BY seqno")?
+        .query_map([&name], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    let unchanged = indexed.len() == keys.len()
+        && indexed
+            .iter()
+            .zip(keys)
+            .all(|(column, key)| column.eq_ignore_ascii_case(key));
+    if unchanged {
+        return Ok(());
+    }
+    if !indexed.is_empty() {
+        tx.execute(&format!("DROP INDEX {index}"), [])?;
+    }
+
+    let key_list: Vec<String> = keys.iter().map(|key| quote_identifier(key)).collect();
+    tx.execute(
+        &format!(
+            "CREATE UNIQUE INDEX {index} ON {} ({})",
+            quote_identifier(table),
+            key_list.join(", ")
+        ),
+        [],
+    )?;
+    Ok(())
+}
+
+/// Builds the `ON CONFLICT ... DO UPDATE` clause for an upsert.
+fn conflict_clause(
+    schema: &TableSchema,
+    keys: &[String],
+) -> String {
+    let key_list: Vec<String> = keys.iter().map(|key| quote_identifier(key)).collect();
+    let updates: Vec<String> = schema
+        .columns
+        .iter()
+        .filter(|column| !keys.contains(&column.name))
+        .map(|column| {
+            let name = quote_identifier(&column.name);
+            format!("{name} = excluded.{name}")
+        })
+        .collect();
+
+    if updates.is_empty() {
+        format!(" ON CONFLICT ({}) DO NOTHING", key_list.join(", "))
+    } else {
+        format!(
+            " ON CONFLICT ({}) DO UPDATE SET {}",
+            key_list.join(", "),
+            updates.join(", ")
+        )
+    }
+}
+
+fn create_table(
+    tx: &Transaction<'_>,
+    table: &str,
+    schema: &TableSchema,
+) -> rusqlite::Result<()> {
+    let column_defs: Vec<String> = schema
+        .columns
+        .iter()
+        .map(|column| {
+            format!(
+                "{} {}",
+                quote_identifier(&column.name),
+                column.sql_type.sql_name()
+            )
+        })
+        .collect();
+
+    tx.execute(
+        &format!(
+            "CREATE TABLE IF NOT EXISTS {} ({})",
+            quote_identifier(table),
+            column_defs.join(", ")
+        ),
+        [],
+    )?;
+    Ok(())
+}
+
+fn insert_batches(
+    tx: &Transaction<'_>,
+    table: &str,
+    schema: &TableSchema,
+    rows: &[Row],
+    batch_size: usize,
+    conflict: &str,
+) -> rusqlite::Result<()> {
+    let width = schema.columns.len();
+    if width == 0 {
+        return Ok(());
+    }
+
+    let mut full_batch = prepare_insert(tx, table, schema, batch_size, conflict)?;
+    let mut chunks = rows.chunks_exact(batch_size);
+    for chunk in chunks.by_ref() {
+        full_batch.execute(params_from_iter(batch_values(chunk, width)))?;
+    }
+
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        let mut tail = prepare_insert(tx, table, schema, remainder.len(), conflict)?;
+        tail.execute(params_from_iter(batch_values(remainder, width)))?;
+    }
+    Ok(())
+}
+
+/// Prepares `INSERT INTO table (...) VALUES (?, ...), (?, ...)` for `rows`
+/// rows, followed by the optional upsert `conflict` clause.
+fn prepare_insert<'tx>(
+    tx: &'tx Transaction<'_>,
+    table: &str,
+    schema: &TableSchema,
+    rows: usize,
+    conflict: &str,
+) -> rusqlite::Result<Statement<'tx>> {
+    let column_list: Vec<String> = schema
+        .columns
+        .iter()
+        .map(|column| quote_identifier(&column.name))
+        .collect();
+    let row_placeholders = format!("({})", vec!["?"; column_list.len()].join(", "));
+    let values = vec![row_placeholders.as_str(); rows].join(", ");
+
+    tx.prepare(&format!(
+        "INSERT INTO {} ({}) VALUES {values}{conflict}",
+        quote_identifier(table),
+        column_list.join(", ")
+    ))
+}
+
+fn batch_values(
+    rows: &[Row],
+    width: usize,
+) -> impl Iterator<Item = Value> + '_ {
+    rows.iter()
+        .flat_map(move |row| (0..width).map(move |i| to_sql_value(row.get(i))))
+}
+
+/// Converts a source cell into the value bound to an insert parameter.
+///
+/// Dates, timestamps and durations are stored as ISO-style text, which is
+/// what SQLite's date functions expect.
+fn to_sql_value(cell: Option<&CellValue>) -> Value {
+    match cell {
+        None | Some(CellValue::Empty) => Value::Null,
+        Some(CellValue::Int(value)) => Value::Integer(*value),
+        Some(CellValue::Float(value)) => Value::Real(*value),
+        Some(CellValue::Bool(value)) => Value::Integer(i64::from(*value)),
+        Some(CellValue::Text(value)) => Value::Text(value.clone()),
+        Some(other) => Value::Text(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{ColumnSchema, SqlType};
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn schema() -> TableSchema {
+        TableSchema::new(vec![
+            ColumnSchema::new("name", SqlType::Text, false),
+            ColumnSchema::new("hours", SqlType::Real, true),
+        ])
+    }
+
+    fn row(
+        name: &str,
+        hours: f64,
+    ) -> Row {
+        vec![
+            CellValue::Text(name.to_string()),
+            CellValue::Float(hours),
+        ]
+    }
+
+    fn count_rows(path: &Path) -> i64 {
+        let conn = Connection::open(path).expect("reopen");
+        conn.query_row("SELECT COUNT(*) FROM hours", [], |r| r.get(0))
+            .expect("count")
+    }
+
+    #[test]
+    fn test_open_creates_missing_file() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("new.db");
+        SqliteWriter::open(&path).expect("open");
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_open_missing_directory_fails() {
+        let dir = TempDir::new().expect("temp dir");
+        let result = SqliteWriter::open(&dir.path().join("missing/new.db"));
+        assert!(
+            matches!(result, Err(DbError::Sqlite { .. })),
+            "expected Sqlite error"
+        );
+    }
+
+    #[test]
+    fn test_write_spans_batches_and_remainder() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("out.db");
+        let rows: Vec<Row> = (0..7).map(|i| row("Ada", f64::from(i))).collect();
+
+        let mut writer = SqliteWriter::open(&path).expect("open").with_batch_size(3);
+        let written = writer.write("hours", &schema(), &rows).expect("write");
+
+        assert_eq!((written, count_rows(&path)), (7, 7));
+    }
+
+    #[test]
+    fn test_write_declares_schema_types() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("out.db");
+        let mut writer = SqliteWriter::open(&path).expect("open");
+        writer.write("hours", &schema(), &[]).expect("write");
+
+        let conn = Connection::open(&path).expect("reopen");
+        let declared: String = conn
+            .query_row(
+                "SELECT type FROM pragma_table_info('hours') WHERE name = 'hours'",
+                [],
+                |r| r.get(0),
+            )
+            .expect("pragma");
+        assert_eq!(declared, "REAL");
+    }
+
+    fn write_twice(
+        mode: WriteMode,
+        keys: &[String],
+        second: &[Row],
+    ) -> (Result<usize, DbError>, PathBuf, TempDir) {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("out.db");
+        SqliteWriter::open(&path)
+            .expect("open")
+            .write("hours", &schema(), &[row("Ada", 1.0)])
+            .expect("first write");
+
+        let result = SqliteWriter::open(&path)
+            .expect("open")
+            .with_mode(mode)
+            .with_key_columns(keys)
+            .write("hours", &schema(), second);
+        (result, path, dir)
+    }
+
+    #[test]
+    fn test_write_create_only_fails_when_table_exists() {
+        let (result, path, _dir) = write_twice(WriteMode::CreateOnly, &[], &[row("Grace", 2.0)]);
+        assert!(
+            matches!(result, Err(DbError::TableExists(_))),
+            "expected TableExists, got: {result:?}"
+        );
+        assert_eq!(count_rows(&path), 1);
+    }
+
+    #[test]
+    fn test_write_append_keeps_existing_rows() {
+        let (result, path, _dir) = write_twice(WriteMode::Append, &[], &[row("Grace", 2.0)]);
+        assert!(result.is_ok());
+        assert_eq!(count_rows(&path), 2);
+    }
+
+    #[test]
+    fn test_write_truncate_replaces_rows() {
+        let (result, path, _dir) =
+            write_twice(WriteMode::TruncateAndReplace, &[], &[row("Grace", 2.0)]);
+        assert!(result.is_ok());
+        assert_eq!(count_rows(&path), 1);
+    }
+
+    #[test]
+    fn test_write_drop_and_recreate_changes_schema() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("out.db");
+        Connection::open(&path)
+            .expect("open")
+            .execute("CREATE TABLE hours (legacy TEXT)", [])
+            .expect("create");
+
+        let result = SqliteWriter::open(&path)
+            .expect("open")
+            .with_mode(WriteMode::DropAndRecreate)
+            .write("hours", &schema(), &[row("Ada", 1.0)]);
+
+        assert!(result.is_ok(), "expected success, got: {result:?}");
+        assert_eq!(count_rows(&path), 1);
+    }
+
+    #[test]
+    fn test_write_append_reports_missing_columns() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("out.db");
+        Connection::open(&path)
+            .expect("open")
+            .execute("CREATE TABLE hours (name TEXT)", [])
+            .expect("create");
+
+        let result =
+            SqliteWriter::open(&path)
+                .expect("open")
+                .write("hours", &schema(), &[row("Ada", 1.0)]);
+
+        assert!(
+            matches!(&result, Err(DbError::MissingColumns { columns, .. }) if columns == &["hours"]),
+            "expected MissingColumns, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_write_append_reports_unmapped_required_columns() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("out.db");
+        Connection::open(&path)
+            .expect("open")
+            .execute(
+                "CREATE TABLE hours (id INTEGER PRIMARY KEY, employee_id INTEGER NOT NULL, \
+                 name TEXT, hours REAL, approved BOOLEAN NOT NULL DEFAULT 0)",
+                [],
+            )
+            .expect("create");
+
+        let result =
+            SqliteWriter::open(&path)
+                .expect("open")
+                .write("hours", &schema(), &[row("Ada", 1.0)]);
+
+        assert!(
+            matches!(&result, Err(DbError::UnmappedRequiredColumns { columns, .. }) if columns == &["employee_id"]),
+            "expected UnmappedRequiredColumns, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_read_table_columns_marks_required() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("out.db");
+        assert!(read_table_columns(&path, "hours").expect("read").is_empty());
+        assert!(!path.exists());
+
+        Connection::open(&path)
+            .expect("open")
+            .execute(
+                "CREATE TABLE hours (id INTEGER PRIMARY KEY, employee_id INTEGER NOT NULL)",
+                [],
+            )
+            .expect("create");
+
+        assert_eq!(
+            read_table_columns(&path, "hours").expect("read"),
+            vec![
+                TargetColumn {
+                    name: "id".to_string(),
+                    declared_type: "INTEGER".to_string(),
+                    required: false,
+                },
+                TargetColumn {
+                    name: "employee_id".to_string(),
+                    declared_type: "INTEGER".to_string(),
+                    required: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_table_columns_requires_keys_that_are_not_rowids() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("out.db");
+        let conn = Connection::open(&path).expect("open");
+        conn.execute_batch(
+            "CREATE TABLE shifts (employee INTEGER NOT NULL, day INTEGER NOT NULL, \
+                 PRIMARY KEY (employee, day));
+             CREATE TABLE badges (id INTEGER NOT NULL PRIMARY KEY) WITHOUT ROWID;",
+        )
+        .expect("create");
+
+        let required = |table: &str| -> Vec<bool> {
+            read_table_columns(&path, table)
+                .expect("read")
+                .iter()
+                .map(|column| column.required)
+                .collect()
+        };
+
+        assert_eq!(
+            required("shifts"),
+            vec![
+                true, true,
+            ]
+        );
+        assert_eq!(required("badges"), vec![true]);
+    }
+
+    #[test]
+    fn test_write_upsert_updates_matching_keys() {
+        let keys = vec!["name".to_string()];
+        let (result, path, _dir) = write_twice(
+            WriteMode::UpsertByKey,
+            &keys,
+            &[
+                row("Ada", 9.0),
+                row("Grace", 2.0),
+            ],
+        );
+        assert!(result.is_ok(), "expected success, got: {result:?}");
+
+        let conn = Connection::open(&path).expect("reopen");
+        let hours: f64 = conn
+            .query_row("SELECT hours FROM hours WHERE name = 'Ada'", [], |r| {
+                r.get(0)
+            })
+            .expect("row");
+        assert_eq!((count_rows(&path), hours), (2, 9.0));
+    }
+
+    #[test]
+    fn test_write_upsert_rebuilds_index_for_new_keys() {
+        let (result, path, _dir) = write_twice(
+            WriteMode::UpsertByKey,
+            &["name".to_string()],
+            &[row("Ada", 2.0)],
+        );
+        assert!(result.is_ok(), "expected success, got: {result:?}");
+
+        let result = SqliteWriter::open(&path)
+            .expect("open")
+            .with_mode(WriteMode::UpsertByKey)
+            .with_key_columns(&[
+                "name".to_string(),
+                "hours".to_string(),
+            ])
+            .write("hours", &schema(), &[row("Ada", 3.0)]);
+
+        assert!(result.is_ok(), "expected success, got: {result:?}");
+        assert_eq!(count_rows(&path), 2);
+    }
+
+    #[test]
+    fn test_write_upsert_unknown_key() {
+        let keys = vec!["employee_id".to_string()];
+        let (result, _path, _dir) = write_twice(WriteMode::UpsertByKey, &keys, &[]);
+        assert!(
+            matches!(result, Err(DbError::UnknownKeyColumns(_))),
+            "expected UnknownKeyColumns, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_write_rolls_back_on_error() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("out.db");
+        Connection::open(&path)
+            .expect("open")
+            .execute("CREATE TABLE hours (name TEXT UNIQUE, hours REAL)", [])
+            .expect("create");
+        let rows = vec![
+            row("Ada", 1.0),
+            row("Grace", 2.0),
+            row("Ada", 3.0),
+        ];
+
+        let mut writer = SqliteWriter::open(&path).expect("open").with_batch_size(2);
+        let result = writer.write("hours", &schema(), &rows);
+
+        assert!(result.is_err(), "expected unique constraint failure");
+        assert_eq!(count_rows(&path), 0);
+    }
+}
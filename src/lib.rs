@@ -1,12 +1,20 @@
+pub mod cli;
 pub mod components;
+pub mod db;
 pub mod logging;
 pub mod models;
+pub mod pipeline;
 pub mod platform;
 pub mod preferences;
+pub mod profiles;
+pub mod schema;
+pub mod sources;
+
+use std::path::PathBuf;
 
 use gpui::{
-    AnyElement, App, AppContext, Context, InteractiveElement, IntoElement, KeyBinding, Menu,
-    MenuItem, ParentElement, Styled, Window, actions,
+    Action, AnyElement, App, AppContext, Context, InteractiveElement, IntoElement, KeyBinding,
+    Menu, MenuItem, ParentElement, Styled, Window, actions,
 };
 use gpui_component::{h_flex, v_flex};
 use tracing::{info, warn};
@@ -16,8 +24,22 @@ use crate::components::{FileSelectionForm, make_button};
 use crate::platform::apply_linux_system_theme;
 #[cfg(target_os = "macos")]
 use crate::platform::apply_macos_system_theme;
-
-actions!(gpui_demo, [Quit]);
+use crate::preferences::{PreferencesStore, current_preferences, update_preferences};
+
+actions!(
+    gpui_demo,
+    [
+        Quit,
+        ClearRecent
+    ]
+);
+
+/// Loads a recently used source file into the form.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = gpui_demo, no_json)]
+pub struct OpenRecent {
+    pub path: PathBuf,
+}
 
 // Takes a reference to the action (often unused) and mutable app context
 pub fn quit(
@@ -51,16 +73,55 @@ pub fn setup_app(app_cx: &mut App) {
 
     // Register the quit action handler
     app_cx.on_action(quit);
+    app_cx.on_action(clear_recent);
+
+    update_preferences(app_cx, |prefs| prefs.recent.prune_missing());
+    refresh_menus(app_cx);
+    app_cx
+        .observe_global::<PreferencesStore>(refresh_menus)
+        .detach();
+}
+
+/// Rebuilds the application menus, including "File > Open Recent" from the
+/// recent source files in the preferences.
+///
+/// Runs again whenever the preferences change.
+fn refresh_menus(cx: &mut App) {
+    let mut recent_items: Vec<MenuItem> = current_preferences(cx)
+        .recent
+        .source_files
+        .into_iter()
+        .map(|path| MenuItem::action(path.display().to_string(), OpenRecent { path }))
+        .collect();
+    if !recent_items.is_empty() {
+        recent_items.push(MenuItem::separator());
+    }
+    recent_items.push(MenuItem::action("Clear Recent", ClearRecent));
 
-    // Set up the application menu with Quit
-    app_cx.set_menus(vec![
+    cx.set_menus(vec![
         Menu {
             name: "TimeKeeper Loader".into(),
             items: vec![MenuItem::action("Quit", Quit)],
         },
+        Menu {
+            name: "File".into(),
+            items: vec![
+                MenuItem::submenu(Menu {
+                    name: "Open Recent".into(),
+                    items: recent_items,
+                }),
+            ],
+        },
     ]);
 }
 
+fn clear_recent(
+    _: &ClearRecent,
+    cx: &mut App,
+) {
+    update_preferences(cx, |prefs| prefs.recent.clear());
+}
+
 /// Builds the primary window content.
 ///
 /// Returns a closure suitable for passing to `Window::set_content`,
@@ -72,6 +133,19 @@ pub fn build_main_content(
     let form = app_cx
         .new(|form_cx: &mut Context<FileSelectionForm>| FileSelectionForm::new(window, form_cx));
 
+    let window_handle = window.window_handle();
+    let recent_form = form.downgrade();
+    app_cx.on_action(move |action: &OpenRecent, cx: &mut App| {
+        let result = window_handle.update(cx, |_, window, cx| {
+            recent_form.update(cx, |form, form_cx| {
+                form.open_source(&action.path, window, form_cx)
+            })
+        });
+        if let Err(error) = result.and_then(|updated| updated) {
+            warn!(%error, "Could not open recent file");
+        }
+    });
+
     move || {
         v_flex()
             .size_full()
@@ -88,33 +162,24 @@ pub fn build_main_content(
                     .child({
                         let form_handle = form.clone();
                         make_button("ok-go", "Convert Files", move |_, _, cx: &mut App| {
-                            let form_model = form_handle.read(cx).to_model(cx);
-                            match form_model.validate_for_submit() {
+                            form_handle
+                                .update(cx, |form, form_cx| form.refresh_missing_paths(form_cx));
+                            let form = form_handle.read(cx);
+                            let form_model = form.to_model(cx);
+                            match form.validate_for_submit(&form_model, cx) {
                                 Ok(()) => {
-                                    // Apply level first so subsequent calls in this session use it.
-                                    if let Err(e) = logging::set_log_level(&form_model.log_level.to_string()) {
-                                        warn!("Could not apply log level: {e}");
-                                    }
-
-                                    // Wire up file logging if a directory was provided.
-                                    if !form_model.log_directory.as_os_str().is_empty() {
-                                        // app_name() is "gpui_demo" (or whatever the exe is called).
-                                        // The file is opened with append(true) so repeated clicks accumulate.
-                                        let log_path = form_model
-                                            .log_directory
-                                            .join(format!("{}.log", logging::app_name()));
-                                        if let Err(e) = logging::enable_file_logging(&log_path) {
-                                            warn!("Could not open log file: {e}");
-                                        }
-                                    }
-
-                                    // Honor the user's stdout preference.
-                                    if let Err(e) = logging::set_stdout_enabled(form_model.log_stdout) {
-                                        warn!("Could not configure stdout logging: {e}");
-                                    }
-
+                                    logging::configure_logging(&form_model);
                                     info!(%form_model, "Form validated");
-                                    // Next step: pass validated model to the processing crate.
+                                    update_preferences(cx, |prefs| {
+                                        prefs.remember_model(&form_model)
+                                    });
+                                    form_handle.update(cx, |form, form_cx| {
+                                        if form_model.is_batch() {
+                                            form.start_batch(form_model, form_cx)
+                                        } else {
+                                            form.start_import(form_model, form_cx)
+                                        }
+                                    });
                                 }
                                 Err(errors) => {
                                     warn!("Cannot submit form due to validation errors");
@@ -131,15 +196,9 @@ pub fn build_main_content(
                             "load-sheets",
                             "Load Sheets",
                             move |_, window, cx: &mut App| {
-                                let form_model = form_handle.read(cx).to_model(cx);
-                                let sheets = form_handle.read(cx).load_sheet_options(cx);
-                                info!(
-                                    source_file = %form_model.source_file.display(),
-                                    sheet_count = sheets.len(),
-                                    "Loaded sheet options"
-                                );
                                 form_handle.update(cx, |form, form_cx| {
-                                    form.set_sheet_options(sheets, window, form_cx);
+                                    form.refresh_missing_paths(form_cx);
+                                    form.load_sheets(None, window, form_cx);
                                 });
                             },
                         )
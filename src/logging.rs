@@ -1,3 +1,4 @@
+use crate::models::{FileFormModel, LogLevel};
 use anyhow::Result;
 use chrono::Local;
 use std::{
@@ -6,7 +7,7 @@ use std::{
     path::Path,
     sync::{Arc, Mutex, MutexGuard, OnceLock},
 };
-use tracing::{error, Event, Level, Subscriber};
+use tracing::{error, warn, Event, Level, Subscriber};
 use tracing_subscriber::{
     fmt::{
         format::{FormatEvent, FormatFields, Writer},
@@ -199,7 +200,7 @@ pub fn disable_file_logging() {
 /// Returns the process name derived from the executable path.
 /// Initialised on first call; always returns the same value.
 /// Falls back to "app" if the path cannot be determined.
-/// Exposed so a future preferences store can read it without re-deriving it.
+/// Also names the preferences directory under the user's config folder.
 pub fn app_name() -> &'static str {
     APP_NAME.get_or_init(|| {
         std::env::current_exe()
@@ -248,6 +249,41 @@ pub fn init_default_logging() {
     }
 }
 
+/// Applies the logging choices captured in a form model.
+///
+/// Sets the level and the stdout preference first, so the remaining calls
+/// in this session, including a warning about the log file, honour them,
+/// then opens `<log_directory>/<app_name>.log` in append mode when a
+/// directory is given. Failures are logged as warnings rather than
+/// aborting the caller.
+pub fn configure_logging(model: &FileFormModel) {
+    if let Err(e) = set_log_level(&model.log_level.to_string()) {
+        warn!("Could not apply log level: {e}");
+    }
+
+    if let Err(e) = set_stdout_enabled(model.log_stdout) {
+        warn!("Could not configure stdout logging: {e}");
+    }
+
+    if !model.log_directory.as_os_str().is_empty() {
+        let log_path = model.log_directory.join(format!("{}.log", app_name()));
+        if let Err(e) = enable_file_logging(&log_path) {
+            warn!("Could not open log file: {e}");
+        }
+    }
+}
+
+/// Applies the log level saved in the preferences at startup, unless the
+/// `RUST_LOG` environment variable chose the filter.
+pub fn apply_saved_log_level(level: LogLevel) {
+    if std::env::var_os("RUST_LOG").is_some() {
+        return;
+    }
+    if let Err(e) = set_log_level(&level.to_string()) {
+        warn!("Could not apply saved log level: {e}");
+    }
+}
+
 /// Logs a background task failure with context.
 pub fn log_task_error(task_name: &'static str, result: Result<()>) {
     if let Err(error) = result {
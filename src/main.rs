@@ -1,19 +1,30 @@
+use std::process::ExitCode;
+
+use clap::Parser;
 use gpui::{
-    App, AppContext, Application, Bounds, Context, TitlebarOptions, WindowBounds,
-    WindowDecorations, WindowHandle, WindowOptions,
+    App, AppContext, Application, Context, TitlebarOptions, WindowDecorations, WindowHandle,
+    WindowOptions,
 };
 
 use gpui_component::Root;
 use gpui_component_assets::Assets;
 use gpui_demo::build_main_content;
 use gpui_demo::{
-    components::AppWindow as MainWindow, logging::init_default_logging, logging::log_task_error,
-    preferences::WindowPreferences, setup_app,
+    cli::{Cli, Command, run_import_command},
+    components::AppWindow as MainWindow,
+    logging::{apply_saved_log_level, init_default_logging, log_task_error},
+    preferences::{PreferencesStore, WindowPreferences},
+    setup_app,
 };
 
-fn main() {
+fn main() -> ExitCode {
     init_default_logging();
 
+    let cli = Cli::parse();
+    if let Some(Command::Import(args)) = &cli.command {
+        return run_import_command(args).into();
+    }
+
     #[cfg(target_os = "linux")]
     {
         let is_gnome = std::env::var("XDG_CURRENT_DESKTOP")
@@ -37,11 +48,14 @@ fn main() {
 
     let app = Application::new().with_assets(Assets);
 
+    let store = PreferencesStore::load_default();
+    apply_saved_log_level(store.preferences().log_level);
+    let prefs = WindowPreferences::from(store.preferences());
+
     app.run(move |app_cx: &mut App| {
+        app_cx.set_global(store);
         setup_app(app_cx);
 
-        let prefs = WindowPreferences::default();
-
         let titlebar = Some(TitlebarOptions {
             title: Some("TimeKeeper Loader".into()),
             appears_transparent: false,
@@ -51,12 +65,13 @@ fn main() {
         app_cx
             .spawn(async move |async_cx| {
                 let result: anyhow::Result<()> = async {
-                    let bounds = async_cx
-                        .update(|app_cx: &mut App| Bounds::centered(None, prefs.size, app_cx))?;
+                    let (window_bounds, display_id) =
+                        async_cx.update(|app_cx: &mut App| prefs.window_bounds(app_cx))?;
 
                     let _window_handle: WindowHandle<Root> = async_cx.open_window(
                         WindowOptions {
-                            window_bounds: Some(WindowBounds::Windowed(bounds)),
+                            window_bounds: Some(window_bounds),
+                            display_id,
                             titlebar,
                             window_decorations: Some(WindowDecorations::Server),
                             ..Default::default()
@@ -64,7 +79,7 @@ fn main() {
                         |window: &mut gpui::Window, window_cx| {
                             let view = window_cx.new(|view_cx: &mut Context<MainWindow>| {
                                 let content = build_main_content(window, view_cx);
-                                let mut main_window = MainWindow::new(view_cx);
+                                let mut main_window = MainWindow::new(window, view_cx);
                                 main_window.set_content(content);
                                 main_window
                             });
@@ -81,4 +96,6 @@ fn main() {
             })
             .detach();
     });
+
+    ExitCode::SUCCESS
 }
@@ -0,0 +1,191 @@
+// models/column_mapping.rs
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::schema::SqlType;
+
+/// How one source column is written to the target table.
+///
+/// Source spreadsheets often label the same data differently ("Emp ID",
+/// "Employee #"); a mapping renames such a column onto the target table's
+/// name for it, forces its type, or leaves it out of the import.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct ColumnMapping {
+    /// Column name as read from the source header (or `column_N`).
+    pub source: String,
+    /// Name of the target column; `None` keeps the source name.
+    pub target: Option<String>,
+    /// Type to import the column as; `None` keeps the inferred type.
+    pub sql_type: Option<SqlType>,
+    /// Leaves the column out of the import.
+    pub exclude: bool,
+}
+
+impl ColumnMapping {
+    /// A mapping that imports the column `source` as it is.
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the target column name.
+    pub fn to(
+        mut self,
+        target: impl Into<String>,
+    ) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Forces the column's type.
+    pub fn with_type(
+        mut self,
+        sql_type: SqlType,
+    ) -> Self {
+        self.sql_type = Some(sql_type);
+        self
+    }
+
+    /// Leaves the column out of the import.
+    pub fn excluded(mut self) -> Self {
+        self.exclude = true;
+        self
+    }
+
+    /// Returns the name the column is written under: the trimmed target, or
+    /// the source name when no target is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpui_demo::models::ColumnMapping;
+    ///
+    /// assert_eq!(ColumnMapping::new("Emp ID").to(" employee_id ").target_name(), "employee_id");
+    /// assert_eq!(ColumnMapping::new("Hours").to("").target_name(), "Hours");
+    /// ```
+    pub fn target_name(&self) -> &str {
+        self.target
+            .as_deref()
+            .map(str::trim)
+            .filter(|target| !target.is_empty())
+            .unwrap_or(&self.source)
+    }
+
+    /// Returns `true` if the mapping changes nothing about its column.
+    pub fn is_identity(&self) -> bool {
+        !self.exclude && self.sql_type.is_none() && self.target_name() == self.source
+    }
+}
+
+impl fmt::Display for ColumnMapping {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        if self.exclude {
+            return write!(f, "{} (excluded)", self.source);
+        }
+        write!(f, "{} → {}", self.source, self.target_name())?;
+        match self.sql_type {
+            Some(sql_type) => write!(f, " ({sql_type})"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks a set of mappings for conflicts that do not depend on the source
+/// data.
+///
+/// Reports mappings without a source column, source columns mapped more
+/// than once and excluded columns that also name a target. Several sources
+/// may share a target, so one profile can cover every spelling of a header;
+/// two of them appearing in the same source is caught when the mappings are
+/// applied.
+pub fn validate_mappings(mappings: &[ColumnMapping]) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut sources: Vec<&str> = Vec::new();
+
+    for mapping in mappings {
+        let source = mapping.source.trim();
+        if source.is_empty() {
+            errors.push("A column mapping has no source column.".to_string());
+            continue;
+        }
+        if sources.iter().any(|seen| seen.eq_ignore_ascii_case(source)) {
+            errors.push(format!("Column '{source}' is mapped more than once."));
+            continue;
+        }
+        sources.push(source);
+
+        if mapping.exclude && mapping.target_name() != mapping.source {
+            errors.push(format!(
+                "Column '{source}' is excluded but also mapped to '{}'.",
+                mapping.target_name()
+            ));
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_display_describes_mapping() {
+        assert_eq!(
+            ColumnMapping::new("Emp ID")
+                .to("employee_id")
+                .with_type(SqlType::Integer)
+                .to_string(),
+            "Emp ID → employee_id (Integer)"
+        );
+        assert_eq!(
+            ColumnMapping::new("Notes").excluded().to_string(),
+            "Notes (excluded)"
+        );
+    }
+
+    #[test]
+    fn test_is_identity() {
+        assert!(ColumnMapping::new("Hours").to("Hours").is_identity());
+        assert!(!ColumnMapping::new("Hours").to("hours").is_identity());
+        assert!(!ColumnMapping::new("Hours").excluded().is_identity());
+    }
+
+    #[test]
+    fn test_validate_mappings_reports_conflicts() {
+        let mappings = vec![
+            ColumnMapping::new("Emp ID").to("employee_id"),
+            ColumnMapping::new("emp id").excluded(),
+            ColumnMapping::new("Notes").to("remarks").excluded(),
+            ColumnMapping::new(" "),
+        ];
+
+        assert_eq!(
+            validate_mappings(&mappings),
+            vec![
+                "Column 'emp id' is mapped more than once.".to_string(),
+                "Column 'Notes' is excluded but also mapped to 'remarks'.".to_string(),
+                "A column mapping has no source column.".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_mappings_allows_shared_targets() {
+        let mappings = vec![
+            ColumnMapping::new("Emp ID").to("employee_id"),
+            ColumnMapping::new("Employee #").to("employee_id"),
+        ];
+
+        assert!(validate_mappings(&mappings).is_empty());
+    }
+}
@@ -1,8 +1,20 @@
 // models/file_form_model.rs
 
-use std::{fmt, path::PathBuf};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
 
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ColumnMapping, FixedWidthColumn, fixed_width::layout_mappings, format_layout, validate_layout,
+    validate_mappings,
+};
+use crate::sources::{CellRange, ReadOptions, SourceFormat, registry};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum DbBackend {
     #[default]
     Sqlite,
@@ -19,6 +31,21 @@ pub enum DbBackend {
 }
 
 impl DbBackend {
+    /// All backends in the order they are offered in the form.
+    pub const ALL: [Self; 11] = [
+        Self::Sqlite,
+        Self::MySql,
+        Self::Db2,
+        Self::PostgreSql,
+        Self::MariaDb,
+        Self::MsSql,
+        Self::Redis,
+        Self::Aws,
+        Self::Azure,
+        Self::GoogleCloud,
+        Self::Apache,
+    ];
+
     pub fn from_label(label: &str) -> Option<Self> {
         match label {
             "SQLite" => Some(Self::Sqlite),
@@ -37,6 +64,20 @@ impl DbBackend {
     }
 }
 
+impl TryFrom<String> for DbBackend {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, String> {
+        Self::from_label(&label).ok_or_else(|| format!("unknown database backend '{label}'"))
+    }
+}
+
+impl From<DbBackend> for String {
+    fn from(value: DbBackend) -> Self {
+        value.to_string()
+    }
+}
+
 impl fmt::Display for DbBackend {
     fn fmt(
         &self,
@@ -59,7 +100,8 @@ impl fmt::Display for DbBackend {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
 pub enum LogLevel {
     Error,
     Warn,
@@ -70,6 +112,15 @@ pub enum LogLevel {
 }
 
 impl LogLevel {
+    /// All levels from least to most verbose.
+    pub const ALL: [Self; 5] = [
+        Self::Error,
+        Self::Warn,
+        Self::Info,
+        Self::Debug,
+        Self::Trace,
+    ];
+
     pub fn from_label(label: &str) -> Option<Self> {
         match label {
             "ERROR" => Some(Self::Error),
@@ -82,6 +133,20 @@ impl LogLevel {
     }
 }
 
+impl TryFrom<String> for LogLevel {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, String> {
+        Self::from_label(&label).ok_or_else(|| format!("unknown log level '{label}'"))
+    }
+}
+
+impl From<LogLevel> for String {
+    fn from(value: LogLevel) -> Self {
+        value.to_string()
+    }
+}
+
 impl fmt::Display for LogLevel {
     fn fmt(
         &self,
@@ -98,42 +163,514 @@ impl fmt::Display for LogLevel {
     }
 }
 
+/// What happens to the target table when an import writes into it.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum WriteMode {
+    /// Creates the table; fails if it already exists.
+    CreateOnly,
+    /// Adds rows to the table, creating it if needed.
+    #[default]
+    Append,
+    /// Deletes all existing rows, keeping the table definition.
+    TruncateAndReplace,
+    /// Drops the table and creates it again from the inferred schema.
+    DropAndRecreate,
+    /// Inserts new rows and updates rows whose key columns already exist.
+    UpsertByKey,
+}
+
+impl WriteMode {
+    /// All modes in the order they are offered in the form.
+    pub const ALL: [Self; 5] = [
+        Self::CreateOnly,
+        Self::Append,
+        Self::TruncateAndReplace,
+        Self::DropAndRecreate,
+        Self::UpsertByKey,
+    ];
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "Create only" => Some(Self::CreateOnly),
+            "Append" => Some(Self::Append),
+            "Truncate and replace" => Some(Self::TruncateAndReplace),
+            "Drop and recreate" => Some(Self::DropAndRecreate),
+            "Upsert by key" => Some(Self::UpsertByKey),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for WriteMode {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, String> {
+        Self::from_label(&label).ok_or_else(|| format!("unknown write mode '{label}'"))
+    }
+}
+
+impl From<WriteMode> for String {
+    fn from(value: WriteMode) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for WriteMode {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let label = match self {
+            Self::CreateOnly => "Create only",
+            Self::Append => "Append",
+            Self::TruncateAndReplace => "Truncate and replace",
+            Self::DropAndRecreate => "Drop and recreate",
+            Self::UpsertByKey => "Upsert by key",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// Where the rows of several imported sheets go.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum SheetTarget {
+    /// Each sheet is written to a table named after the sheet.
+    #[default]
+    TablePerSheet,
+    /// All sheets are appended to one table named after the workbook, with
+    /// a column recording the sheet each row came from.
+    SingleTable,
+}
+
+impl SheetTarget {
+    /// All targets in the order they are offered in the form.
+    pub const ALL: [Self; 2] = [
+        Self::TablePerSheet,
+        Self::SingleTable,
+    ];
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "One table per sheet" => Some(Self::TablePerSheet),
+            "One table for all sheets" => Some(Self::SingleTable),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for SheetTarget {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, String> {
+        Self::from_label(&label).ok_or_else(|| format!("unknown sheet target '{label}'"))
+    }
+}
+
+impl From<SheetTarget> for String {
+    fn from(value: SheetTarget) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for SheetTarget {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let label = match self {
+            Self::TablePerSheet => "One table per sheet",
+            Self::SingleTable => "One table for all sheets",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// What a batch import does when one of its files fails.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum FailurePolicy {
+    /// Records the failure and goes on with the next file.
+    #[default]
+    ContinueOnError,
+    /// Skips every file after the first failure.
+    StopOnFirstFailure,
+}
+
+impl FailurePolicy {
+    /// All policies in the order they are offered in the form.
+    pub const ALL: [Self; 2] = [
+        Self::ContinueOnError,
+        Self::StopOnFirstFailure,
+    ];
+
+    /// Returns the policy shown as `label` in the form, as saved in
+    /// profiles.
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "Continue on error" => Some(Self::ContinueOnError),
+            "Stop on first failure" => Some(Self::StopOnFirstFailure),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for FailurePolicy {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, String> {
+        Self::from_label(&label).ok_or_else(|| format!("unknown failure policy '{label}'"))
+    }
+}
+
+impl From<FailurePolicy> for String {
+    fn from(value: FailurePolicy) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for FailurePolicy {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let label = match self {
+            Self::ContinueOnError => "Continue on error",
+            Self::StopOnFirstFailure => "Stop on first failure",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// Field delimiter of a CSV source.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum CsvDelimiter {
+    /// Sniffed from the start of the file.
+    #[default]
+    Auto,
+    Comma,
+    Semicolon,
+    Tab,
+    Pipe,
+}
+
+impl CsvDelimiter {
+    /// All delimiters in the order they are offered in the form.
+    pub const ALL: [Self; 5] = [
+        Self::Auto,
+        Self::Comma,
+        Self::Semicolon,
+        Self::Tab,
+        Self::Pipe,
+    ];
+
+    /// Returns the delimiter shown as `label` in the form, such as
+    /// `Semicolon`.
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "Detect delimiter" => Some(Self::Auto),
+            "Comma" => Some(Self::Comma),
+            "Semicolon" => Some(Self::Semicolon),
+            "Tab" => Some(Self::Tab),
+            "Pipe" => Some(Self::Pipe),
+            _ => None,
+        }
+    }
+
+    /// Returns the delimiter byte, or `None` when it is to be sniffed.
+    pub fn byte(self) -> Option<u8> {
+        match self {
+            Self::Auto => None,
+            Self::Comma => Some(b','),
+            Self::Semicolon => Some(b';'),
+            Self::Tab => Some(b'\t'),
+            Self::Pipe => Some(b'|'),
+        }
+    }
+}
+
+impl TryFrom<String> for CsvDelimiter {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, String> {
+        Self::from_label(&label).ok_or_else(|| format!("unknown CSV delimiter '{label}'"))
+    }
+}
+
+impl From<CsvDelimiter> for String {
+    fn from(value: CsvDelimiter) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for CsvDelimiter {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let label = match self {
+            Self::Auto => "Detect delimiter",
+            Self::Comma => "Comma",
+            Self::Semicolon => "Semicolon",
+            Self::Tab => "Tab",
+            Self::Pipe => "Pipe",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// Quote character of a CSV source.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum CsvQuote {
+    /// Sniffed from the start of the file.
+    #[default]
+    Auto,
+    DoubleQuote,
+    SingleQuote,
+    /// Quote characters are kept as part of the field text.
+    NoQuoting,
+}
+
+impl CsvQuote {
+    /// All quote settings in the order they are offered in the form.
+    pub const ALL: [Self; 4] = [
+        Self::Auto,
+        Self::DoubleQuote,
+        Self::SingleQuote,
+        Self::NoQuoting,
+    ];
+
+    /// Returns the quote setting shown as `label` in the form, such as
+    /// `No quoting`.
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "Detect quote" => Some(Self::Auto),
+            "Double quote" => Some(Self::DoubleQuote),
+            "Single quote" => Some(Self::SingleQuote),
+            "No quoting" => Some(Self::NoQuoting),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for CsvQuote {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, String> {
+        Self::from_label(&label).ok_or_else(|| format!("unknown CSV quote '{label}'"))
+    }
+}
+
+impl From<CsvQuote> for String {
+    fn from(value: CsvQuote) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for CsvQuote {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let label = match self {
+            Self::Auto => "Detect quote",
+            Self::DoubleQuote => "Double quote",
+            Self::SingleQuote => "Single quote",
+            Self::NoQuoting => "No quoting",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// Character encoding of a text source.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum TextEncoding {
+    /// Detected from a byte order mark, or guessed from the contents.
+    #[default]
+    Auto,
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Windows1252,
+    /// ISO-8859-1, where every byte is the code point of the same value.
+    Latin1,
+}
+
+impl TextEncoding {
+    /// All encodings in the order they are offered in the form.
+    pub const ALL: [Self; 6] = [
+        Self::Auto,
+        Self::Utf8,
+        Self::Utf16Le,
+        Self::Utf16Be,
+        Self::Windows1252,
+        Self::Latin1,
+    ];
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "Detect encoding" => Some(Self::Auto),
+            "UTF-8" => Some(Self::Utf8),
+            "UTF-16 LE" => Some(Self::Utf16Le),
+            "UTF-16 BE" => Some(Self::Utf16Be),
+            "Windows-1252" => Some(Self::Windows1252),
+            "Latin-1" => Some(Self::Latin1),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for TextEncoding {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, String> {
+        Self::from_label(&label).ok_or_else(|| format!("unknown text encoding '{label}'"))
+    }
+}
+
+impl From<TextEncoding> for String {
+    fn from(value: TextEncoding) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for TextEncoding {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let label = match self {
+            Self::Auto => "Detect encoding",
+            Self::Utf8 => "UTF-8",
+            Self::Utf16Le => "UTF-16 LE",
+            Self::Utf16Be => "UTF-16 BE",
+            Self::Windows1252 => "Windows-1252",
+            Self::Latin1 => "Latin-1",
+        };
+        write!(f, "{label}")
+    }
+}
+
 /// Represents the collected values from the file selection form.
-#[derive(Clone, Debug, Default)]
+///
+/// Missing keys deserialize to their defaults so saved snapshots from older
+/// builds still load.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
 pub struct FileFormModel {
     pub source_file: PathBuf,
+    /// Files and glob patterns (such as `timesheets/*.xlsx`) imported as one
+    /// batch with these settings; empty imports `source_file` alone.
+    pub batch_sources: Vec<String>,
+    pub failure_policy: FailurePolicy,
+    /// Folder watched for new or changed source files to import
+    /// automatically; empty when not watching.
+    pub watch_folder: PathBuf,
+    /// File name pattern (such as `*.xlsx`) a watched file must match;
+    /// `None` accepts every file a source reader handles.
+    pub watch_pattern: Option<String>,
     pub database_file: PathBuf,
     pub log_directory: PathBuf,
     pub db_backend: DbBackend,
     pub log_level: LogLevel,
+    /// Sheet shown in the preview, and the sheet imported when no others
+    /// are chosen.
     pub selected_sheet: Option<String>,
+    /// Sheets to import instead of `selected_sheet`.
+    pub import_sheets: Vec<String>,
+    /// Imports every visible sheet of the workbook, overriding
+    /// `import_sheets`.
+    pub all_visible_sheets: bool,
+    pub sheet_target: SheetTarget,
     pub log_stdout: bool,
     pub has_headers: bool,
+    /// One-based row holding the column names; `None` uses the first row
+    /// of the range or sheet.
+    pub header_row: Option<usize>,
+    /// One-based row of the first data row; `None` starts below the header.
+    pub data_start_row: Option<usize>,
+    /// Rows dropped from the end of the data, such as a totals footer.
+    pub skip_trailing_rows: usize,
+    /// A1-style block holding the table, e.g. `B4:K200`.
+    pub cell_range: Option<String>,
+    /// Delimiter of CSV sources; sniffed unless set.
+    pub csv_delimiter: CsvDelimiter,
+    /// Quote character of CSV sources; sniffed unless set.
+    pub csv_quote: CsvQuote,
+    /// Encoding of text sources; detected unless set.
+    pub encoding: TextEncoding,
+    pub write_mode: WriteMode,
+    /// Columns identifying a row for [`WriteMode::UpsertByKey`].
+    pub key_columns: Vec<String>,
+    /// Number of data rows sampled for column type inference; `None` uses
+    /// the default sample size.
+    pub sample_rows: Option<usize>,
+    /// Renames, retypes or excludes source columns; columns without a
+    /// mapping are imported as inferred.
+    pub column_mappings: Vec<ColumnMapping>,
+    /// Columns cut out of each line of a fixed-width text source; a
+    /// layout reads a text source as fixed-width whatever its extension.
+    pub fixed_width_columns: Vec<FixedWidthColumn>,
+    /// Format of the source file, set by
+    /// [`FileFormModel::detect_source_format`] when the source changes;
+    /// `None` until then, or when no reader handles the file, in which case
+    /// the predicates detect it themselves. Not saved.
+    #[serde(skip)]
+    pub source_format: Option<SourceFormat>,
 }
 
 impl FileFormModel {
-    /// Returns `true` if the source file has an Excel extension.
-    pub fn is_excel(&self) -> bool {
-        matches!(
-            self.source_file
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ext.to_ascii_lowercase())
-                .as_deref(),
-            Some("xlsx" | "xlsm" | "xlsb" | "xls")
-        )
+    /// Recognizes the format of the source file by its contents, or else by
+    /// its extension, and stores it in `source_format` so the predicates
+    /// below need not read the file each time.
+    pub fn detect_source_format(&mut self) {
+        self.source_format = self.guess_source_format();
+    }
+
+    /// Returns the model with its source format detected (see
+    /// [`FileFormModel::detect_source_format`]).
+    pub fn with_detected_format(mut self) -> Self {
+        self.detect_source_format();
+        self
+    }
+
+    /// Returns `true` if the source file is a workbook, such as `.xlsx`,
+    /// `.xls` or `.ods`.
+    pub fn is_workbook(&self) -> bool {
+        self.format() == Some(SourceFormat::Workbook)
+    }
+
+    /// Returns `true` if the form describes a batch of source files.
+    pub fn is_batch(&self) -> bool {
+        self.batch_sources
+            .iter()
+            .any(|source| !source.trim().is_empty())
     }
 
-    /// Returns `true` if the source file has an CSV extension.
+    /// Returns `true` if more than the previewed sheet is chosen for import.
+    pub fn imports_several_sheets(&self) -> bool {
+        self.all_visible_sheets || !self.import_sheets.is_empty()
+    }
+
+    /// Returns `true` if the source format may hold several sheets (see
+    /// [`SourceFormat::has_sheets`]).
+    pub fn has_sheets(&self) -> bool {
+        self.format().is_some_and(SourceFormat::has_sheets)
+    }
+
+    /// Returns `true` if the source file is a CSV file.
     pub fn is_csv(&self) -> bool {
-        matches!(
-            self.source_file
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ext.to_ascii_lowercase())
-                .as_deref(),
-            Some("csv")
-        )
+        self.format() == Some(SourceFormat::Csv)
+    }
+
+    /// Returns the column mappings with the types forced by the fixed-width
+    /// layout added; a type chosen in a mapping wins.
+    pub fn effective_mappings(&self) -> Vec<ColumnMapping> {
+        layout_mappings(&self.fixed_width_columns, &self.column_mappings)
     }
 
     /// Returns `true` if the database file has a SQLite extension.
@@ -148,16 +685,61 @@ impl FileFormModel {
         )
     }
 
+    /// Describes each filled-in path that no longer exists on disk.
+    ///
+    /// The database file itself may be missing, since SQLite creates it on
+    /// the first import; only a missing parent folder is reported for it.
+    pub fn missing_paths(&self) -> Vec<String> {
+        let mut missing = Vec::new();
+
+        if is_missing(&self.source_file) {
+            missing.push(format!(
+                "Source file '{}' no longer exists.",
+                self.source_file.display()
+            ));
+        }
+
+        if let Some(parent) = self
+            .database_file
+            .parent()
+            .filter(|parent| is_missing(parent))
+        {
+            missing.push(format!(
+                "Database folder '{}' no longer exists.",
+                parent.display()
+            ));
+        }
+
+        if is_missing(&self.log_directory) {
+            missing.push(format!(
+                "Log folder '{}' no longer exists.",
+                self.log_directory.display()
+            ));
+        }
+
+        missing
+    }
+
     /// Validates that the model has all required values for submission.
     ///
     /// Rules:
-    /// - source file is required
+    /// - source file is required, unless batch sources are given
     /// - database file is required
-    /// - selected sheet is required only for Excel source files
+    /// - a sheet selection (the selected sheet, a list of sheets or all
+    ///   visible sheets) is required only for workbooks (Excel or
+    ///   OpenDocument), and only workbooks, JSON documents and zip archives
+    ///   accept more than one sheet; batch patterns are checked by their
+    ///   extension
+    /// - upsert needs at least one key column, and key columns are only
+    ///   accepted for upsert
+    /// - row numbers start at 1, a header row needs headers to be enabled
+    ///   and data must start below the header
+    /// - the cell range must be valid A1 notation
+    /// - column mappings must not conflict (see [`validate_mappings`])
     pub fn validate_for_submit(&self) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
 
-        if self.source_file.as_os_str().is_empty() {
+        if self.source_file.as_os_str().is_empty() && !self.is_batch() {
             errors.push("Source file is required.".to_string());
         }
 
@@ -165,7 +747,9 @@ impl FileFormModel {
             errors.push("Database file is required.".to_string());
         }
 
-        if self.is_excel()
+        let formats = self.submitted_formats();
+        if formats.contains(&Some(SourceFormat::Workbook))
+            && !self.imports_several_sheets()
             && self
                 .selected_sheet
                 .as_deref()
@@ -173,8 +757,54 @@ impl FileFormModel {
                 .filter(|sheet| !sheet.is_empty())
                 .is_none()
         {
-            errors.push("Sheet selection is required for Excel sources.".to_string());
+            errors.push("Sheet selection is required for workbook sources.".to_string());
+        }
+
+        if self
+            .import_sheets
+            .iter()
+            .any(|sheet| sheet.trim().is_empty())
+        {
+            errors.push("Sheet names to import must not be blank.".to_string());
+        }
+        if self.imports_several_sheets()
+            && formats
+                .iter()
+                .any(|format| !format.is_some_and(SourceFormat::has_sheets))
+        {
+            errors.push(
+                "Only workbooks, JSON documents and zip archives have several sheets to import."
+                    .to_string(),
+            );
+        }
+
+        let has_keys = self.key_columns.iter().any(|key| !key.trim().is_empty());
+        match self.write_mode {
+            WriteMode::UpsertByKey if !has_keys => {
+                errors.push("Upsert by key requires at least one key column.".to_string());
+            }
+            WriteMode::UpsertByKey => {}
+            mode if has_keys => {
+                errors.push(format!(
+                    "Key columns are only used by \"Upsert by key\", not \"{mode}\"."
+                ));
+            }
+            _ => {}
+        }
+
+        errors.extend(self.layout_errors());
+        errors.extend(validate_mappings(&self.column_mappings));
+        if self.fixed_width_columns.is_empty() && formats.contains(&Some(SourceFormat::FixedWidth))
+        {
+            errors.push("Fixed-width text sources need a column layout.".to_string());
+        }
+        if !self.fixed_width_columns.is_empty() {
+            let not_text = formats.iter().flatten().find(|format| !format.is_text());
+            errors.extend(not_text.map(|format| {
+                format!("A fixed-width layout only applies to text sources, not to {format} files.")
+            }));
         }
+        errors.extend(validate_layout(&self.fixed_width_columns));
 
         if errors.is_empty() {
             Ok(())
@@ -182,6 +812,98 @@ impl FileFormModel {
             Err(errors)
         }
     }
+
+    /// Validates the model as the files arriving in its watch folder would
+    /// be imported: like [`FileFormModel::validate_for_submit`], with the
+    /// watch pattern in place of the source file.
+    pub fn validate_for_watch(&self) -> Result<(), Vec<String>> {
+        let pattern = self
+            .watch_pattern
+            .as_deref()
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .unwrap_or("*");
+        Self {
+            source_file: PathBuf::new(),
+            batch_sources: vec![self.watch_folder.join(pattern).display().to_string()],
+            ..self.clone()
+        }
+        .validate_for_submit()
+    }
+}
+
+impl FileFormModel {
+    /// Format of the source file: `source_format`, or the format detected
+    /// now for a model built without detecting it, such as one loaded from
+    /// a profile.
+    fn format(&self) -> Option<SourceFormat> {
+        self.source_format.or_else(|| self.guess_source_format())
+    }
+
+    fn guess_source_format(&self) -> Option<SourceFormat> {
+        let options = ReadOptions {
+            fixed_width: self.fixed_width_columns.clone(),
+            ..ReadOptions::default()
+        };
+        registry()
+            .guess_with(&self.source_file, &options)
+            .map(|reader| reader.format())
+    }
+
+    /// Formats of the sources an import reads: that of the source file, or
+    /// those of the batch entries going by their extensions, leaving out
+    /// entries without a known one.
+    fn submitted_formats(&self) -> Vec<Option<SourceFormat>> {
+        if !self.is_batch() {
+            return vec![self.format()];
+        }
+        self.batch_sources
+            .iter()
+            .filter_map(|source| registry().by_extension(Path::new(source.trim())))
+            .map(|reader| Some(reader.format()))
+            .collect()
+    }
+
+    /// Checks the header row, data start row and cell range.
+    fn layout_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.header_row == Some(0) || self.data_start_row == Some(0) {
+            errors.push("Row numbers start at 1.".to_string());
+        }
+        if self.header_row.is_some() && !self.has_headers {
+            errors.push("A header row is set but the input has no headers.".to_string());
+        }
+
+        let range = self
+            .cell_range
+            .as_deref()
+            .filter(|range| !range.trim().is_empty())
+            .map(str::parse::<CellRange>);
+        // Without a header row the header is the first row of the range,
+        // or of the sheet, which is row 1 at the earliest.
+        let header = self.header_row.unwrap_or(match &range {
+            Some(Ok(range)) => range.first_row + 1,
+            _ => 1,
+        });
+        match self.data_start_row {
+            Some(start) if self.has_headers && start <= header => {
+                errors.push(format!(
+                    "Data must start below the header row ({header}), not at row {start}."
+                ));
+            }
+            _ => {}
+        }
+        if let Some(Err(error)) = range {
+            errors.push(error.to_string());
+        }
+
+        errors
+    }
+}
+
+fn is_missing(path: &Path) -> bool {
+    !path.as_os_str().is_empty() && !path.exists()
 }
 
 impl fmt::Display for FileFormModel {
@@ -190,6 +912,18 @@ impl fmt::Display for FileFormModel {
         f: &mut fmt::Formatter<'_>,
     ) -> fmt::Result {
         writeln!(f, "Source file:   {}", self.source_file.to_string_lossy())?;
+        if self.is_batch() {
+            writeln!(f, "Batch:         {}", self.batch_sources.join("; "))?;
+            writeln!(f, "On failure:    {}", self.failure_policy)?;
+        }
+        if !self.watch_folder.as_os_str().is_empty() {
+            writeln!(
+                f,
+                "Watch folder:  {} ({})",
+                self.watch_folder.to_string_lossy(),
+                self.watch_pattern.as_deref().unwrap_or("all sources")
+            )?;
+        }
         writeln!(f, "Database:      {}", self.database_file.to_string_lossy())?;
         writeln!(f, "Log folder:    {}", self.log_directory.to_string_lossy())?;
         writeln!(f, "DB Backend:    {}", self.db_backend)?;
@@ -199,14 +933,69 @@ impl fmt::Display for FileFormModel {
             "Sheet:         {}",
             self.selected_sheet.as_deref().unwrap_or("(none)")
         )?;
+        if self.all_visible_sheets {
+            writeln!(f, "Import sheets: (all visible)")?;
+        } else if !self.import_sheets.is_empty() {
+            writeln!(f, "Import sheets: {}", self.import_sheets.join(", "))?;
+        }
+        if self.imports_several_sheets() {
+            writeln!(f, "Sheet target:  {}", self.sheet_target)?;
+        }
         writeln!(f, "Log to stdout: {}", self.log_stdout)?;
-        write!(f, "Has headers:   {}", self.has_headers)
+        writeln!(f, "Has headers:   {}", self.has_headers)?;
+        if let Some(row) = self.header_row {
+            writeln!(f, "Header row:    {row}")?;
+        }
+        if let Some(row) = self.data_start_row {
+            writeln!(f, "Data starts:   {row}")?;
+        }
+        if self.skip_trailing_rows > 0 {
+            writeln!(f, "Skip trailing: {}", self.skip_trailing_rows)?;
+        }
+        if let Some(range) = &self.cell_range {
+            writeln!(f, "Cell range:    {range}")?;
+        }
+        if self.csv_delimiter != CsvDelimiter::Auto || self.csv_quote != CsvQuote::Auto {
+            writeln!(
+                f,
+                "CSV dialect:   {}, {}",
+                self.csv_delimiter, self.csv_quote
+            )?;
+        }
+        if self.encoding != TextEncoding::Auto {
+            writeln!(f, "Encoding:      {}", self.encoding)?;
+        }
+        writeln!(f, "Write mode:    {}", self.write_mode)?;
+        if !self.key_columns.is_empty() {
+            writeln!(f, "Key columns:   {}", self.key_columns.join(", "))?;
+        }
+        if !self.column_mappings.is_empty() {
+            let mappings: Vec<String> = self
+                .column_mappings
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+            writeln!(f, "Mappings:      {}", mappings.join(", "))?;
+        }
+        if !self.fixed_width_columns.is_empty() {
+            writeln!(
+                f,
+                "Fixed width:   {}",
+                format_layout(&self.fixed_width_columns)
+            )?;
+        }
+        match self.sample_rows {
+            Some(rows) => write!(f, "Sample rows:   {rows}"),
+            None => write!(f, "Sample rows:   (default)"),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
+    use tempfile::TempDir;
 
     #[test]
     fn test_default_values() {
@@ -223,13 +1012,36 @@ mod tests {
     fn test_display_populated() {
         let model = FileFormModel {
             source_file: PathBuf::from("data.xlsx"),
+            batch_sources: vec!["timesheets/*.xlsx".to_string()],
+            failure_policy: FailurePolicy::StopOnFirstFailure,
+            watch_folder: PathBuf::from("inbox"),
+            watch_pattern: Some("*.xlsx".to_string()),
             database_file: PathBuf::from("app.db"),
             log_directory: PathBuf::from("output.log"),
             db_backend: DbBackend::MySql,
             log_level: LogLevel::Info,
             selected_sheet: Some("Sheet1".to_string()),
+            import_sheets: vec![
+                "Sheet1".to_string(),
+                "Sheet2".to_string(),
+            ],
+            all_visible_sheets: false,
+            sheet_target: SheetTarget::SingleTable,
             log_stdout: true,
             has_headers: true,
+            write_mode: WriteMode::UpsertByKey,
+            key_columns: vec!["employee_id".to_string()],
+            header_row: Some(3),
+            data_start_row: Some(5),
+            skip_trailing_rows: 1,
+            cell_range: Some("B3:K200".to_string()),
+            csv_delimiter: CsvDelimiter::Semicolon,
+            csv_quote: CsvQuote::Auto,
+            encoding: TextEncoding::Windows1252,
+            sample_rows: Some(250),
+            column_mappings: vec![ColumnMapping::new("Emp ID").to("employee_id")],
+            fixed_width_columns: vec![FixedWidthColumn::new("hours", 14, 5)],
+            source_format: Some(SourceFormat::Workbook),
         };
         let output = model.to_string();
         assert!(output.contains("data.xlsx"));
@@ -239,6 +1051,19 @@ mod tests {
         assert!(output.contains("INFO"));
         assert!(output.contains("Sheet1"));
         assert!(output.contains("true"));
+        assert!(output.contains("250"));
+        assert!(output.contains("Upsert by key"));
+        assert!(output.contains("employee_id"));
+        assert!(output.contains("Emp ID → employee_id"));
+        assert!(output.contains("B3:K200"));
+        assert!(output.contains("Sheet1, Sheet2"));
+        assert!(output.contains("One table for all sheets"));
+        assert!(output.contains("timesheets/*.xlsx"));
+        assert!(output.contains("Stop on first failure"));
+        assert!(output.contains("inbox (*.xlsx)"));
+        assert!(output.contains("Semicolon, Detect quote"));
+        assert!(output.contains("Windows-1252"));
+        assert!(output.contains("hours:14:5"));
     }
 
     #[test]
@@ -277,6 +1102,43 @@ mod tests {
         assert!(model.validate_for_submit().is_ok());
     }
 
+    #[test]
+    fn test_validate_for_submit_all_sheets_needs_no_selection() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.xlsx"),
+            database_file: PathBuf::from("app.db"),
+            all_visible_sheets: true,
+            ..FileFormModel::default()
+        };
+
+        assert!(model.validate_for_submit().is_ok());
+    }
+
+    #[test]
+    fn test_validate_for_submit_several_sheets_need_workbook() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.csv"),
+            database_file: PathBuf::from("app.db"),
+            import_sheets: vec![
+                "Hours".to_string(),
+                " ".to_string(),
+            ],
+            ..FileFormModel::default()
+        };
+
+        let errors = model
+            .validate_for_submit()
+            .expect_err("expected validation errors");
+        assert_eq!(
+            errors,
+            vec![
+                "Sheet names to import must not be blank.".to_string(),
+                "Only workbooks, JSON documents and zip archives have several sheets to import."
+                    .to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn test_validate_for_submit_csv_without_sheet_is_valid() {
         let model = FileFormModel {
@@ -316,6 +1178,310 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_for_submit_batch_needs_no_source_file() {
+        let model = FileFormModel {
+            batch_sources: vec!["timesheets/*.csv".to_string()],
+            database_file: PathBuf::from("app.db"),
+            ..FileFormModel::default()
+        };
+
+        assert!(model.validate_for_submit().is_ok());
+    }
+
+    #[test]
+    fn test_validate_for_submit_checks_batch_patterns() {
+        let model = FileFormModel {
+            batch_sources: vec![
+                "timesheets/*.xlsx".to_string(),
+                "exports/*".to_string(),
+            ],
+            database_file: PathBuf::from("app.db"),
+            ..FileFormModel::default()
+        };
+        let csv_sheets = FileFormModel {
+            batch_sources: vec!["timesheets/*.csv".to_string()],
+            all_visible_sheets: true,
+            ..model.clone()
+        };
+
+        assert_eq!(
+            model.validate_for_submit(),
+            Err(vec![
+                "Sheet selection is required for workbook sources.".to_string(),
+            ])
+        );
+        assert!(
+            FileFormModel {
+                all_visible_sheets: true,
+                ..model
+            }
+            .validate_for_submit()
+            .is_ok()
+        );
+        assert_eq!(
+            csv_sheets.validate_for_submit(),
+            Err(vec![
+                "Only workbooks, JSON documents and zip archives have several sheets to import."
+                    .to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_for_submit_upsert_requires_key() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.csv"),
+            database_file: PathBuf::from("app.db"),
+            write_mode: WriteMode::UpsertByKey,
+            key_columns: vec![" ".to_string()],
+            ..FileFormModel::default()
+        };
+
+        let errors = model
+            .validate_for_submit()
+            .expect_err("expected validation error");
+        assert!(
+            errors
+                .iter()
+                .any(|err| err.contains("requires at least one key column")),
+            "expected key column validation error, got: {errors:?}"
+        );
+    }
+
+    #[test]
+    fn test_validate_for_submit_upsert_with_key_is_valid() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.csv"),
+            database_file: PathBuf::from("app.db"),
+            write_mode: WriteMode::UpsertByKey,
+            key_columns: vec!["employee_id".to_string()],
+            ..FileFormModel::default()
+        };
+
+        assert!(model.validate_for_submit().is_ok());
+    }
+
+    #[test]
+    fn test_validate_for_submit_keys_rejected_outside_upsert() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.csv"),
+            database_file: PathBuf::from("app.db"),
+            write_mode: WriteMode::Append,
+            key_columns: vec!["employee_id".to_string()],
+            ..FileFormModel::default()
+        };
+
+        let errors = model
+            .validate_for_submit()
+            .expect_err("expected validation error");
+        assert!(
+            errors
+                .iter()
+                .any(|err| err.contains("only used by \"Upsert by key\"")),
+            "expected key column validation error, got: {errors:?}"
+        );
+    }
+
+    #[test]
+    fn test_validate_for_submit_checks_mappings() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.csv"),
+            database_file: PathBuf::from("app.db"),
+            column_mappings: vec![
+                ColumnMapping::new("Emp ID").to("employee_id"),
+                ColumnMapping::new("Emp ID").excluded(),
+            ],
+            ..FileFormModel::default()
+        };
+
+        let errors = model
+            .validate_for_submit()
+            .expect_err("expected validation error");
+        assert!(
+            errors
+                .iter()
+                .any(|err| err.contains("mapped more than once")),
+            "expected mapping validation error, got: {errors:?}"
+        );
+    }
+
+    #[test]
+    fn test_fixed_width_layout_decides_format() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("punches.csv"),
+            database_file: PathBuf::from("app.db"),
+            fixed_width_columns: vec![
+                FixedWidthColumn::new("employee", 1, 5),
+                FixedWidthColumn::new("hours", 5, 5),
+            ],
+            ..FileFormModel::default()
+        }
+        .with_detected_format();
+
+        assert_eq!(model.source_format, Some(SourceFormat::FixedWidth));
+        assert_eq!(
+            model.validate_for_submit(),
+            Err(vec![
+                "Fixed-width columns 'employee' and 'hours' overlap.".to_string(),
+            ])
+        );
+
+        let workbook = FileFormModel {
+            source_file: PathBuf::from("punches.xlsx"),
+            selected_sheet: Some("Hours".to_string()),
+            fixed_width_columns: vec![FixedWidthColumn::new("employee", 1, 5)],
+            ..model
+        }
+        .with_detected_format();
+        assert_eq!(workbook.source_format, Some(SourceFormat::Workbook));
+        assert_eq!(
+            workbook.validate_for_submit(),
+            Err(vec![
+                "A fixed-width layout only applies to text sources, not to Spreadsheet files."
+                    .to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_for_submit_checks_layout() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.csv"),
+            database_file: PathBuf::from("app.db"),
+            has_headers: true,
+            header_row: Some(4),
+            data_start_row: Some(4),
+            cell_range: Some("B4:".to_string()),
+            ..FileFormModel::default()
+        };
+
+        let errors = model
+            .validate_for_submit()
+            .expect_err("expected validation errors");
+        assert_eq!(
+            errors,
+            vec![
+                "Data must start below the header row (4), not at row 4.".to_string(),
+                "'B4:' is not a cell range such as B4:K200.".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_for_submit_data_below_implicit_header() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.csv"),
+            database_file: PathBuf::from("app.db"),
+            has_headers: true,
+            data_start_row: Some(1),
+            ..FileFormModel::default()
+        };
+        let ranged = FileFormModel {
+            data_start_row: Some(3),
+            cell_range: Some("B3:K200".to_string()),
+            ..model.clone()
+        };
+
+        assert_eq!(
+            model.validate_for_submit(),
+            Err(vec![
+                "Data must start below the header row (1), not at row 1.".to_string(),
+            ])
+        );
+        assert_eq!(
+            ranged.validate_for_submit(),
+            Err(vec![
+                "Data must start below the header row (3), not at row 3.".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_for_submit_header_row_needs_headers() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.csv"),
+            database_file: PathBuf::from("app.db"),
+            header_row: Some(3),
+            cell_range: Some("B4:K200".to_string()),
+            ..FileFormModel::default()
+        };
+
+        let errors = model
+            .validate_for_submit()
+            .expect_err("expected validation error");
+        assert_eq!(
+            errors,
+            vec!["A header row is set but the input has no headers.".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_missing_paths_ignores_blank_and_existing() {
+        let dir = std::env::temp_dir();
+        let model = FileFormModel {
+            database_file: dir.join("not-created-yet.db"),
+            log_directory: dir,
+            ..FileFormModel::default()
+        };
+
+        assert!(model.missing_paths().is_empty());
+    }
+
+    #[test]
+    fn test_missing_paths_flags_each_field() {
+        let gone = std::env::temp_dir().join("gpui-demo-missing").join("gone");
+        let model = FileFormModel {
+            source_file: gone.join("hours.xlsx"),
+            database_file: gone.join("hours.db"),
+            log_directory: gone.clone(),
+            ..FileFormModel::default()
+        };
+
+        let missing = model.missing_paths();
+        assert_eq!(missing.len(), 3, "got: {missing:?}");
+        assert!(missing[0].starts_with("Source file"));
+        assert!(missing[1].starts_with("Database folder"));
+        assert!(missing[2].starts_with("Log folder"));
+    }
+
+    #[test]
+    fn test_write_mode_labels_round_trip() {
+        for mode in WriteMode::ALL {
+            assert_eq!(WriteMode::from_label(&mode.to_string()), Some(mode));
+        }
+    }
+
+    #[test]
+    fn test_sheet_target_labels_round_trip() {
+        for target in SheetTarget::ALL {
+            assert_eq!(SheetTarget::from_label(&target.to_string()), Some(target));
+        }
+    }
+
+    #[test]
+    fn test_csv_dialect_labels_round_trip() {
+        for delimiter in CsvDelimiter::ALL {
+            assert_eq!(
+                CsvDelimiter::from_label(&delimiter.to_string()),
+                Some(delimiter)
+            );
+        }
+        for quote in CsvQuote::ALL {
+            assert_eq!(CsvQuote::from_label(&quote.to_string()), Some(quote));
+        }
+    }
+
+    #[test]
+    fn test_text_encoding_labels_round_trip() {
+        for encoding in TextEncoding::ALL {
+            assert_eq!(
+                TextEncoding::from_label(&encoding.to_string()),
+                Some(encoding)
+            );
+        }
+    }
+
     #[test]
     fn test_db_backend_from_label() {
         assert_eq!(DbBackend::from_label("MySQL"), Some(DbBackend::MySql));
@@ -369,6 +1535,7 @@ mod tests {
         let csv_sources = [
             "input.csv",
             "INPUT.CSV",
+            "input.tsv",
         ];
         for file_name in csv_sources {
             let model = FileFormModel {
@@ -385,9 +1552,27 @@ mod tests {
     #[test]
     fn test_is_csv_negative() {
         let non_csv = FileFormModel {
-            source_file: PathBuf::from("input.tsv"),
+            source_file: PathBuf::from("input.txt"),
             ..FileFormModel::default()
         };
         assert!(!non_csv.is_csv());
     }
+
+    #[test]
+    fn test_predicates_use_detected_format() {
+        let dir = TempDir::new().expect("temp dir");
+        let source_file = dir.path().join("export.xls");
+        fs::write(&source_file, "Employee,Hours\nAda,7.5\n").expect("write csv");
+        let mut model = FileFormModel {
+            source_file,
+            ..FileFormModel::default()
+        };
+
+        assert_eq!(model.source_format, None);
+        assert!(!model.is_workbook());
+        model.detect_source_format();
+        assert_eq!(model.source_format, Some(SourceFormat::Csv));
+        assert!(model.is_csv());
+        assert!(!model.has_sheets());
+    }
 }
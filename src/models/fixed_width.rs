@@ -0,0 +1,363 @@
+// models/fixed_width.rs
+
+use std::{fmt, ops::Range, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use super::ColumnMapping;
+use crate::schema::SqlType;
+
+/// Separates the columns of a layout written as text.
+pub const LAYOUT_SEPARATOR: char = ',';
+
+/// Last character position a layout may cut, so a mistyped position
+/// cannot make a column, or the ruler drawn over the preview, huge.
+pub const MAX_POSITION: usize = 65_535;
+
+/// One column of a fixed-width text layout.
+///
+/// Positions count characters, not bytes, from 1 at the start of a line,
+/// as text editors show them.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct FixedWidthColumn {
+    /// Column name the import gives the text cut out of each line.
+    pub name: String,
+    /// One-based position of the column's first character.
+    pub start: usize,
+    /// Number of characters in the column.
+    pub width: usize,
+    /// Type to import the column as; `None` infers it.
+    pub sql_type: Option<SqlType>,
+}
+
+impl FixedWidthColumn {
+    /// A column `width` characters wide starting at the one-based position
+    /// `start`, whose type is inferred.
+    pub fn new(
+        name: impl Into<String>,
+        start: usize,
+        width: usize,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            start,
+            width,
+            sql_type: None,
+        }
+    }
+
+    /// Forces the column's type.
+    pub fn with_type(
+        mut self,
+        sql_type: SqlType,
+    ) -> Self {
+        self.sql_type = Some(sql_type);
+        self
+    }
+
+    /// Zero-based character positions the column covers, ending at
+    /// `usize::MAX` for a column too wide to end anywhere.
+    pub fn span(&self) -> Range<usize> {
+        let first = self.start.saturating_sub(1);
+        first..first.saturating_add(self.width)
+    }
+
+    /// Returns `true` if the column ends by [`MAX_POSITION`].
+    fn fits(&self) -> bool {
+        self.start
+            .checked_add(self.width)
+            .is_some_and(|end| end <= MAX_POSITION + 1)
+    }
+
+    /// Returns the text of the column in `line`, without the padding
+    /// around it; the part of the column past the end of the line is
+    /// blank.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpui_demo::models::FixedWidthColumn;
+    ///
+    /// let hours = FixedWidthColumn::new("hours", 9, 5);
+    /// assert_eq!(hours.cut("00417   07.50X"), "07.50");
+    /// assert_eq!(hours.cut("00417   7.5"), "7.5");
+    /// assert_eq!(hours.cut("00417"), "");
+    /// ```
+    pub fn cut<'a>(
+        &self,
+        line: &'a str,
+    ) -> &'a str {
+        let span = self.span();
+        let byte_at = |position: usize| {
+            line.char_indices()
+                .nth(position)
+                .map_or(line.len(), |(index, _)| index)
+        };
+        line[byte_at(span.start)..byte_at(span.end)].trim()
+    }
+}
+
+impl fmt::Display for FixedWidthColumn {
+    /// Writes the column as `name:start:width`, followed by `:type` when
+    /// the type is forced.
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.name, self.start, self.width)?;
+        match self.sql_type {
+            Some(sql_type) => write!(f, ":{sql_type}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for FixedWidthColumn {
+    type Err = String;
+
+    /// Parses a column written as `name:start:width` or
+    /// `name:start:width:type`.
+    fn from_str(text: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = text.split(':').map(str::trim).collect();
+        let (name, start, width, sql_type) = match parts.as_slice() {
+            [
+                name,
+                start,
+                width,
+            ] => (name, start, width, None),
+            [
+                name,
+                start,
+                width,
+                sql_type,
+            ] => (name, start, width, Some(sql_type)),
+            _ => {
+                return Err(format!(
+                    "'{}' is not of the form name:start:width or name:start:width:type.",
+                    text.trim()
+                ));
+            }
+        };
+
+        let number = |value: &str, what: &str| {
+            value
+                .parse::<usize>()
+                .map_err(|_| format!("The {what} of column '{name}' must be a whole number."))
+        };
+        let sql_type = sql_type
+            .map(|label| {
+                SqlType::from_label(label).ok_or_else(|| format!("'{label}' is not a column type."))
+            })
+            .transpose()?;
+
+        let column = Self {
+            name: name.to_string(),
+            start: number(start, "start")?,
+            width: number(width, "width")?,
+            sql_type,
+        };
+        if column.fits() {
+            Ok(column)
+        } else {
+            Err(format!(
+                "Column '{name}' must end by position {MAX_POSITION}."
+            ))
+        }
+    }
+}
+
+/// Parses a layout written as columns separated by [`LAYOUT_SEPARATOR`],
+/// as in `employee:1:8, date:9:10:Date, hours:19:5:Real`.
+///
+/// Blank text is an empty layout.
+pub fn parse_layout(text: &str) -> Result<Vec<FixedWidthColumn>, String> {
+    text.split(LAYOUT_SEPARATOR)
+        .filter(|column| !column.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Writes `columns` as text that [`parse_layout`] reads back.
+pub fn format_layout(columns: &[FixedWidthColumn]) -> String {
+    columns
+        .iter()
+        .map(FixedWidthColumn::to_string)
+        .collect::<Vec<_>>()
+        .join(&format!("{LAYOUT_SEPARATOR} "))
+}
+
+/// Checks a layout for columns that cannot be cut out of a line: columns
+/// without a name, a start or a width, columns ending past
+/// [`MAX_POSITION`], names used twice and columns that overlap.
+pub fn validate_layout(columns: &[FixedWidthColumn]) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for (index, column) in columns.iter().enumerate() {
+        let name = column.name.trim();
+        if name.is_empty() {
+            errors.push(format!("Fixed-width column {} has no name.", index + 1));
+            continue;
+        }
+        if column.start == 0 {
+            errors.push(format!(
+                "Fixed-width column '{name}' must start at position 1 or later."
+            ));
+        }
+        if column.width == 0 {
+            errors.push(format!(
+                "Fixed-width column '{name}' must be at least one character wide."
+            ));
+        }
+        if !column.fits() {
+            errors.push(format!(
+                "Fixed-width column '{name}' must end by position {MAX_POSITION}."
+            ));
+        }
+
+        for earlier in &columns[..index] {
+            if earlier.name.trim().eq_ignore_ascii_case(name) {
+                errors.push(format!("Fixed-width column '{name}' is defined twice."));
+            } else if column.width > 0
+                && earlier.width > 0
+                && column.span().start < earlier.span().end
+                && earlier.span().start < column.span().end
+            {
+                errors.push(format!(
+                    "Fixed-width columns '{}' and '{name}' overlap.",
+                    earlier.name.trim()
+                ));
+            }
+        }
+    }
+
+    errors
+}
+
+/// Returns `mappings` with the type of each typed column of `columns`
+/// added, unless a mapping already retypes that column, so the import
+/// treats the layout's types like types chosen in the mapping panel.
+pub fn layout_mappings(
+    columns: &[FixedWidthColumn],
+    mappings: &[ColumnMapping],
+) -> Vec<ColumnMapping> {
+    let mut merged = mappings.to_vec();
+    for column in columns {
+        let Some(sql_type) = column.sql_type else {
+            continue;
+        };
+        let name = column.name.trim();
+        match merged
+            .iter_mut()
+            .find(|mapping| mapping.source.trim().eq_ignore_ascii_case(name))
+        {
+            Some(mapping) => {
+                mapping.sql_type.get_or_insert(sql_type);
+            }
+            None => merged.push(ColumnMapping::new(name).with_type(sql_type)),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_layout_text_round_trips() {
+        let columns = vec![
+            FixedWidthColumn::new("employee", 1, 8),
+            FixedWidthColumn::new("date", 9, 10).with_type(SqlType::Date),
+        ];
+
+        let text = format_layout(&columns);
+
+        assert_eq!(text, "employee:1:8, date:9:10:Date");
+        assert_eq!(parse_layout(&text), Ok(columns));
+        assert_eq!(
+            parse_layout(" date : 9 : 10 : date ,"),
+            parse_layout("date:9:10:Date")
+        );
+        assert_eq!(parse_layout("  "), Ok(Vec::new()));
+        assert_eq!(
+            parse_layout("employee:1"),
+            Err(
+                "'employee:1' is not of the form name:start:width or name:start:width:type."
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            parse_layout("hours:x:5"),
+            Err("The start of column 'hours' must be a whole number.".to_string())
+        );
+        assert_eq!(
+            parse_layout("hours:1:5:Money"),
+            Err("'Money' is not a column type.".to_string())
+        );
+        assert_eq!(
+            parse_layout("hours:1:18446744073709551615"),
+            Err("Column 'hours' must end by position 65535.".to_string())
+        );
+        assert_eq!(
+            parse_layout("hours:65535:1").map(|columns| columns[0].span()),
+            Ok(65534..65535)
+        );
+    }
+
+    #[test]
+    fn test_cut_counts_characters() {
+        let name = FixedWidthColumn::new("name", 6, 6);
+
+        assert_eq!(name.cut("00417Renée 07.50"), "Renée");
+        assert_eq!(name.cut(""), "");
+    }
+
+    #[test]
+    fn test_validate_layout_reports_conflicts() {
+        let columns = vec![
+            FixedWidthColumn::new("employee", 1, 8),
+            FixedWidthColumn::new("date", 8, 10),
+            FixedWidthColumn::new("Employee", 20, 2),
+            FixedWidthColumn::new(" ", 30, 2),
+            FixedWidthColumn::new("hours", 0, 0),
+            FixedWidthColumn::new("note", 40, usize::MAX),
+        ];
+
+        assert_eq!(
+            validate_layout(&columns),
+            vec![
+                "Fixed-width columns 'employee' and 'date' overlap.".to_string(),
+                "Fixed-width column 'Employee' is defined twice.".to_string(),
+                "Fixed-width column 4 has no name.".to_string(),
+                "Fixed-width column 'hours' must start at position 1 or later.".to_string(),
+                "Fixed-width column 'hours' must be at least one character wide.".to_string(),
+                "Fixed-width column 'note' must end by position 65535.".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_mappings_keep_chosen_types() {
+        let columns = vec![
+            FixedWidthColumn::new("employee", 1, 8).with_type(SqlType::Text),
+            FixedWidthColumn::new("date", 9, 10).with_type(SqlType::Date),
+            FixedWidthColumn::new("note", 19, 20),
+        ];
+        let mappings = vec![
+            ColumnMapping::new("Employee").with_type(SqlType::Integer),
+            ColumnMapping::new("date").to("worked_on"),
+        ];
+
+        assert_eq!(
+            layout_mappings(&columns, &mappings),
+            vec![
+                ColumnMapping::new("Employee").with_type(SqlType::Integer),
+                ColumnMapping::new("date")
+                    .to("worked_on")
+                    .with_type(SqlType::Date),
+            ]
+        );
+    }
+}
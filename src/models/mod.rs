@@ -1,5 +1,14 @@
 // models/mod.rs
 
+pub mod column_mapping;
 pub mod file_form_model;
+pub mod fixed_width;
 
-pub use file_form_model::{DbBackend, FileFormModel, LogLevel};
+pub use column_mapping::{ColumnMapping, validate_mappings};
+pub use file_form_model::{
+    CsvDelimiter, CsvQuote, DbBackend, FailurePolicy, FileFormModel, LogLevel, SheetTarget,
+    TextEncoding, WriteMode,
+};
+pub use fixed_width::{
+    FixedWidthColumn, MAX_POSITION, format_layout, parse_layout, validate_layout,
+};
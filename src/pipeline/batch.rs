@@ -0,0 +1,408 @@
+// pipeline/batch.rs
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::{
+    models::{FailurePolicy, FileFormModel},
+    sources::registry,
+};
+
+use super::{ImportReport, PipelineError, run_import};
+
+/// Where one file of a batch stands.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum FileStatus {
+    Queued,
+    Running,
+    Imported {
+        report: ImportReport,
+    },
+    Failed {
+        error: String,
+    },
+    /// Not attempted because an earlier file failed under
+    /// [`FailurePolicy::StopOnFirstFailure`].
+    Skipped,
+}
+
+impl fmt::Display for FileStatus {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Self::Queued => write!(f, "queued"),
+            Self::Running => write!(f, "importing..."),
+            Self::Imported { report } => {
+                write!(f, "{} rows written", report.rows_written())?;
+                if report.rows_rejected() > 0 {
+                    write!(f, ", {} rejected", report.rows_rejected())?;
+                }
+                if report.malformed_rows() > 0 {
+                    write!(f, ", {} malformed", report.malformed_rows())?;
+                }
+                Ok(())
+            }
+            Self::Failed { error } => write!(f, "failed: {error}"),
+            Self::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// One source file of a batch and its status.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BatchItem {
+    pub source: PathBuf,
+    #[serde(flatten)]
+    pub status: FileStatus,
+}
+
+impl fmt::Display for BatchItem {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let name = self.source.file_name().map_or_else(
+            || self.source.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        write!(f, "{name}: {}", self.status)
+    }
+}
+
+/// A queue of source files imported one after another with the same form
+/// settings.
+///
+/// [`BatchJob::run`] imports the whole queue; a caller that wants to show
+/// progress can instead take one file at a time with
+/// [`BatchJob::start_next`] and hand its outcome to [`BatchJob::record`].
+#[derive(Clone, Debug)]
+pub struct BatchJob {
+    template: FileFormModel,
+    pub items: Vec<BatchItem>,
+}
+
+impl BatchJob {
+    /// Expands the batch sources of `model` into a queue of files.
+    ///
+    /// Fails if the form is not valid for its batch (see
+    /// [`FileFormModel::validate_for_submit`]), with a validation message
+    /// per pattern that is malformed or matches no source file.
+    pub fn new(model: &FileFormModel) -> Result<Self, PipelineError> {
+        model
+            .validate_for_submit()
+            .map_err(PipelineError::Validation)?;
+        let sources = expand_sources(&model.batch_sources).map_err(PipelineError::Validation)?;
+        let items = sources
+            .into_iter()
+            .map(|source| BatchItem {
+                source,
+                status: FileStatus::Queued,
+            })
+            .collect();
+
+        Ok(Self {
+            template: model.clone(),
+            items,
+        })
+    }
+
+    /// Marks the next queued file as running and returns its index and the
+    /// form model to import it with.
+    pub fn start_next(&mut self) -> Option<(usize, FileFormModel)> {
+        let index = self
+            .items
+            .iter()
+            .position(|item| item.status == FileStatus::Queued)?;
+        let item = &mut self.items[index];
+        item.status = FileStatus::Running;
+
+        let model = FileFormModel {
+            source_file: item.source.clone(),
+            batch_sources: Vec::new(),
+            ..self.template.clone()
+        };
+        Some((index, model))
+    }
+
+    /// Records the outcome of the file at `index`.
+    ///
+    /// Under [`FailurePolicy::StopOnFirstFailure`] a failure marks every
+    /// queued file as skipped.
+    pub fn record(
+        &mut self,
+        index: usize,
+        result: Result<ImportReport, PipelineError>,
+    ) {
+        let source = self.items[index].source.display().to_string();
+        self.items[index].status = match result {
+            Ok(report) => {
+                info!(
+                    source,
+                    rows_written = report.rows_written(),
+                    "Batch file imported"
+                );
+                FileStatus::Imported { report }
+            }
+            Err(error) => {
+                warn!(source, %error, "Batch file failed");
+                if self.template.failure_policy == FailurePolicy::StopOnFirstFailure {
+                    for item in &mut self.items {
+                        if item.status == FileStatus::Queued {
+                            item.status = FileStatus::Skipped;
+                        }
+                    }
+                }
+                FileStatus::Failed {
+                    error: error.to_string(),
+                }
+            }
+        };
+    }
+
+    /// Imports every queued file in order.
+    pub fn run(&mut self) {
+        while let Some((index, model)) = self.start_next() {
+            let result = run_import(&model);
+            self.record(index, result);
+        }
+    }
+
+    /// Returns `true` once no file is queued or running.
+    pub fn is_finished(&self) -> bool {
+        !self
+            .items
+            .iter()
+            .any(|item| matches!(item.status, FileStatus::Queued | FileStatus::Running))
+    }
+
+    /// Returns the number of files that failed.
+    pub fn failures(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| matches!(item.status, FileStatus::Failed { .. }))
+            .count()
+    }
+
+    /// Returns the reports of the files imported so far.
+    pub fn reports(&self) -> impl Iterator<Item = &ImportReport> {
+        self.items.iter().filter_map(|item| match &item.status {
+            FileStatus::Imported { report } => Some(report),
+            _ => None,
+        })
+    }
+}
+
+/// Turns batch source entries into the list of files to import.
+///
+/// An entry holding `*`, `?` or `[` is a glob pattern and expands to the
+/// source files it matches, those a reader handles, in path order; any
+/// other entry is kept as a file path, so a missing file fails on its own
+/// status line. Files named twice are imported once.
+///
+/// # Examples
+///
+/// ```
+/// use gpui_demo::pipeline::batch::expand_sources;
+///
+/// let files = expand_sources(&["march.xlsx".to_string(), " march.xlsx ".to_string()]);
+/// assert_eq!(files.map(|files| files.len()), Ok(1));
+/// assert!(expand_sources(&["[".to_string()]).is_err());
+/// ```
+pub fn expand_sources(entries: &[String]) -> Result<Vec<PathBuf>, Vec<String>> {
+    let mut errors = Vec::new();
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut add = |path: PathBuf| {
+        if !files.contains(&path) {
+            files.push(path);
+        }
+    };
+
+    for entry in entries.iter().map(|entry| entry.trim()) {
+        if entry.is_empty() {
+            continue;
+        }
+        if !entry.contains([
+            '*', '?', '[',
+        ]) {
+            add(PathBuf::from(entry));
+            continue;
+        }
+
+        let paths = match glob::glob(entry) {
+            Ok(paths) => paths,
+            Err(error) => {
+                errors.push(format!("'{entry}' is not a valid file pattern: {error}."));
+                continue;
+            }
+        };
+        let mut matched = false;
+        for path in paths.flatten() {
+            if path.is_file() && is_source_file(&path) {
+                matched = true;
+                add(path);
+            }
+        }
+        if !matched {
+            errors.push(format!("No source file matches '{entry}'."));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(files)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Returns the batch entry that names `path` itself, escaping the
+/// characters [`expand_sources`] would read as a pattern, so a picked
+/// `Week [12].xlsx` stays one file.
+pub fn path_entry(path: &Path) -> String {
+    glob::Pattern::escape(&path.display().to_string())
+}
+
+/// Returns `true` if `path` has an extension the import can read.
+pub(crate) fn is_source_file(path: &Path) -> bool {
+    registry().handles_extension(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_week(
+        dir: &Path,
+        name: &str,
+        contents: &str,
+    ) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, contents).expect("write csv");
+        path
+    }
+
+    fn batch_model(
+        dir: &Path,
+        policy: FailurePolicy,
+    ) -> FileFormModel {
+        FileFormModel {
+            batch_sources: vec![
+                dir.join("week_1*.csv").display().to_string(),
+                dir.join("missing.csv").display().to_string(),
+                dir.join("week_9.csv").display().to_string(),
+            ],
+            failure_policy: policy,
+            database_file: dir.join("out.db"),
+            has_headers: true,
+            ..FileFormModel::default()
+        }
+    }
+
+    #[test]
+    fn test_expand_sources_matches_source_files() {
+        let dir = TempDir::new().expect("temp dir");
+        let first = write_week(dir.path(), "week_1.csv", "");
+        let second = write_week(dir.path(), "week_2.csv", "");
+        write_week(dir.path(), "week_3.pdf", "");
+        let pattern = dir.path().join("week_*").display().to_string();
+
+        assert_eq!(
+            expand_sources(&[
+                pattern.clone(),
+                second.display().to_string(),
+            ]),
+            Ok(vec![
+                first, second
+            ])
+        );
+        assert_eq!(
+            expand_sources(&[pattern.replace("week_", "month_")]),
+            Err(vec![
+                format!(
+                    "No source file matches '{}'.",
+                    pattern.replace("week_", "month_")
+                )
+            ])
+        );
+    }
+
+    #[test]
+    fn test_expand_sources_keeps_escaped_paths() {
+        let dir = TempDir::new().expect("temp dir");
+        let picked = write_week(dir.path(), "Week [12].csv", "");
+        write_week(dir.path(), "Week 1.csv", "");
+
+        assert_eq!(expand_sources(&[path_entry(&picked)]), Ok(vec![picked]));
+    }
+
+    #[test]
+    fn test_batch_continues_on_error() {
+        let dir = TempDir::new().expect("temp dir");
+        write_week(dir.path(), "week_1.csv", "Employee,Hours\nAda,7.5\n");
+        write_week(
+            dir.path(),
+            "week_9.csv",
+            "Employee,Hours\nGrace,8\nLinus,6\n",
+        );
+
+        let mut job = BatchJob::new(&batch_model(dir.path(), FailurePolicy::ContinueOnError))
+            .expect("valid batch");
+        job.run();
+
+        let lines: Vec<String> = job.items.iter().map(ToString::to_string).collect();
+        assert_eq!(lines[0], "week_1.csv: 1 rows written");
+        assert!(
+            lines[1].starts_with("missing.csv: failed:"),
+            "got: {lines:?}"
+        );
+        assert_eq!(lines[2], "week_9.csv: 2 rows written");
+        assert!(job.is_finished());
+        assert_eq!(job.failures(), 1);
+    }
+
+    #[test]
+    fn test_batch_stops_on_first_failure() {
+        let dir = TempDir::new().expect("temp dir");
+        write_week(dir.path(), "week_1.csv", "Employee,Hours\nAda,7.5\n");
+        write_week(dir.path(), "week_9.csv", "Employee,Hours\nGrace,8\n");
+
+        let mut job = BatchJob::new(&batch_model(dir.path(), FailurePolicy::StopOnFirstFailure))
+            .expect("valid batch");
+        job.run();
+
+        let statuses: Vec<&FileStatus> = job.items.iter().map(|item| &item.status).collect();
+        assert!(matches!(statuses[0], FileStatus::Imported { .. }));
+        assert!(matches!(statuses[1], FileStatus::Failed { .. }));
+        assert_eq!(statuses[2], &FileStatus::Skipped);
+        assert_eq!(job.reports().count(), 1);
+    }
+
+    #[test]
+    fn test_batch_of_workbooks_needs_sheet() {
+        let dir = TempDir::new().expect("temp dir");
+        let model = FileFormModel {
+            batch_sources: vec![dir.path().join("*.xlsx").display().to_string()],
+            database_file: dir.path().join("out.db"),
+            ..FileFormModel::default()
+        };
+
+        let result = BatchJob::new(&model);
+
+        assert!(
+            matches!(&result, Err(PipelineError::Validation(errors)) if errors == &vec![
+                "Sheet selection is required for workbook sources.".to_string(),
+            ]),
+            "expected Validation, got: {result:?}"
+        );
+    }
+}
@@ -0,0 +1,1155 @@
+// pipeline
+
+pub mod batch;
+pub mod preview;
+pub mod report;
+pub mod watch;
+
+use std::{iter, path::Path, time::Instant};
+
+use thiserror::Error;
+use tracing::{debug, info, warn};
+
+use crate::{
+    db::{DbError, sqlite::SqliteWriter},
+    models::{DbBackend, FileFormModel, SheetTarget},
+    schema::{InferenceOptions, MappedSchema, infer_schema},
+    sources::{
+        CellValue, ReadOptions, Row, SheetInfo, SourceError, SourceFormat, SourceRows,
+        archive::uncompressed_path, registry,
+    },
+};
+
+pub use batch::{BatchItem, BatchJob, FileStatus};
+pub use preview::{DataPreview, PreviewCell, preview_source};
+pub use report::{ImportReport, SheetReport};
+pub use watch::{FolderWatch, WatchError, import_watched_file};
+
+/// Column recording the source sheet of each row when several sheets are
+/// imported into one table.
+pub const SHEET_COLUMN: &str = "source_sheet";
+
+/// Errors that stop an import before any rows are committed.
+#[derive(Debug, Error)]
+pub enum PipelineError {
+    #[error("Form is not valid: {}", .0.join(" "))]
+    Validation(Vec<String>),
+
+    #[error(transparent)]
+    Source(#[from] SourceError),
+
+    #[error(transparent)]
+    Database(#[from] DbError),
+
+    /// A sheet failed after the sheets before it were imported, each in
+    /// its own transaction; `report` lists those sheets.
+    #[error("Sheet '{sheet}' failed, so only the sheets before it were imported: {error}")]
+    SheetFailed {
+        report: Box<ImportReport>,
+        sheet: String,
+        error: Box<PipelineError>,
+    },
+}
+
+/// Runs a full import for a validated form model.
+///
+/// Detects the format of the source file afresh (see
+/// [`FileFormModel::detect_source_format`]), then reads each chosen sheet
+/// (or a source without sheets), infers a
+/// [`TableSchema`] from it, applies the form's column mappings (and the
+/// types of its fixed-width layout) and writes
+/// the data rows into the target database. Rows that are wider than the
+/// source or hold a cell that does not fit its column's type are rejected
+/// and counted in the returned [`ImportReport`]; blank rows are skipped.
+///
+/// With [`SheetTarget::TablePerSheet`] each sheet is written in its own
+/// transaction, so sheets imported before a failing one stay imported and
+/// are reported in [`PipelineError::SheetFailed`]. With
+/// [`SheetTarget::SingleTable`] all sheets are written in one.
+///
+/// [`TableSchema`]: crate::schema::TableSchema
+pub fn run_import(model: &FileFormModel) -> Result<ImportReport, PipelineError> {
+    let started = Instant::now();
+    let model = &model.clone().with_detected_format();
+    model
+        .validate_for_submit()
+        .map_err(PipelineError::Validation)?;
+
+    if model.db_backend != DbBackend::Sqlite {
+        return Err(DbError::UnsupportedBackend(model.db_backend).into());
+    }
+
+    let mut writer = SqliteWriter::open(&model.database_file)?
+        .with_mode(model.write_mode)
+        .with_key_columns(&model.key_columns);
+    let sheets = if model.has_sheets() {
+        sheets_to_import(model)?
+    } else {
+        Vec::new()
+    };
+    let sheets = if sheets.is_empty() {
+        vec![import_table(model, None, &mut writer)?]
+    } else if model.sheet_target == SheetTarget::SingleTable {
+        import_into_one_table(model, &sheets, &mut writer)?
+    } else {
+        check_table_names(model, &sheets)?;
+        let mut reports = Vec::new();
+        for sheet in &sheets {
+            match import_table(model, Some(sheet), &mut writer) {
+                Ok(report) => reports.push(report),
+                Err(error) if reports.is_empty() => return Err(error),
+                Err(error) => {
+                    return Err(PipelineError::SheetFailed {
+                        report: Box::new(import_report(model, reports, started)),
+                        sheet: sheet.clone(),
+                        error: Box::new(error),
+                    });
+                }
+            }
+        }
+        reports
+    };
+
+    let report = import_report(model, sheets, started);
+    info!(%report, "Import finished");
+    Ok(report)
+}
+
+fn import_report(
+    model: &FileFormModel,
+    sheets: Vec<SheetReport>,
+    started: Instant,
+) -> ImportReport {
+    ImportReport {
+        source_file: model.source_file.clone(),
+        database_file: model.database_file.clone(),
+        sheets,
+        elapsed: started.elapsed(),
+    }
+}
+
+/// Fails if two of `sheets` would be imported into the same table, as
+/// sheets named "Week 1" and "week-1" or zip entries `2024/week_1.csv` and
+/// `2025/week_1.csv` would.
+fn check_table_names(
+    model: &FileFormModel,
+    sheets: &[String],
+) -> Result<(), PipelineError> {
+    let tables: Vec<String> = sheets
+        .iter()
+        .map(|sheet| sheet_table_name(model, Some(sheet)))
+        .collect();
+    let errors: Vec<String> = tables
+        .iter()
+        .enumerate()
+        .filter_map(|(index, table)| {
+            let first = tables[..index].iter().position(|other| other == table)?;
+            Some(format!(
+                "Sheets '{}' and '{}' would both be imported into table '{table}'.",
+                sheets[first], sheets[index]
+            ))
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(PipelineError::Validation(errors))
+    }
+}
+
+/// Reports that `sheet` has the columns `names` instead of those of the
+/// `first` sheet, `expected`.
+fn differing_columns(
+    sheet: &str,
+    names: &[String],
+    first: &str,
+    expected: &[String],
+) -> PipelineError {
+    PipelineError::Validation(vec![
+        format!(
+            "Sheet '{sheet}' has columns ({}) that differ from sheet '{first}' ({}).",
+            names.join(", "),
+            expected.join(", ")
+        ),
+    ])
+}
+
+/// Lists the sheets the form chooses: every visible sheet, the listed
+/// sheets, or else the selected one.
+///
+/// Only a workbook must have a sheet to import; a JSON document without
+/// one is imported whole.
+fn sheets_to_import(model: &FileFormModel) -> Result<Vec<String>, PipelineError> {
+    let sheets: Vec<String> = if model.all_visible_sheets {
+        registry()
+            .detect(&model.source_file)?
+            .list_sheets(&model.source_file)?
+            .into_iter()
+            .filter(SheetInfo::is_visible)
+            .map(|sheet| sheet.name)
+            .collect()
+    } else if !model.import_sheets.is_empty() {
+        model.import_sheets.clone()
+    } else {
+        model.selected_sheet.iter().cloned().collect()
+    };
+
+    if sheets.is_empty() && model.is_workbook() {
+        return Err(PipelineError::Validation(vec![
+            "The workbook has no visible sheets to import.".to_string(),
+        ]));
+    }
+    Ok(sheets)
+}
+
+/// Imports one sheet (or the sheetless source) into its own table.
+fn import_table(
+    model: &FileFormModel,
+    sheet: Option<&str>,
+    writer: &mut SqliteWriter,
+) -> Result<SheetReport, PipelineError> {
+    let started = Instant::now();
+    let read = read_source_sheet(model, sheet)?;
+    let options = inference_options(model, &read);
+    let SourceRows {
+        rows, malformed, ..
+    } = read;
+    let mapped = MappedSchema::new(&infer_schema(&rows, &options), &model.effective_mappings())
+        .map_err(PipelineError::Validation)?;
+
+    let mut rows_read = 0;
+    let mut accepted: Vec<Row> = Vec::new();
+    for row in options.data_rows(&rows) {
+        if row.iter().all(CellValue::is_blank) {
+            continue;
+        }
+        rows_read += 1;
+        if let Some(typed) = mapped.coerce_row(row) {
+            accepted.push(typed);
+        }
+    }
+
+    let table = sheet_table_name(model, sheet);
+    warn_rejected(&table, rows_read - accepted.len(), &options);
+    debug!(
+        table,
+        columns = mapped.schema.columns.len(),
+        rows = accepted.len(),
+        "Writing rows"
+    );
+    let rows_written = writer.write(&table, &mapped.schema, &accepted)?;
+
+    Ok(SheetReport {
+        sheet: sheet.map(str::to_string),
+        table,
+        rows_read,
+        rows_written,
+        rows_rejected: rows_read - accepted.len(),
+        malformed_rows: malformed,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// Appends the rows of every sheet in `sheets` to one table, adding a
+/// [`SHEET_COLUMN`] that records where each row came from.
+///
+/// Sheets are matched up by column name, ignoring case and order, and must
+/// all have the same columns. Types are inferred from the combined rows so
+/// every sheet is read the same way.
+fn import_into_one_table(
+    model: &FileFormModel,
+    sheets: &[String],
+    writer: &mut SqliteWriter,
+) -> Result<Vec<SheetReport>, PipelineError> {
+    let table = table_name(model);
+    let mut reports: Vec<SheetReport> = sheets
+        .iter()
+        .map(|sheet| SheetReport {
+            sheet: Some(sheet.clone()),
+            table: table.clone(),
+            ..SheetReport::default()
+        })
+        .collect();
+
+    // The combined rows always start with a header row, so the sheet
+    // column is named even when the sheets have no headers.
+    let mut combined: Vec<Row> = Vec::new();
+    let mut origins: Vec<usize> = Vec::new();
+    let mut expected: Vec<String> = Vec::new();
+    for (index, sheet) in sheets.iter().enumerate() {
+        let started = Instant::now();
+        let read = read_source_sheet(model, Some(sheet))?;
+        let options = inference_options(model, &read);
+        let SourceRows {
+            rows, malformed, ..
+        } = read;
+        reports[index].malformed_rows = malformed;
+        let names: Vec<String> = infer_schema(&rows, &options)
+            .column_names()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        if names
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(SHEET_COLUMN))
+        {
+            return Err(PipelineError::Validation(vec![
+                format!(
+                    "Sheet '{sheet}' has a column named '{SHEET_COLUMN}', which the import adds \
+                     to record the sheet of each row."
+                ),
+            ]));
+        }
+
+        let order: Vec<usize> = if index == 0 {
+            combined.push(
+                names
+                    .iter()
+                    .map(String::as_str)
+                    .chain(iter::once(SHEET_COLUMN))
+                    .map(|name| CellValue::Text(name.to_string()))
+                    .collect(),
+            );
+            expected = names.clone();
+            (0..names.len()).collect()
+        } else {
+            expected
+                .iter()
+                .map(|name| {
+                    names
+                        .iter()
+                        .position(|other| other.eq_ignore_ascii_case(name))
+                })
+                .collect::<Option<Vec<usize>>>()
+                .filter(|_| names.len() == expected.len())
+                .ok_or_else(|| differing_columns(sheet, &names, &sheets[0], &expected))?
+        };
+
+        let report = &mut reports[index];
+        for row in options.data_rows(&rows) {
+            if row.iter().all(CellValue::is_blank) {
+                continue;
+            }
+            report.rows_read += 1;
+            if row.len() > names.len() {
+                report.rows_rejected += 1;
+                continue;
+            }
+            combined.push(
+                order
+                    .iter()
+                    .map(|&column| row.get(column).cloned().unwrap_or_default())
+                    .chain(iter::once(CellValue::Text(sheet.clone())))
+                    .collect(),
+            );
+            origins.push(index);
+        }
+        report.elapsed = started.elapsed();
+    }
+
+    let combined_options = InferenceOptions {
+        has_headers: true,
+        ..InferenceOptions::from(model)
+    };
+    let mapped = MappedSchema::new(
+        &infer_schema(&combined, &combined_options),
+        &model.effective_mappings(),
+    )
+    .map_err(PipelineError::Validation)?;
+
+    let mut accepted: Vec<Row> = Vec::new();
+    for (row, &index) in combined_options.data_rows(&combined).iter().zip(&origins) {
+        match mapped.coerce_row(row) {
+            Some(typed) => {
+                accepted.push(typed);
+                reports[index].rows_written += 1;
+            }
+            None => reports[index].rows_rejected += 1,
+        }
+    }
+
+    let rows_rejected = reports.iter().map(|report| report.rows_rejected).sum();
+    warn_rejected(&table, rows_rejected, &combined_options);
+    debug!(
+        table,
+        sheets = sheets.len(),
+        columns = mapped.schema.columns.len(),
+        rows = accepted.len(),
+        "Writing rows"
+    );
+    writer.write(&table, &mapped.schema, &accepted)?;
+    Ok(reports)
+}
+
+/// Reads the part of the selected sheet (or the CSV file) the form's layout
+/// selects: the header row (if any) followed by at most `row_limit` data
+/// rows, or all of them when it is `None`.
+fn read_source(
+    model: &FileFormModel,
+    row_limit: Option<usize>,
+) -> Result<SourceRows, SourceError> {
+    let mut options = ReadOptions::from_model(model)?;
+    options.layout.row_limit = row_limit;
+    read_with(model, model.selected_sheet.as_deref(), &options)
+}
+
+/// Reads the part of `sheet` the form's layout selects; `sheet` is ignored
+/// for sheetless sources.
+fn read_source_sheet(
+    model: &FileFormModel,
+    sheet: Option<&str>,
+) -> Result<SourceRows, SourceError> {
+    read_with(model, sheet, &ReadOptions::from_model(model)?)
+}
+
+/// Reads `sheet` of the form's source with `options`. Malformed rows are
+/// logged as they are found.
+fn read_with(
+    model: &FileFormModel,
+    sheet: Option<&str>,
+    options: &ReadOptions,
+) -> Result<SourceRows, SourceError> {
+    let read = registry().detect_with(&model.source_file, options)?.read(
+        &model.source_file,
+        sheet,
+        options,
+    )?;
+
+    for row in &read.malformed {
+        warn!(source = %model.source_file.display(), %row, "Malformed source row");
+    }
+    Ok(read)
+}
+
+/// Warns that `rows_rejected` rows of `table` did not fit their columns.
+///
+/// Column types are inferred from the first `sample_rows` data rows only,
+/// so a row further down that holds, say, text in a number column is
+/// rejected; the warning names the sample size so it can be raised.
+fn warn_rejected(
+    table: &str,
+    rows_rejected: usize,
+    options: &InferenceOptions,
+) {
+    if rows_rejected == 0 {
+        return;
+    }
+    let sample = match options.sample_rows {
+        0 => "every data row".to_string(),
+        rows => format!("the first {rows} data rows"),
+    };
+    warn!(
+        table,
+        rows_rejected,
+        sample_rows = options.sample_rows,
+        "Rejected rows that do not fit the column types inferred from {sample}"
+    );
+}
+
+/// Inference options for rows read from the form's source; a source that
+/// names its own columns always has a header row.
+fn inference_options(
+    model: &FileFormModel,
+    read: &SourceRows,
+) -> InferenceOptions {
+    InferenceOptions {
+        has_headers: model.has_headers || read.header_in_source,
+        ..InferenceOptions::from(model)
+    }
+}
+
+/// Derives the target table name from the selected sheet name, or from the
+/// source file stem for sheetless sources and for workbooks imported with
+/// [`SheetTarget::SingleTable`].
+pub fn table_name(model: &FileFormModel) -> String {
+    let sheet = match model.sheet_target {
+        SheetTarget::TablePerSheet => model.selected_sheet.as_deref(),
+        SheetTarget::SingleTable => None,
+    };
+    sheet_table_name(model, sheet)
+}
+
+/// Derives a table name from `sheet`, falling back to the source file stem.
+///
+/// The sheets of a zip archive are its files, named by their stem, and a
+/// gzip-compressed source is named by the file it holds.
+fn sheet_table_name(
+    model: &FileFormModel,
+    sheet: Option<&str>,
+) -> String {
+    let stem = |path: &Path| {
+        uncompressed_path(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+    };
+    let format = model.source_format;
+    let raw = sheet
+        .filter(|_| format.is_some_and(SourceFormat::has_sheets))
+        .and_then(|sheet| match format {
+            Some(SourceFormat::Zip) => stem(Path::new(sheet)),
+            _ => Some(sheet.to_string()),
+        })
+        .or_else(|| stem(&model.source_file))
+        .unwrap_or_default();
+
+    let name: String = raw
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        "import".to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{ColumnMapping, FixedWidthColumn, TextEncoding},
+        schema::SqlType,
+    };
+    use flate2::{Compression, write::GzEncoder};
+    use pretty_assertions::assert_eq;
+    use rusqlite::Connection;
+    use std::{
+        fs::{self, File},
+        io::Write,
+        path::{Path, PathBuf},
+    };
+    use tempfile::TempDir;
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn test_run_import_excel_sheet() {
+        let dir = TempDir::new().expect("temp dir");
+        let model = FileFormModel {
+            source_file: fixture("timesheet.xlsx"),
+            database_file: dir.path().join("out.db"),
+            selected_sheet: Some("Hours".to_string()),
+            has_headers: true,
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        assert_eq!(
+            report.sheets,
+            vec![
+                SheetReport {
+                    sheet: Some("Hours".to_string()),
+                    table: "hours".to_string(),
+                    rows_read: 3,
+                    rows_written: 3,
+                    rows_rejected: 0,
+                    malformed_rows: Vec::new(),
+                    elapsed: report.sheets[0].elapsed,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_import_ods_visible_sheets() {
+        let dir = TempDir::new().expect("temp dir");
+        let model = FileFormModel {
+            source_file: fixture("timesheet.ods"),
+            database_file: dir.path().join("out.db"),
+            all_visible_sheets: true,
+            has_headers: true,
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        let tables: Vec<(&str, usize)> = report
+            .sheets
+            .iter()
+            .map(|sheet| (sheet.table.as_str(), sheet.rows_written))
+            .collect();
+        assert_eq!(tables, vec![("hours", 4)]);
+
+        let conn = Connection::open(&model.database_file).expect("open db");
+        let ada: (String, String, f64) = conn
+            .query_row(
+                "SELECT Date, Shift, Utilisation FROM hours WHERE Employee = 'Ada'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .expect("row");
+        assert_eq!(
+            ada,
+            ("2024-01-08".to_string(), "07:30:00".to_string(), 0.75)
+        );
+    }
+
+    #[test]
+    fn test_run_import_json_arrays_as_sheets() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("schedule.json");
+        fs::write(
+            &source,
+            r#"{
+                "shifts": [
+                    {"employee": {"name": "Ada"}, "date": "2024-01-08", "hours": 7.5},
+                    {"employee": {"name": "Grace"}, "date": "2024-01-09", "hours": 8}
+                ],
+                "sites": [{"city": "Leeds"}]
+            }"#,
+        )
+        .expect("write json");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            all_visible_sheets: true,
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        let tables: Vec<(&str, usize)> = report
+            .sheets
+            .iter()
+            .map(|sheet| (sheet.table.as_str(), sheet.rows_written))
+            .collect();
+        assert_eq!(
+            tables,
+            vec![
+                ("shifts", 2),
+                ("sites", 1),
+            ]
+        );
+
+        let conn = Connection::open(&model.database_file).expect("open db");
+        let grace: f64 = conn
+            .query_row(
+                r#"SELECT hours FROM shifts WHERE "employee.name" = 'Grace'"#,
+                [],
+                |r| r.get(0),
+            )
+            .expect("row");
+        assert_eq!(grace, 8.0);
+    }
+
+    #[test]
+    fn test_run_import_zip_entries() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("timesheets.zip");
+        let mut zip = ZipWriter::new(File::create(&source).expect("create zip"));
+        for (name, contents) in [
+            ("week_1.csv", "Employee,Hours\nAda,7.5\nGrace,8\n"),
+            ("2024/Week 2.csv", "Employee,Hours\nLinus,6\n"),
+        ] {
+            zip.start_file(name, SimpleFileOptions::default())
+                .expect("start entry");
+            zip.write_all(contents.as_bytes()).expect("write entry");
+        }
+        zip.finish().expect("finish zip");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            has_headers: true,
+            all_visible_sheets: true,
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        let tables: Vec<(&str, usize)> = report
+            .sheets
+            .iter()
+            .map(|sheet| (sheet.table.as_str(), sheet.rows_written))
+            .collect();
+        assert_eq!(
+            tables,
+            vec![
+                ("week_1", 2),
+                ("week_2", 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_import_gzip() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("hours.csv.gz");
+        let mut encoder = GzEncoder::new(
+            File::create(&source).expect("create gz"),
+            Compression::default(),
+        );
+        encoder
+            .write_all(b"Employee,Hours\nAda,7.5\n")
+            .expect("write gz");
+        encoder.finish().expect("finish gz");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            has_headers: true,
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        assert_eq!(report.sheets[0].table, "hours");
+        assert_eq!(report.sheets[0].rows_written, 1);
+    }
+
+    #[test]
+    fn test_run_import_fixed_width_types() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("punches.txt");
+        fs::write(
+            &source,
+            "00417 2024-01-08  750\n00418 2024-01-08  8OO\n00419 2024-01-09  600\n",
+        )
+        .expect("write punches");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            fixed_width_columns: vec![
+                FixedWidthColumn::new("employee", 1, 5).with_type(SqlType::Text),
+                FixedWidthColumn::new("date", 7, 10),
+                FixedWidthColumn::new("minutes", 18, 4).with_type(SqlType::Integer),
+            ],
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        assert_eq!(
+            (
+                report.sheets[0].table.as_str(),
+                report.sheets[0].rows_written,
+                report.sheets[0].rows_rejected
+            ),
+            ("punches", 2, 1)
+        );
+
+        let conn = Connection::open(&model.database_file).expect("open db");
+        let first: (String, String, i64) = conn
+            .query_row(
+                "SELECT employee, date, minutes FROM punches ORDER BY employee LIMIT 1",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .expect("row");
+        assert_eq!(first, ("00417".to_string(), "2024-01-08".to_string(), 750));
+    }
+
+    #[test]
+    fn test_run_import_table_per_sheet() {
+        let dir = TempDir::new().expect("temp dir");
+        let model = FileFormModel {
+            source_file: fixture("timesheet.xlsx"),
+            database_file: dir.path().join("out.db"),
+            selected_sheet: Some("Hours".to_string()),
+            import_sheets: vec![
+                "Hours".to_string(),
+                "Archive".to_string(),
+            ],
+            has_headers: true,
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        let tables: Vec<(&str, usize)> = report
+            .sheets
+            .iter()
+            .map(|sheet| (sheet.table.as_str(), sheet.rows_written))
+            .collect();
+        assert_eq!(
+            tables,
+            vec![
+                ("hours", 3),
+                ("archive", 1),
+            ]
+        );
+        assert_eq!(report.rows_written(), 4);
+    }
+
+    #[test]
+    fn test_run_import_keeps_sheets_before_a_failing_one() {
+        let dir = TempDir::new().expect("temp dir");
+        let model = FileFormModel {
+            source_file: fixture("timesheet.xlsx"),
+            database_file: dir.path().join("out.db"),
+            import_sheets: vec![
+                "Hours".to_string(),
+                "Missing".to_string(),
+            ],
+            has_headers: true,
+            ..FileFormModel::default()
+        };
+
+        let Err(PipelineError::SheetFailed {
+            report,
+            sheet,
+            error,
+        }) = run_import(&model)
+        else {
+            panic!("expected the second sheet to fail");
+        };
+        assert_eq!(sheet, "Missing");
+        assert!(matches!(
+            *error,
+            PipelineError::Source(SourceError::SheetNotFound { .. })
+        ));
+        assert_eq!(report.sheets.len(), 1);
+        assert_eq!(report.rows_written(), 3);
+
+        let conn = Connection::open(&model.database_file).expect("open db");
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM hours", [], |r| r.get(0))
+            .expect("count");
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_run_import_rejects_sheets_sharing_a_table() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("weeks.json");
+        fs::write(
+            &source,
+            r#"{"Week 1": [{"hours": 7.5}], "week-1": [{"hours": 8}]}"#,
+        )
+        .expect("write json");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            all_visible_sheets: true,
+            ..FileFormModel::default()
+        };
+
+        let result = run_import(&model);
+        assert!(
+            matches!(&result, Err(PipelineError::Validation(errors)) if errors == &vec![
+                "Sheets 'Week 1' and 'week-1' would both be imported into table 'week_1'."
+                    .to_string(),
+            ]),
+            "expected Validation, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_run_import_one_table_reports_sheet_column_clash() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("weeks.json");
+        fs::write(
+            &source,
+            r#"{"week1": [{"source_sheet": "a"}], "week2": [{"source_sheet": "b"}]}"#,
+        )
+        .expect("write json");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            all_visible_sheets: true,
+            sheet_target: SheetTarget::SingleTable,
+            ..FileFormModel::default()
+        };
+
+        let result = run_import(&model);
+        assert!(
+            matches!(&result, Err(PipelineError::Validation(errors)) if errors[0].starts_with("Sheet 'week1' has a column named 'source_sheet'")),
+            "expected Validation, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_run_import_all_visible_sheets_into_one_table() {
+        let dir = TempDir::new().expect("temp dir");
+        let model = FileFormModel {
+            source_file: fixture("weeks.xlsx"),
+            database_file: dir.path().join("out.db"),
+            all_visible_sheets: true,
+            sheet_target: SheetTarget::SingleTable,
+            has_headers: true,
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        let sheets: Vec<(Option<&str>, &str, usize)> = report
+            .sheets
+            .iter()
+            .map(|sheet| {
+                (
+                    sheet.sheet.as_deref(),
+                    sheet.table.as_str(),
+                    sheet.rows_written,
+                )
+            })
+            .collect();
+        assert_eq!(
+            sheets,
+            vec![
+                (Some("Week 1"), "weeks", 2),
+                (Some("Week 2"), "weeks", 1),
+            ]
+        );
+
+        let conn = Connection::open(&model.database_file).expect("open db");
+        let linus: (f64, String) = conn
+            .query_row(
+                "SELECT Hours, source_sheet FROM weeks WHERE Employee = 'Linus'",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .expect("row");
+        assert_eq!(linus, (6.25, "Week 2".to_string()));
+    }
+
+    #[test]
+    fn test_run_import_one_table_needs_matching_columns() {
+        let dir = TempDir::new().expect("temp dir");
+        let model = FileFormModel {
+            source_file: fixture("timesheet.xlsx"),
+            database_file: dir.path().join("out.db"),
+            import_sheets: vec![
+                "Hours".to_string(),
+                "Archive".to_string(),
+            ],
+            sheet_target: SheetTarget::SingleTable,
+            has_headers: true,
+            ..FileFormModel::default()
+        };
+
+        let result = run_import(&model);
+        assert!(
+            matches!(&result, Err(PipelineError::Validation(errors)) if errors[0].starts_with("Sheet 'Archive' has columns")),
+            "expected Validation, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_run_import_csv_rejects_wide_rows() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("Week 1.csv");
+        fs::write(&source, "Employee,Hours\nAda,7.5\n\nGrace,8,extra\n").expect("write csv");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            has_headers: true,
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        assert_eq!(
+            (
+                report.rows_read(),
+                report.rows_written(),
+                report.rows_rejected()
+            ),
+            (2, 1, 1)
+        );
+        let conn = Connection::open(&model.database_file).expect("open db");
+        let name: String = conn
+            .query_row("SELECT Employee FROM week_1", [], |r| r.get(0))
+            .expect("row");
+        assert_eq!(name, "Ada");
+    }
+
+    #[test]
+    fn test_run_import_semicolon_csv_reports_malformed_rows() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("hours.csv");
+        fs::write(
+            &source,
+            b"\xEF\xBB\xBFEmployee;Hours\r\nAda;\"7.5\"\r\nGr\xFFce;8\r\nLinus;6\r\n",
+        )
+        .expect("write csv");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            has_headers: true,
+            encoding: TextEncoding::Utf8,
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        assert_eq!(report.rows_written(), 2);
+        assert_eq!(
+            report.sheets[0]
+                .malformed_rows
+                .iter()
+                .map(|row| row.line)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+        let conn = Connection::open(&model.database_file).expect("open db");
+        let hours: f64 = conn
+            .query_row("SELECT SUM(Hours) FROM hours", [], |r| r.get(0))
+            .expect("sum");
+        assert_eq!(hours, 13.5);
+    }
+
+    #[test]
+    fn test_run_import_rejects_mistyped_rows() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("hours.csv");
+        fs::write(&source, "Employee,Hours\nAda,7.5\nGrace,8\nLinus,lots\n").expect("write csv");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            has_headers: true,
+            sample_rows: Some(2),
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        assert_eq!(
+            (
+                report.rows_read(),
+                report.rows_written(),
+                report.rows_rejected()
+            ),
+            (3, 2, 1)
+        );
+    }
+
+    #[test]
+    fn test_run_import_skips_banner_and_footer() {
+        let dir = TempDir::new().expect("temp dir");
+        let model = FileFormModel {
+            source_file: fixture("banner.xlsx"),
+            database_file: dir.path().join("out.db"),
+            selected_sheet: Some("Week 1".to_string()),
+            has_headers: true,
+            header_row: Some(4),
+            skip_trailing_rows: 1,
+            ..FileFormModel::default()
+        };
+
+        let report = run_import(&model).expect("import should succeed");
+        assert_eq!(
+            (
+                report.rows_read(),
+                report.rows_written(),
+                report.rows_rejected()
+            ),
+            (3, 3, 0)
+        );
+        let conn = Connection::open(&model.database_file).expect("open db");
+        let total: f64 = conn
+            .query_row("SELECT SUM(Hours) FROM week_1", [], |r| r.get(0))
+            .expect("sum");
+        assert_eq!(total, 21.75);
+    }
+
+    #[test]
+    fn test_run_import_applies_column_mappings() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("hours.csv");
+        fs::write(&source, "Emp ID,Notes,Hours\n7,late,8\n9,,6\n").expect("write csv");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            has_headers: true,
+            column_mappings: vec![
+                ColumnMapping::new("Emp ID").to("employee_id"),
+                ColumnMapping::new("Notes").excluded(),
+            ],
+            ..FileFormModel::default()
+        };
+        Connection::open(&model.database_file)
+            .expect("open db")
+            .execute(
+                "CREATE TABLE hours (employee_id INTEGER NOT NULL, Hours REAL)",
+                [],
+            )
+            .expect("create");
+
+        let report = run_import(&model).expect("import should succeed");
+        assert_eq!(report.rows_written(), 2);
+        let conn = Connection::open(&model.database_file).expect("open db");
+        let total: i64 = conn
+            .query_row("SELECT SUM(employee_id) FROM hours", [], |r| r.get(0))
+            .expect("sum");
+        assert_eq!(total, 16);
+    }
+
+    #[test]
+    fn test_run_import_unmapped_required_column() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("hours.csv");
+        fs::write(&source, "Employee #,Hours\n7,8\n").expect("write csv");
+        let model = FileFormModel {
+            source_file: source,
+            database_file: dir.path().join("out.db"),
+            has_headers: true,
+            column_mappings: vec![ColumnMapping::new("Employee #").excluded()],
+            ..FileFormModel::default()
+        };
+        Connection::open(&model.database_file)
+            .expect("open db")
+            .execute(
+                "CREATE TABLE hours (employee_id INTEGER NOT NULL, Hours REAL)",
+                [],
+            )
+            .expect("create");
+
+        let result = run_import(&model);
+        assert!(
+            matches!(
+                result,
+                Err(PipelineError::Database(
+                    DbError::UnmappedRequiredColumns { .. }
+                ))
+            ),
+            "expected UnmappedRequiredColumns, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_run_import_invalid_model() {
+        let result = run_import(&FileFormModel::default());
+        assert!(
+            matches!(result, Err(PipelineError::Validation(_))),
+            "expected Validation, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_run_import_unsupported_backend() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.csv"),
+            database_file: PathBuf::from("app.db"),
+            db_backend: DbBackend::MySql,
+            ..FileFormModel::default()
+        };
+
+        let result = run_import(&model);
+        assert!(
+            matches!(
+                result,
+                Err(PipelineError::Database(DbError::UnsupportedBackend(
+                    DbBackend::MySql
+                )))
+            ),
+            "expected UnsupportedBackend, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_table_name_from_sheet() {
+        let model = FileFormModel {
+            source_file: PathBuf::from("input.xlsx"),
+            selected_sheet: Some("Week 1 Hours".to_string()),
+            ..FileFormModel::default()
+        }
+        .with_detected_format();
+        assert_eq!(table_name(&model), "week_1_hours");
+
+        let model = FileFormModel {
+            sheet_target: SheetTarget::SingleTable,
+            ..model
+        };
+        assert_eq!(table_name(&model), "input");
+    }
+}
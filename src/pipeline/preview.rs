@@ -0,0 +1,353 @@
+// pipeline/preview.rs
+
+use crate::{
+    models::FileFormModel,
+    schema::{InferenceOptions, MappedSchema, TableSchema, infer_schema},
+    sources::{CellValue, CsvDialect, MalformedRow, Row, SourceError, fixed_width},
+};
+
+use super::{inference_options, read_source};
+
+/// Number of data rows shown in the preview table.
+pub const DEFAULT_PREVIEW_ROWS: usize = 50;
+
+/// A source cell as shown in the preview.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreviewCell {
+    pub text: String,
+    /// `false` if the cell does not fit its column's inferred type, or lies
+    /// beyond the last column, so the import would reject its row.
+    pub valid: bool,
+}
+
+/// The first rows of a source, checked against the schema an import would
+/// infer for it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DataPreview {
+    pub schema: TableSchema,
+    /// Data rows in source order, blank rows skipped. Each row has one cell
+    /// per column, plus any cells past the last column.
+    pub rows: Vec<Vec<PreviewCell>>,
+    /// Non-blank data rows in the whole source; `None` when the preview
+    /// stopped reading before its end.
+    pub total_rows: Option<usize>,
+    /// Source rows that could not be read cleanly.
+    pub malformed: Vec<MalformedRow>,
+    /// How a CSV source was read; `None` for other formats.
+    pub dialect: Option<CsvDialect>,
+    /// The first lines of a fixed-width source as they are in the file,
+    /// shown under [`DataPreview::ruler`]; empty for other formats.
+    pub raw_lines: Vec<String>,
+    /// The columns of the fixed-width layout drawn over `raw_lines` (see
+    /// [`fixed_width::ruler`]).
+    pub ruler: Option<String>,
+}
+
+impl DataPreview {
+    /// Infers the schema from `rows` exactly as an import would and checks
+    /// the first `limit` data rows against it.
+    pub fn from_rows(
+        rows: &[Row],
+        options: &InferenceOptions,
+        limit: usize,
+    ) -> Self {
+        Self::with_schema(infer_schema(rows, options), rows, options, limit)
+    }
+
+    /// Checks the first `limit` data rows of `rows` against `schema`.
+    pub fn with_schema(
+        schema: TableSchema,
+        rows: &[Row],
+        options: &InferenceOptions,
+        limit: usize,
+    ) -> Self {
+        let data: Vec<&Row> = options
+            .data_rows(rows)
+            .iter()
+            .filter(|row| !row.iter().all(CellValue::is_blank))
+            .collect();
+
+        let preview_rows = data
+            .iter()
+            .take(limit)
+            .map(|row| preview_row(&schema, row))
+            .collect();
+
+        Self {
+            schema,
+            rows: preview_rows,
+            total_rows: Some(data.len()),
+            ..Self::default()
+        }
+    }
+
+    /// Header text for column `index`: its name and inferred type.
+    pub fn header(
+        &self,
+        index: usize,
+    ) -> String {
+        match self.schema.columns.get(index) {
+            Some(column) => format!("{} ({})", column.name, column.sql_type),
+            None => "(extra)".to_string(),
+        }
+    }
+
+    /// Number of columns needed to show every previewed cell.
+    pub fn width(&self) -> usize {
+        self.rows
+            .iter()
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0)
+            .max(self.schema.columns.len())
+    }
+
+    /// Number of previewed cells that fail type parsing.
+    pub fn invalid_cells(&self) -> usize {
+        self.rows
+            .iter()
+            .flatten()
+            .filter(|cell| !cell.valid)
+            .count()
+    }
+}
+
+fn preview_row(
+    schema: &TableSchema,
+    row: &Row,
+) -> Vec<PreviewCell> {
+    let width = row.len().max(schema.columns.len());
+    (0..width)
+        .map(|i| {
+            let cell = row.get(i).unwrap_or(&CellValue::Empty);
+            let valid = match schema.columns.get(i) {
+                Some(column) => column.sql_type.coerce(cell).is_some(),
+                None => cell.is_blank(),
+            };
+            PreviewCell {
+                text: cell.to_string(),
+                valid,
+            }
+        })
+        .collect()
+}
+
+/// Reads the form's source and previews its first `limit` data rows.
+///
+/// Unlike an import this needs no database and skips form validation, so
+/// it can run as soon as a source (and sheet) is chosen. Only the rows
+/// the schema is inferred from and the previewed rows are read, so the
+/// number of rows is known only for short sources. Cells are checked
+/// against the types the import would give their columns, those of the
+/// column mappings and of a fixed-width layout included, and a layout is
+/// drawn over the first `limit` lines of a fixed-width file.
+pub fn preview_source(
+    model: &FileFormModel,
+    limit: usize,
+) -> Result<DataPreview, SourceError> {
+    // One row more than needed tells whether the source goes on.
+    let row_limit = match InferenceOptions::from(model).sample_rows {
+        0 => None,
+        sample => Some(sample.max(limit).saturating_add(1)),
+    };
+    let read = read_source(model, row_limit)?;
+    let options = inference_options(model, &read);
+    let truncated =
+        row_limit.is_some_and(|row_limit| options.data_rows(&read.rows).len() >= row_limit);
+    let inferred = infer_schema(&read.rows, &options);
+    // Name clashes are for the mapping panel to report; the cells are then
+    // checked against the inferred types.
+    let schema = match MappedSchema::new(&inferred, &model.effective_mappings()) {
+        Ok(mapped) => mapped.retype(&inferred),
+        Err(_) => inferred,
+    };
+
+    let ruler =
+        Some(fixed_width::ruler(&model.fixed_width_columns)).filter(|_| !read.raw_lines.is_empty());
+    let preview = DataPreview::with_schema(schema, &read.rows, &options, limit);
+    Ok(DataPreview {
+        total_rows: preview.total_rows.filter(|_| !truncated),
+        malformed: read.malformed,
+        dialect: read.dialect,
+        raw_lines: read.raw_lines.into_iter().take(limit).collect(),
+        ruler,
+        ..preview
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{ColumnMapping, FixedWidthColumn},
+        schema::SqlType,
+    };
+    use pretty_assertions::assert_eq;
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+    };
+    use tempfile::TempDir;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    fn text(value: &str) -> CellValue {
+        CellValue::Text(value.to_string())
+    }
+
+    fn options() -> InferenceOptions {
+        InferenceOptions {
+            has_headers: true,
+            sample_rows: 2,
+        }
+    }
+
+    #[test]
+    fn test_preview_flags_cells_outside_sample() {
+        let rows = vec![
+            vec![
+                text("Employee"),
+                text("Hours"),
+            ],
+            vec![
+                text("Ada"),
+                text("8"),
+            ],
+            vec![
+                text("Grace"),
+                text("7"),
+            ],
+            vec![
+                CellValue::Empty,
+                CellValue::Empty,
+            ],
+            vec![
+                text("Linus"),
+                text("n/a"),
+            ],
+        ];
+
+        let preview = DataPreview::from_rows(&rows, &options(), 10);
+
+        assert_eq!(preview.schema.columns[1].sql_type, SqlType::Integer);
+        assert_eq!(preview.header(1), "Hours (Integer)");
+        assert_eq!(preview.total_rows, Some(3));
+        assert_eq!(preview.rows.len(), 3);
+        assert!(preview.rows[0][1].valid);
+        assert_eq!(
+            preview.rows[2][1],
+            PreviewCell {
+                text: "n/a".to_string(),
+                valid: false,
+            }
+        );
+        assert_eq!(preview.invalid_cells(), 1);
+    }
+
+    #[test]
+    fn test_preview_marks_cells_past_last_column() {
+        let rows = vec![
+            vec![text("Employee")],
+            vec![
+                text("Ada"),
+                text("stray"),
+            ],
+            vec![text("Grace")],
+        ];
+
+        let preview = DataPreview::from_rows(&rows, &options(), 10);
+
+        assert_eq!(preview.width(), 2);
+        assert_eq!(preview.header(1), "(extra)");
+        assert!(!preview.rows[0][1].valid);
+        assert_eq!(preview.rows[1].len(), 1);
+    }
+
+    #[test]
+    fn test_preview_stops_at_limit() {
+        let rows: Vec<Row> = (0..10).map(|i| vec![CellValue::Int(i)]).collect();
+
+        let preview = DataPreview::from_rows(&rows, &InferenceOptions::default(), 4);
+
+        assert_eq!(preview.rows.len(), 4);
+        assert_eq!(preview.total_rows, Some(10));
+    }
+
+    #[test]
+    fn test_preview_source_reads_sheet() {
+        let model = FileFormModel {
+            source_file: fixture("timesheet.xlsx"),
+            selected_sheet: Some("Hours".to_string()),
+            has_headers: true,
+            ..FileFormModel::default()
+        };
+
+        let preview = preview_source(&model, DEFAULT_PREVIEW_ROWS).expect("preview");
+
+        assert_eq!(preview.header(1), "Date (Date)");
+        assert_eq!(preview.rows.len(), 3);
+        assert_eq!(preview.rows[0][0].text, "Ada");
+        assert_eq!(preview.invalid_cells(), 0);
+    }
+
+    #[test]
+    fn test_preview_source_reads_only_needed_rows() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("hours.csv");
+        let lines: Vec<String> = (0..10).map(|i| format!("Ada,{i}")).collect();
+        fs::write(&source, format!("Employee,Hours\n{}\n", lines.join("\n"))).expect("write csv");
+        let model = FileFormModel {
+            source_file: source,
+            has_headers: true,
+            sample_rows: Some(3),
+            ..FileFormModel::default()
+        };
+
+        let preview = preview_source(&model, 2).expect("preview");
+        let whole = preview_source(&model, 20).expect("preview");
+
+        assert_eq!(preview.rows.len(), 2);
+        assert_eq!(preview.total_rows, None);
+        assert_eq!(preview.header(1), "Hours (Integer)");
+        assert_eq!(whole.rows.len(), 10);
+        assert_eq!(whole.total_rows, Some(10));
+    }
+
+    #[test]
+    fn test_preview_source_draws_fixed_width_layout() {
+        let dir = TempDir::new().expect("temp dir");
+        let source = dir.path().join("punches.txt");
+        fs::write(&source, "00417  750\n00418  8OO\n").expect("write punches");
+        let model = FileFormModel {
+            source_file: source,
+            fixed_width_columns: vec![
+                FixedWidthColumn::new("employee", 1, 5),
+                FixedWidthColumn::new("minutes", 7, 4).with_type(SqlType::Integer),
+            ],
+            column_mappings: vec![
+                ColumnMapping::new("employee")
+                    .to("employee_id")
+                    .with_type(SqlType::Real),
+            ],
+            ..FileFormModel::default()
+        };
+
+        let preview = preview_source(&model, DEFAULT_PREVIEW_ROWS).expect("preview");
+
+        assert_eq!(preview.header(0), "employee (Real)");
+        assert_eq!(preview.header(1), "minutes (Integer)");
+        assert!(!preview.rows[1][1].valid);
+        assert_eq!(preview.ruler.as_deref(), Some("|empl |min"));
+        assert_eq!(
+            preview.raw_lines,
+            vec![
+                "00417  750".to_string(),
+                "00418  8OO".to_string(),
+            ]
+        );
+    }
+}
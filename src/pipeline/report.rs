@@ -0,0 +1,109 @@
+// pipeline/report.rs
+
+use std::{fmt, path::PathBuf, time::Duration};
+
+use serde::{Serialize, Serializer};
+
+use crate::sources::MalformedRow;
+
+/// Outcome of importing one sheet, or a whole sheetless source.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SheetReport {
+    /// Sheet the rows were read from; `None` for CSV sources.
+    pub sheet: Option<String>,
+    pub table: String,
+    /// Non-blank data rows read from the source, excluding the header row.
+    pub rows_read: usize,
+    pub rows_written: usize,
+    /// Rows skipped because they could not be mapped onto the table.
+    pub rows_rejected: usize,
+    /// Source rows that could not be read cleanly, by line.
+    pub malformed_rows: Vec<MalformedRow>,
+    /// Time spent reading and writing the sheet; for sheets combined into
+    /// one table, the time spent reading it.
+    #[serde(rename = "elapsed_ms", serialize_with = "serialize_millis")]
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for SheetReport {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        if let Some(sheet) = &self.sheet {
+            write!(f, "{sheet} → ")?;
+        }
+        write!(
+            f,
+            "{}: {} read, {} written, {} rejected",
+            self.table, self.rows_read, self.rows_written, self.rows_rejected
+        )?;
+        if !self.malformed_rows.is_empty() {
+            write!(f, ", {} malformed", self.malformed_rows.len())?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of a single import run, with one [`SheetReport`] per sheet read.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct ImportReport {
+    pub source_file: PathBuf,
+    pub database_file: PathBuf,
+    pub sheets: Vec<SheetReport>,
+    #[serde(rename = "elapsed_ms", serialize_with = "serialize_millis")]
+    pub elapsed: Duration,
+}
+
+impl ImportReport {
+    /// Total rows read across all sheets.
+    pub fn rows_read(&self) -> usize {
+        self.sheets.iter().map(|sheet| sheet.rows_read).sum()
+    }
+
+    /// Total rows written across all sheets.
+    pub fn rows_written(&self) -> usize {
+        self.sheets.iter().map(|sheet| sheet.rows_written).sum()
+    }
+
+    /// Total rows rejected across all sheets.
+    pub fn rows_rejected(&self) -> usize {
+        self.sheets.iter().map(|sheet| sheet.rows_rejected).sum()
+    }
+
+    /// Total malformed source rows across all sheets.
+    pub fn malformed_rows(&self) -> usize {
+        self.sheets
+            .iter()
+            .map(|sheet| sheet.malformed_rows.len())
+            .sum()
+    }
+}
+
+/// Serializes a duration as fractional milliseconds for machine consumers.
+pub(crate) fn serialize_millis<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        writeln!(f, "Source file:   {}", self.source_file.to_string_lossy())?;
+        writeln!(f, "Database:      {}", self.database_file.to_string_lossy())?;
+        for sheet in &self.sheets {
+            writeln!(f, "Imported:      {sheet}")?;
+            for row in &sheet.malformed_rows {
+                writeln!(f, "Malformed:     {row}")?;
+            }
+        }
+        writeln!(f, "Rows read:     {}", self.rows_read())?;
+        writeln!(f, "Rows written:  {}", self.rows_written())?;
+        writeln!(f, "Rows rejected: {}", self.rows_rejected())?;
+        write!(f, "Elapsed:       {:.3}s", self.elapsed.as_secs_f64())
+    }
+}
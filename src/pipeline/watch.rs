@@ -0,0 +1,422 @@
+// pipeline/watch.rs
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc::{Receiver, channel},
+    time::{Duration, Instant},
+};
+
+use glob::Pattern;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::models::FileFormModel;
+
+use super::{BatchItem, FileStatus, batch::is_source_file, run_import};
+
+/// How long a file's size must stay the same before it is imported.
+pub const DEFAULT_SETTLE_TIME: Duration = Duration::from_secs(2);
+
+/// Subfolder of the watched folder that imported files are moved to.
+pub const ARCHIVE_FOLDER: &str = "archive";
+
+/// Subfolder of the watched folder that files failing to import are moved
+/// to.
+pub const FAILED_FOLDER: &str = "failed";
+
+/// Errors that stop a folder watch from starting.
+#[derive(Debug, Error)]
+pub enum WatchError {
+    #[error("Choose a folder to watch.")]
+    NoFolder,
+
+    #[error("'{0}' is not a valid file name pattern.")]
+    InvalidPattern(String),
+
+    #[error("Form is not valid: {}", .0.join(" "))]
+    Validation(Vec<String>),
+
+    #[error("Cannot watch '{}': {source}", path.display())]
+    Notify {
+        path: PathBuf,
+        #[source]
+        source: notify::Error,
+    },
+}
+
+/// Holds back changed files until their size stops changing, so a file
+/// still being copied into the folder is not imported half-written.
+#[derive(Debug)]
+pub struct SettleTracker {
+    settle_time: Duration,
+    /// Last seen size (if checked yet) and when it was last seen changing.
+    pending: HashMap<PathBuf, (Option<u64>, Instant)>,
+}
+
+impl SettleTracker {
+    /// A tracker that holds files back until their size has stayed the
+    /// same for `settle_time`.
+    pub fn new(settle_time: Duration) -> Self {
+        Self {
+            settle_time,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Notes that `path` was created or changed at `now`.
+    pub fn touch(
+        &mut self,
+        path: PathBuf,
+        now: Instant,
+    ) {
+        self.pending.insert(path, (None, now));
+    }
+
+    /// Returns the files whose size has not changed for the settle time,
+    /// in path order, and stops tracking them.
+    ///
+    /// `size_of` reports a file's current size; files it returns `None` for
+    /// have gone away and are dropped.
+    pub fn settled(
+        &mut self,
+        now: Instant,
+        size_of: impl Fn(&Path) -> Option<u64>,
+    ) -> Vec<PathBuf> {
+        let mut ready = Vec::new();
+        self.pending.retain(|path, (last_size, since)| {
+            let Some(size) = size_of(path) else {
+                return false;
+            };
+            if *last_size != Some(size) {
+                *last_size = Some(size);
+                *since = now;
+                return true;
+            }
+            if now.duration_since(*since) >= self.settle_time {
+                ready.push(path.clone());
+                return false;
+            }
+            true
+        });
+        ready.sort();
+        ready
+    }
+
+    /// Returns `true` if no file is waiting to settle.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// Watches a folder for new or changed source files and hands each one out
+/// for import once it has settled.
+///
+/// Only files directly in the folder are watched, so the [`ARCHIVE_FOLDER`]
+/// and [`FAILED_FOLDER`] subfolders are ignored. Files already in the folder
+/// when the watch starts are left alone.
+pub struct FolderWatch {
+    template: FileFormModel,
+    folder: PathBuf,
+    pattern: Option<Pattern>,
+    tracker: SettleTracker,
+    events: Receiver<notify::Result<Event>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl FolderWatch {
+    /// Starts watching the `watch_folder` of `model`; matching files are
+    /// imported with the rest of the model's settings, which are checked
+    /// first (see [`FileFormModel::validate_for_watch`]).
+    pub fn start(model: &FileFormModel) -> Result<Self, WatchError> {
+        if model.watch_folder.as_os_str().is_empty() {
+            return Err(WatchError::NoFolder);
+        }
+        model.validate_for_watch().map_err(WatchError::Validation)?;
+        let pattern = model
+            .watch_pattern
+            .as_deref()
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| {
+                Pattern::new(pattern).map_err(|_| WatchError::InvalidPattern(pattern.to_string()))
+            })
+            .transpose()?;
+
+        let notify_error = |source| WatchError::Notify {
+            path: model.watch_folder.clone(),
+            source,
+        };
+        // Events name files by absolute path, which a relative folder, as
+        // typed or saved in a profile, would never match.
+        let folder = fs::canonicalize(&model.watch_folder)
+            .map_err(|error| notify_error(notify::Error::io(error)))?;
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(notify_error)?;
+        watcher
+            .watch(&folder, RecursiveMode::NonRecursive)
+            .map_err(notify_error)?;
+        info!(folder = %folder.display(), "Watching folder");
+
+        Ok(Self {
+            template: model.clone(),
+            folder,
+            pattern,
+            tracker: SettleTracker::new(DEFAULT_SETTLE_TIME),
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    /// The folder being watched.
+    pub fn folder(&self) -> &Path {
+        &self.folder
+    }
+
+    /// Collects the changes seen since the last call and returns a form
+    /// model for each file that is ready to import.
+    pub fn poll(&mut self) -> Vec<FileFormModel> {
+        let now = Instant::now();
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                    for path in event.paths {
+                        if self.matches(&path) {
+                            self.tracker.touch(path, now);
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(error) => warn!(%error, "Folder watch error"),
+            }
+        }
+
+        self.tracker
+            .settled(now, |path| {
+                fs::metadata(path)
+                    .ok()
+                    .filter(|meta| meta.is_file())
+                    .map(|meta| meta.len())
+            })
+            .into_iter()
+            .map(|source_file| FileFormModel {
+                source_file,
+                batch_sources: Vec::new(),
+                ..self.template.clone()
+            })
+            .collect()
+    }
+
+    /// Returns `true` if `path` is a source file directly in the folder
+    /// whose name fits the pattern.
+    fn matches(
+        &self,
+        path: &Path,
+    ) -> bool {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        path.parent() == Some(self.folder.as_path())
+            && is_source_file(path)
+            && self
+                .pattern
+                .as_ref()
+                .is_none_or(|pattern| pattern.matches(&name))
+    }
+}
+
+/// Imports the source file of `model` and moves it to the
+/// [`ARCHIVE_FOLDER`] or [`FAILED_FOLDER`] next to it.
+///
+/// A file that imports but cannot be moved is reported as failed, since it
+/// would otherwise be imported again on its next change.
+pub fn import_watched_file(model: &FileFormModel) -> BatchItem {
+    let source = model.source_file.clone();
+    let result = run_import(model);
+    let subfolder = if result.is_ok() {
+        ARCHIVE_FOLDER
+    } else {
+        FAILED_FOLDER
+    };
+
+    let status = match (result, move_into(&source, subfolder)) {
+        (Ok(report), Ok(_)) => FileStatus::Imported { report },
+        (Ok(_), Err(error)) => FileStatus::Failed {
+            error: format!("Imported, but could not be moved to {ARCHIVE_FOLDER}/: {error}"),
+        },
+        (Err(error), Ok(_)) => FileStatus::Failed {
+            error: error.to_string(),
+        },
+        (Err(error), Err(move_error)) => {
+            warn!(
+                source = %source.display(),
+                error = %move_error,
+                "Could not move failed file to {FAILED_FOLDER}/"
+            );
+            FileStatus::Failed {
+                error: format!(
+                    "{error} It could not be moved to {FAILED_FOLDER}/ either: {move_error}"
+                ),
+            }
+        }
+    };
+    let item = BatchItem { source, status };
+    info!(%item, "Watched file processed");
+    item
+}
+
+/// Moves `path` into `subfolder` of its parent folder, creating the
+/// subfolder if needed. A file of the same name already there is kept by
+/// numbering the new one, as in `hours (1).xlsx`.
+fn move_into(
+    path: &Path,
+    subfolder: &str,
+) -> io::Result<PathBuf> {
+    let folder = path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(subfolder);
+    fs::create_dir_all(&folder)?;
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    let mut target = folder.join(format!("{stem}{extension}"));
+    let mut copy = 1;
+    while target.exists() {
+        target = folder.join(format!("{stem} ({copy}){extension}"));
+        copy += 1;
+    }
+
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::{env, path::Component};
+    use tempfile::TempDir;
+
+    /// Returns `path` relative to the current folder, climbing out of it
+    /// with `..` as far as the two differ.
+    fn relative_to_current(path: &Path) -> PathBuf {
+        let current = env::current_dir()
+            .and_then(fs::canonicalize)
+            .expect("current folder");
+        let path = fs::canonicalize(path).expect("canonical path");
+        let shared = current
+            .components()
+            .zip(path.components())
+            .take_while(|(a, b)| a == b)
+            .count();
+        current
+            .components()
+            .skip(shared)
+            .map(|_| Component::ParentDir)
+            .chain(path.components().skip(shared))
+            .collect()
+    }
+
+    #[test]
+    fn test_settle_tracker_waits_for_stable_size() {
+        let start = Instant::now();
+        let settle = Duration::from_secs(2);
+        let mut tracker = SettleTracker::new(settle);
+        let path = PathBuf::from("hours.csv");
+        tracker.touch(path.clone(), start);
+
+        // First look records the size; a change restarts the wait.
+        assert!(tracker.settled(start, |_| Some(10)).is_empty());
+        let grown = start + Duration::from_secs(1);
+        assert!(tracker.settled(grown, |_| Some(20)).is_empty());
+        assert!(tracker.settled(start + settle, |_| Some(20)).is_empty());
+
+        assert_eq!(tracker.settled(grown + settle, |_| Some(20)), vec![path]);
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_settle_tracker_drops_removed_files() {
+        let now = Instant::now();
+        let mut tracker = SettleTracker::new(Duration::ZERO);
+        tracker.touch(PathBuf::from("gone.csv"), now);
+
+        assert!(tracker.settled(now, |_| None).is_empty());
+        assert!(tracker.is_empty());
+    }
+
+    #[test]
+    fn test_import_watched_file_archives_and_fails() {
+        let dir = TempDir::new().expect("temp dir");
+        let good = dir.path().join("hours.csv");
+        let bad = dir.path().join("hours.xlsx");
+        fs::write(&good, "Employee,Hours\nAda,7.5\n").expect("write csv");
+        fs::write(&bad, "not a workbook").expect("write xlsx");
+        fs::create_dir(dir.path().join(ARCHIVE_FOLDER)).expect("archive");
+        fs::write(dir.path().join(ARCHIVE_FOLDER).join("hours.csv"), "").expect("old copy");
+        let model = |source: &Path| FileFormModel {
+            source_file: source.to_path_buf(),
+            database_file: dir.path().join("out.db"),
+            selected_sheet: Some("Hours".to_string()),
+            has_headers: true,
+            ..FileFormModel::default()
+        };
+
+        let imported = import_watched_file(&model(&good));
+        let failed = import_watched_file(&model(&bad));
+
+        assert!(matches!(imported.status, FileStatus::Imported { .. }));
+        assert!(dir.path().join("archive/hours (1).csv").is_file());
+        assert!(matches!(failed.status, FileStatus::Failed { .. }));
+        assert!(dir.path().join("failed/hours.xlsx").is_file());
+        assert!(!good.exists() && !bad.exists());
+    }
+
+    #[test]
+    fn test_start_resolves_relative_folder() {
+        let dir = TempDir::new().expect("temp dir");
+        let model = FileFormModel {
+            watch_folder: relative_to_current(dir.path()),
+            watch_pattern: Some("*.csv".to_string()),
+            database_file: dir.path().join("out.db"),
+            ..FileFormModel::default()
+        };
+
+        assert!(model.watch_folder.is_relative());
+
+        let watch = FolderWatch::start(&model).expect("watch should start");
+
+        assert!(watch.folder().is_absolute());
+        assert!(watch.matches(&watch.folder().join("hours.csv")));
+    }
+
+    #[test]
+    fn test_start_validates_form() {
+        let dir = TempDir::new().expect("temp dir");
+        let model = FileFormModel {
+            watch_folder: dir.path().to_path_buf(),
+            watch_pattern: Some("*.xlsx".to_string()),
+            database_file: dir.path().join("out.db"),
+            ..FileFormModel::default()
+        };
+
+        let result = FolderWatch::start(&model);
+
+        assert!(
+            matches!(&result, Err(WatchError::Validation(errors)) if errors == &vec![
+                "Sheet selection is required for workbook sources.".to_string(),
+            ]),
+            "expected Validation"
+        );
+    }
+}
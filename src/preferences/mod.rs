@@ -1,22 +1,35 @@
 // preferences
 
-use gpui::{Pixels, Size, px};
+pub mod store;
 
-#[derive(Debug, Clone, Copy)]
+use gpui::{
+    App, Bounds, DisplayId, Global, Pixels, PlatformDisplay, Point, Size, Window, WindowBounds,
+    point, px, size,
+};
+use tracing::warn;
+
+pub use store::{
+    DefaultDirectories, MAX_RECENT, Preferences, PreferencesError, PreferencesStore, RecentPaths,
+    ScreenRect, WindowSettings, WindowState,
+};
+
+impl Global for PreferencesStore {}
+
+/// Window placement used when opening the main window.
+#[derive(Debug, Clone)]
 pub struct WindowPreferences {
     pub size: Size<Pixels>,
-    // TODO: Implement window centering once we determine the correct
-    // gpui API for getting display bounds
+    /// Top-left corner of the restored window, or `None` to center it.
+    pub origin: Option<Point<Pixels>>,
+    pub state: WindowState,
+    /// Identifier of the display to open on; the primary display is used
+    /// when it is unset or no longer connected.
+    pub display: Option<String>,
 }
 
 impl Default for WindowPreferences {
     fn default() -> Self {
-        Self {
-            size: Size {
-                width: px(800.0),
-                height: px(600.0),
-            },
-        }
+        Self::new(800.0, 600.0)
     }
 }
 
@@ -30,6 +43,131 @@ impl WindowPreferences {
                 width: width.into(),
                 height: height.into(),
             },
+            origin: None,
+            state: WindowState::default(),
+            display: None,
         }
     }
+
+    /// Resolves the saved placement against the displays that are currently
+    /// connected.
+    ///
+    /// A saved position is clamped onto its display so the window is never
+    /// restored off-screen; without one the window is centered.
+    pub fn window_bounds(
+        &self,
+        cx: &App,
+    ) -> (WindowBounds, Option<DisplayId>) {
+        let display = self
+            .display
+            .as_deref()
+            .and_then(|uuid| {
+                cx.displays()
+                    .into_iter()
+                    .find(|display| display_uuid(display.as_ref()).as_deref() == Some(uuid))
+            })
+            .or_else(|| cx.primary_display());
+        let display_id = display.as_ref().map(|display| display.id());
+
+        let bounds = match (self.origin, &display) {
+            (Some(origin), Some(display)) => {
+                let saved = ScreenRect {
+                    x: origin.x.into(),
+                    y: origin.y.into(),
+                    width: self.size.width.into(),
+                    height: self.size.height.into(),
+                };
+                to_bounds(saved.clamp_to(to_rect(display.bounds())))
+            }
+            _ => Bounds::centered(display_id, self.size, cx),
+        };
+
+        let bounds = match self.state {
+            WindowState::Windowed => WindowBounds::Windowed(bounds),
+            WindowState::Maximized => WindowBounds::Maximized(bounds),
+            WindowState::Fullscreen => WindowBounds::Fullscreen(bounds),
+        };
+        (bounds, display_id)
+    }
+}
+
+impl From<&Preferences> for WindowPreferences {
+    fn from(preferences: &Preferences) -> Self {
+        let window = &preferences.window;
+        Self {
+            origin: window.rect().map(|rect| point(px(rect.x), px(rect.y))),
+            state: window.state,
+            display: window.display.clone(),
+            ..Self::new(window.width, window.height)
+        }
+    }
+}
+
+/// Captures the current placement of `window` for saving.
+///
+/// The size and position are the restore bounds, so a maximized window
+/// keeps its previous windowed size.
+pub fn window_settings(
+    window: &Window,
+    cx: &App,
+) -> WindowSettings {
+    let (state, bounds) = match window.window_bounds() {
+        WindowBounds::Windowed(bounds) => (WindowState::Windowed, bounds),
+        WindowBounds::Maximized(bounds) => (WindowState::Maximized, bounds),
+        WindowBounds::Fullscreen(bounds) => (WindowState::Fullscreen, bounds),
+    };
+    let rect = to_rect(bounds);
+
+    WindowSettings {
+        width: rect.width,
+        height: rect.height,
+        x: Some(rect.x),
+        y: Some(rect.y),
+        state,
+        display: window
+            .display(cx)
+            .and_then(|display| display_uuid(display.as_ref())),
+    }
+}
+
+fn display_uuid(display: &dyn PlatformDisplay) -> Option<String> {
+    display.uuid().ok().map(|uuid| uuid.to_string())
+}
+
+fn to_rect(bounds: Bounds<Pixels>) -> ScreenRect {
+    ScreenRect {
+        x: bounds.origin.x.into(),
+        y: bounds.origin.y.into(),
+        width: bounds.size.width.into(),
+        height: bounds.size.height.into(),
+    }
+}
+
+fn to_bounds(rect: ScreenRect) -> Bounds<Pixels> {
+    Bounds {
+        origin: point(px(rect.x), px(rect.y)),
+        size: size(px(rect.width), px(rect.height)),
+    }
+}
+
+/// Returns a copy of the app-wide preferences, or defaults if no store has
+/// been installed as a global.
+pub fn current_preferences(cx: &App) -> Preferences {
+    cx.try_global::<PreferencesStore>()
+        .map(|store| store.preferences().clone())
+        .unwrap_or_default()
+}
+
+/// Applies `change` to the app-wide preferences store and saves it if
+/// anything changed. Does nothing if no store has been installed.
+pub fn update_preferences(
+    cx: &mut App,
+    change: impl FnOnce(&mut Preferences),
+) {
+    if !cx.has_global::<PreferencesStore>() {
+        return;
+    }
+    if let Err(error) = cx.global_mut::<PreferencesStore>().update(change) {
+        warn!(%error, "Could not save preferences");
+    }
 }
@@ -0,0 +1,823 @@
+// preferences/store.rs
+
+use std::{
+    fs, io, iter,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use toml::{Table, Value};
+use tracing::{debug, info, warn};
+
+use crate::{
+    logging::app_name,
+    models::{DbBackend, FileFormModel, LogLevel},
+};
+
+/// Current layout version written to the preferences file.
+pub const PREFERENCES_VERSION: u32 = 1;
+
+/// File name of the preferences file inside the app's config directory.
+pub const PREFERENCES_FILE: &str = "preferences.toml";
+
+/// Number of entries kept in each recently-used list.
+pub const MAX_RECENT: usize = 10;
+
+/// Errors raised while saving preferences.
+///
+/// Loading never fails: unreadable files and values fall back to defaults.
+#[derive(Debug, Error)]
+pub enum PreferencesError {
+    #[error("Cannot write preferences to '{}': {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Cannot serialize preferences: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// How the main window was shown when it was last closed.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowState {
+    #[default]
+    Windowed,
+    Maximized,
+    Fullscreen,
+}
+
+/// Saved window placement in logical pixels.
+///
+/// The size and position describe the restored (un-maximized) bounds so a
+/// maximized window still returns to its previous size when toggled back.
+/// A window without a saved position is centered on its display.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub width: f32,
+    pub height: f32,
+    pub x: Option<f32>,
+    pub y: Option<f32>,
+    pub state: WindowState,
+    /// Stable identifier of the display the window was on.
+    pub display: Option<String>,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 800.0,
+            height: 600.0,
+            x: None,
+            y: None,
+            state: WindowState::default(),
+            display: None,
+        }
+    }
+}
+
+impl WindowSettings {
+    /// Returns the saved bounds, if a position was recorded.
+    pub fn rect(&self) -> Option<ScreenRect> {
+        Some(ScreenRect {
+            x: self.x?,
+            y: self.y?,
+            width: self.width,
+            height: self.height,
+        })
+    }
+}
+
+/// A rectangle in logical screen coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScreenRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl ScreenRect {
+    /// Shrinks `self` to fit inside `display` and moves it so it lies fully
+    /// on that display.
+    ///
+    /// Used when a saved window would otherwise open off-screen, e.g. after
+    /// a monitor was unplugged or its resolution changed.
+    pub fn clamp_to(
+        self,
+        display: ScreenRect,
+    ) -> ScreenRect {
+        let width = self.width.min(display.width);
+        let height = self.height.min(display.height);
+        ScreenRect {
+            x: self.x.clamp(display.x, display.x + display.width - width),
+            y: self.y.clamp(display.y, display.y + display.height - height),
+            width,
+            height,
+        }
+    }
+}
+
+/// Folders the file pickers open in.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct DefaultDirectories {
+    pub source: Option<PathBuf>,
+    pub database: Option<PathBuf>,
+    pub log: Option<PathBuf>,
+}
+
+/// Recently used paths, newest first.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RecentPaths {
+    pub source_files: Vec<PathBuf>,
+    pub database_files: Vec<PathBuf>,
+    pub log_folders: Vec<PathBuf>,
+}
+
+impl RecentPaths {
+    /// Moves the paths of a submitted form to the front of their lists.
+    pub fn remember(
+        &mut self,
+        model: &FileFormModel,
+    ) {
+        push_recent(&mut self.source_files, &model.source_file);
+        push_recent(&mut self.database_files, &model.database_file);
+        push_recent(&mut self.log_folders, &model.log_directory);
+    }
+
+    /// Drops entries whose file or folder no longer exists.
+    pub fn prune_missing(&mut self) {
+        for list in [
+            &mut self.source_files,
+            &mut self.database_files,
+            &mut self.log_folders,
+        ] {
+            list.retain(|path| path.exists());
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+fn push_recent(
+    list: &mut Vec<PathBuf>,
+    path: &Path,
+) {
+    if path.as_os_str().is_empty() {
+        return;
+    }
+    list.retain(|existing| existing != path);
+    list.insert(0, path.to_path_buf());
+    list.truncate(MAX_RECENT);
+}
+
+/// Everything persisted between sessions.
+///
+/// Missing keys take their default values and unknown keys are ignored, so
+/// files written by older or newer builds still load. [`PreferencesStore`]
+/// writes the unknown keys back when it saves.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub version: u32,
+    pub default_backend: DbBackend,
+    pub log_level: LogLevel,
+    pub log_stdout: bool,
+    pub window: WindowSettings,
+    pub directories: DefaultDirectories,
+    /// Form values from the last submit or window close.
+    pub last_form: Option<FileFormModel>,
+    pub recent: RecentPaths,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            version: PREFERENCES_VERSION,
+            default_backend: DbBackend::default(),
+            log_level: LogLevel::default(),
+            log_stdout: false,
+            window: WindowSettings::default(),
+            directories: DefaultDirectories::default(),
+            last_form: None,
+            recent: RecentPaths::default(),
+        }
+    }
+}
+
+impl Preferences {
+    /// Takes the backend, logging choices and picker folders from a
+    /// submitted form, adds its paths to the recent lists and keeps the form
+    /// itself for the next launch.
+    ///
+    /// Folders are only replaced for fields that were filled in.
+    pub fn remember_model(
+        &mut self,
+        model: &FileFormModel,
+    ) {
+        self.remember_form(model);
+        self.recent.remember(model);
+        self.default_backend = model.db_backend;
+        self.log_level = model.log_level;
+        self.log_stdout = model.log_stdout;
+
+        if let Some(dir) = parent_dir(&model.source_file) {
+            self.directories.source = Some(dir);
+        }
+        if let Some(dir) = parent_dir(&model.database_file) {
+            self.directories.database = Some(dir);
+        }
+        if !model.log_directory.as_os_str().is_empty() {
+            self.directories.log = Some(model.log_directory.clone());
+        }
+    }
+
+    /// Keeps a snapshot of the form so it can be restored on the next launch.
+    pub fn remember_form(
+        &mut self,
+        model: &FileFormModel,
+    ) {
+        self.last_form = Some(model.clone());
+    }
+}
+
+fn parent_dir(path: &Path) -> Option<PathBuf> {
+    path.parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+}
+
+/// Loads preferences from disk and writes them back whenever they change.
+#[derive(Debug)]
+pub struct PreferencesStore {
+    path: PathBuf,
+    preferences: Preferences,
+    /// Keys of the loaded file this build does not know, such as settings
+    /// of a newer build, kept so saving does not drop them.
+    unknown: Table,
+}
+
+impl PreferencesStore {
+    /// Returns `$XDG_CONFIG_HOME/<app_name>/preferences.toml`, or the
+    /// platform's equivalent config directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(app_name()).join(PREFERENCES_FILE))
+    }
+
+    /// Loads the store from [`PreferencesStore::default_path`].
+    ///
+    /// Without a config directory the store keeps defaults in memory and
+    /// saves into the working directory.
+    pub fn load_default() -> Self {
+        let path = Self::default_path().unwrap_or_else(|| PathBuf::from(PREFERENCES_FILE));
+        Self::load(path)
+    }
+
+    /// Loads preferences from `path`, falling back to defaults.
+    ///
+    /// A value that does not fit its setting, such as an unknown log level,
+    /// is ignored with a warning and only that setting takes its default. A
+    /// file that is not TOML at all is renamed to `<name>.bak` so the next
+    /// save does not silently destroy it.
+    pub fn load(path: PathBuf) -> Self {
+        let (preferences, unknown) = match fs::read_to_string(&path) {
+            Ok(text) => parse_preferences(&text).unwrap_or_else(|error| {
+                warn!(path = %path.display(), %error, "Ignoring unreadable preferences");
+                backup_unreadable(&path);
+                (Preferences::default(), Table::new())
+            }),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                debug!(path = %path.display(), "No preferences file yet");
+                (Preferences::default(), Table::new())
+            }
+            Err(error) => {
+                warn!(path = %path.display(), %error, "Cannot read preferences");
+                (Preferences::default(), Table::new())
+            }
+        };
+
+        Self {
+            path,
+            preferences,
+            unknown,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn preferences(&self) -> &Preferences {
+        &self.preferences
+    }
+
+    /// Applies `change` and saves the file if anything changed.
+    ///
+    /// Returns whether the preferences were modified.
+    pub fn update(
+        &mut self,
+        change: impl FnOnce(&mut Preferences),
+    ) -> Result<bool, PreferencesError> {
+        let mut updated = self.preferences.clone();
+        change(&mut updated);
+        if updated == self.preferences {
+            return Ok(false);
+        }
+
+        self.preferences = updated;
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Writes the preferences file, creating its directory if needed.
+    ///
+    /// Keys the loaded file held that this build does not know are written
+    /// back, and a newer file's version is kept. The file is written to a
+    /// temporary sibling and renamed into place so a crash mid-write cannot
+    /// leave a truncated file behind.
+    pub fn save(&self) -> Result<(), PreferencesError> {
+        let io_error = |source| PreferencesError::Io {
+            path: self.path.clone(),
+            source,
+        };
+
+        let mut preferences = self.preferences.clone();
+        preferences.version = preferences.version.max(PREFERENCES_VERSION);
+        let mut table = Table::try_from(&preferences)?;
+        add_missing(&mut table, &self.unknown);
+        let text = toml::to_string_pretty(&table)?;
+
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        let temp = self.path.with_extension("toml.tmp");
+        fs::write(&temp, text).map_err(io_error)?;
+        fs::rename(&temp, &self.path).map_err(io_error)?;
+
+        info!(path = %self.path.display(), "Saved preferences");
+        Ok(())
+    }
+}
+
+/// Parses a preferences file, returning the preferences and the keys of
+/// the file they do not use.
+///
+/// Values that do not fit their setting are left out with a warning, so
+/// they take their defaults; see [`merge_readable`].
+fn parse_preferences(text: &str) -> Result<(Preferences, Table), toml::de::Error> {
+    let mut loaded: Table = toml::from_str(text)?;
+    migrate(&mut loaded);
+
+    let mut readable = Table::new();
+    merge_readable(&mut readable, &mut Vec::new(), loaded);
+    let preferences = Preferences::deserialize(readable.clone())?;
+    let known = Table::try_from(&preferences).unwrap_or_default();
+    Ok((preferences, unknown_keys(&readable, &known)))
+}
+
+/// Copies the keys of `loaded` one at a time into the table at `path` of
+/// `readable`, keeping each one only if `readable` still deserializes as
+/// [`Preferences`]. A table that does not is copied key by key in turn.
+fn merge_readable(
+    readable: &mut Table,
+    path: &mut Vec<String>,
+    loaded: Table,
+) {
+    for (key, value) in loaded {
+        if try_insert(readable, path, &key, value.clone()) {
+            continue;
+        }
+        if let Value::Table(inner) = value
+            && try_insert(readable, path, &key, Value::Table(Table::new()))
+        {
+            path.push(key);
+            merge_readable(readable, path, inner);
+            path.pop();
+            continue;
+        }
+
+        let setting = path
+            .iter()
+            .chain(iter::once(&key))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(".");
+        warn!(setting, "Ignoring unreadable preference");
+    }
+}
+
+/// Inserts `key` into the table at `path` of `readable` if the result still
+/// deserializes as [`Preferences`]; returns whether it did.
+fn try_insert(
+    readable: &mut Table,
+    path: &[String],
+    key: &str,
+    value: Value,
+) -> bool {
+    let mut candidate = readable.clone();
+    let Some(table) = path.iter().try_fold(&mut candidate, |table, name| {
+        table.get_mut(name)?.as_table_mut()
+    }) else {
+        return false;
+    };
+    table.insert(key.to_string(), value);
+    if Preferences::deserialize(candidate.clone()).is_err() {
+        return false;
+    }
+    *readable = candidate;
+    true
+}
+
+/// Returns the keys of `loaded`, at any depth, that `known` lacks.
+fn unknown_keys(
+    loaded: &Table,
+    known: &Table,
+) -> Table {
+    let mut unknown = Table::new();
+    for (key, value) in loaded {
+        match (value, known.get(key)) {
+            (_, None) => {
+                unknown.insert(key.clone(), value.clone());
+            }
+            (Value::Table(loaded), Some(Value::Table(known))) => {
+                let nested = unknown_keys(loaded, known);
+                if !nested.is_empty() {
+                    unknown.insert(key.clone(), Value::Table(nested));
+                }
+            }
+            _ => {}
+        }
+    }
+    unknown
+}
+
+/// Adds the keys of `extra`, at any depth, that `table` lacks.
+fn add_missing(
+    table: &mut Table,
+    extra: &Table,
+) {
+    for (key, value) in extra {
+        match (table.get_mut(key), value) {
+            (None, _) => {
+                table.insert(key.clone(), value.clone());
+            }
+            (Some(Value::Table(table)), Value::Table(extra)) => add_missing(table, extra),
+            _ => {}
+        }
+    }
+}
+
+/// Upgrades an older preferences layout in place to
+/// [`PREFERENCES_VERSION`]; a newer file keeps its version.
+///
+/// Version 0 files predate the `version` key and kept everything at the top
+/// level: `window_width`, `window_height` and `backend`.
+fn migrate(table: &mut Table) {
+    let version = table
+        .get("version")
+        .and_then(Value::as_integer)
+        .unwrap_or(0);
+
+    if version < 1 {
+        debug!(from = version, to = 1, "Migrating preferences");
+        let mut window = Table::new();
+        if let Some(width) = table.remove("window_width") {
+            window.insert("width".to_string(), width);
+        }
+        if let Some(height) = table.remove("window_height") {
+            window.insert("height".to_string(), height);
+        }
+        if !window.is_empty() {
+            table.insert("window".to_string(), Value::Table(window));
+        }
+        if let Some(backend) = table.remove("backend") {
+            table.insert("default_backend".to_string(), backend);
+        }
+    }
+
+    if version < i64::from(PREFERENCES_VERSION) {
+        table.insert(
+            "version".to_string(),
+            Value::Integer(i64::from(PREFERENCES_VERSION)),
+        );
+    }
+}
+
+fn backup_unreadable(path: &Path) {
+    let backup = path.with_extension("toml.bak");
+    if let Err(error) = fs::rename(path, &backup) {
+        warn!(path = %path.display(), %error, "Could not back up unreadable preferences");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    use crate::models::WriteMode;
+
+    fn store_in(dir: &TempDir) -> PreferencesStore {
+        PreferencesStore::load(dir.path().join("app").join(PREFERENCES_FILE))
+    }
+
+    #[test]
+    fn test_load_missing_file_uses_defaults() {
+        let dir = TempDir::new().expect("temp dir");
+        assert_eq!(store_in(&dir).preferences(), &Preferences::default());
+    }
+
+    #[test]
+    fn test_update_saves_and_reloads() {
+        let dir = TempDir::new().expect("temp dir");
+        let mut store = store_in(&dir);
+
+        let changed = store
+            .update(|prefs| {
+                prefs.default_backend = DbBackend::PostgreSql;
+                prefs.window.width = 1024.0;
+            })
+            .expect("save");
+
+        assert!(changed);
+        assert_eq!(store_in(&dir).preferences(), store.preferences());
+    }
+
+    #[test]
+    fn test_update_without_change_does_not_write() {
+        let dir = TempDir::new().expect("temp dir");
+        let mut store = store_in(&dir);
+
+        let changed = store.update(|_| {}).expect("no-op");
+
+        assert!(!changed);
+        assert!(!store.path().exists());
+    }
+
+    #[test]
+    fn test_parse_migrates_version_zero() {
+        let text = "window_width = 1280.0\nwindow_height = 720.0\nbackend = \"MySQL\"\n";
+
+        let prefs = parse_preferences(text).expect("parse").0;
+
+        assert_eq!(prefs.version, PREFERENCES_VERSION);
+        assert_eq!(prefs.window.width, 1280.0);
+        assert_eq!(prefs.window.height, 720.0);
+        assert_eq!(prefs.default_backend, DbBackend::MySql);
+    }
+
+    #[test]
+    fn test_parse_ignores_unknown_keys_and_fills_missing() {
+        let text = "version = 1\ntheme = \"dark\"\nlog_level = \"DEBUG\"\n";
+
+        let (prefs, unknown) = parse_preferences(text).expect("parse");
+
+        assert_eq!(prefs.log_level, LogLevel::Debug);
+        assert_eq!(prefs.window, WindowSettings::default());
+        assert_eq!(unknown.get("theme"), Some(&Value::from("dark")));
+        assert_eq!(unknown.len(), 1);
+    }
+
+    #[test]
+    fn test_load_unreadable_value_keeps_other_settings() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join(PREFERENCES_FILE);
+        fs::write(
+            &path,
+            "log_level = \"LOUD\"\nlog_stdout = true\n[window]\nwidth = 1024.0\nheight = \"tall\"\n",
+        )
+        .expect("write");
+
+        let store = PreferencesStore::load(path.clone());
+
+        let prefs = store.preferences();
+        assert_eq!(prefs.log_level, LogLevel::default());
+        assert!(prefs.log_stdout);
+        assert_eq!(prefs.window.width, 1024.0);
+        assert_eq!(prefs.window.height, WindowSettings::default().height);
+        assert!(!path.with_extension("toml.bak").exists());
+    }
+
+    #[test]
+    fn test_load_unreadable_file_backs_up() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join(PREFERENCES_FILE);
+        fs::write(&path, "log_level = ").expect("write");
+
+        let store = PreferencesStore::load(path.clone());
+
+        assert_eq!(store.preferences(), &Preferences::default());
+        assert!(path.with_extension("toml.bak").exists());
+    }
+
+    #[test]
+    fn test_save_keeps_unknown_keys_and_newer_version() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join(PREFERENCES_FILE);
+        fs::write(
+            &path,
+            "version = 3\ntheme = \"dark\"\n[window]\nwidth = 1024.0\nopacity = 0.9\n",
+        )
+        .expect("write");
+        let mut store = PreferencesStore::load(path.clone());
+
+        store
+            .update(|prefs| prefs.log_level = LogLevel::Debug)
+            .expect("save");
+
+        let saved: Table = toml::from_str(&fs::read_to_string(&path).expect("read")).expect("toml");
+        assert_eq!(saved.get("version"), Some(&Value::Integer(3)));
+        assert_eq!(saved.get("theme"), Some(&Value::from("dark")));
+        assert_eq!(saved["window"].get("opacity"), Some(&Value::Float(0.9)));
+        assert_eq!(saved["window"].get("width"), Some(&Value::Float(1024.0)));
+        assert_eq!(
+            PreferencesStore::load(path).preferences().log_level,
+            LogLevel::Debug
+        );
+    }
+
+    #[test]
+    fn test_remember_model_keeps_unset_folders() {
+        let mut prefs = Preferences {
+            directories: DefaultDirectories {
+                log: Some(PathBuf::from("/var/log/loader")),
+                ..DefaultDirectories::default()
+            },
+            ..Preferences::default()
+        };
+        let model = FileFormModel {
+            source_file: PathBuf::from("/data/in/hours.xlsx"),
+            database_file: PathBuf::from("out.db"),
+            log_level: LogLevel::Trace,
+            ..FileFormModel::default()
+        };
+
+        prefs.remember_model(&model);
+
+        assert_eq!(
+            prefs.directories,
+            DefaultDirectories {
+                source: Some(PathBuf::from("/data/in")),
+                database: None,
+                log: Some(PathBuf::from("/var/log/loader")),
+            }
+        );
+        assert_eq!(prefs.log_level, LogLevel::Trace);
+    }
+
+    #[test]
+    fn test_window_settings_round_trip() {
+        let prefs = Preferences {
+            window: WindowSettings {
+                width: 1200.0,
+                height: 900.0,
+                x: Some(40.0),
+                y: Some(-20.0),
+                state: WindowState::Maximized,
+                display: Some("a1b2".to_string()),
+            },
+            ..Preferences::default()
+        };
+
+        let text = toml::to_string_pretty(&prefs).expect("serialize");
+        assert!(text.contains("state = \"maximized\""));
+        assert_eq!(parse_preferences(&text).expect("parse").0, prefs);
+    }
+
+    #[test]
+    fn test_window_rect_requires_position() {
+        let mut settings = WindowSettings::default();
+        assert_eq!(settings.rect(), None);
+
+        settings.x = Some(10.0);
+        settings.y = Some(20.0);
+        assert_eq!(
+            settings.rect(),
+            Some(ScreenRect {
+                x: 10.0,
+                y: 20.0,
+                width: 800.0,
+                height: 600.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_clamp_to_moves_window_onto_display() {
+        let display = ScreenRect {
+            x: 0.0,
+            y: 0.0,
+            width: 1920.0,
+            height: 1080.0,
+        };
+        let off_screen = ScreenRect {
+            x: 2500.0,
+            y: -300.0,
+            width: 800.0,
+            height: 600.0,
+        };
+
+        assert_eq!(
+            off_screen.clamp_to(display),
+            ScreenRect {
+                x: 1120.0,
+                y: 0.0,
+                width: 800.0,
+                height: 600.0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_clamp_to_shrinks_oversized_window() {
+        let display = ScreenRect {
+            x: 1920.0,
+            y: 0.0,
+            width: 1280.0,
+            height: 720.0,
+        };
+        let oversized = ScreenRect {
+            x: 1900.0,
+            y: 100.0,
+            width: 2560.0,
+            height: 1440.0,
+        };
+
+        assert_eq!(oversized.clamp_to(display), display);
+    }
+
+    #[test]
+    fn test_last_form_round_trip() {
+        let mut prefs = Preferences::default();
+        prefs.remember_form(&FileFormModel {
+            source_file: PathBuf::from("/data/in/hours.xlsx"),
+            database_file: PathBuf::from("/data/out/hours.db"),
+            selected_sheet: Some("Hours".to_string()),
+            has_headers: true,
+            write_mode: WriteMode::UpsertByKey,
+            key_columns: vec!["Employee".to_string()],
+            ..FileFormModel::default()
+        });
+
+        let text = toml::to_string_pretty(&prefs).expect("serialize");
+        assert!(text.contains("[last_form]"));
+        assert_eq!(parse_preferences(&text).expect("parse").0, prefs);
+    }
+
+    #[test]
+    fn test_recent_paths_move_to_front_and_cap() {
+        let mut recent = RecentPaths::default();
+        for i in 0..MAX_RECENT + 2 {
+            recent.remember(&FileFormModel {
+                source_file: PathBuf::from(format!("{i}.csv")),
+                ..FileFormModel::default()
+            });
+        }
+        recent.remember(&FileFormModel {
+            source_file: PathBuf::from("5.csv"),
+            ..FileFormModel::default()
+        });
+
+        assert_eq!(recent.source_files.len(), MAX_RECENT);
+        assert_eq!(recent.source_files[0], PathBuf::from("5.csv"));
+        assert_eq!(recent.source_files[1], PathBuf::from("11.csv"));
+        assert_eq!(
+            recent
+                .source_files
+                .iter()
+                .filter(|path| path.as_path() == Path::new("5.csv"))
+                .count(),
+            1
+        );
+        assert!(recent.database_files.is_empty());
+    }
+
+    #[test]
+    fn test_recent_paths_prune_missing() {
+        let dir = TempDir::new().expect("temp dir");
+        let kept = dir.path().join("hours.csv");
+        fs::write(&kept, "Employee\n").expect("write");
+        let mut recent = RecentPaths {
+            source_files: vec![
+                dir.path().join("gone.csv"),
+                kept.clone(),
+            ],
+            log_folders: vec![dir.path().to_path_buf()],
+            ..RecentPaths::default()
+        };
+
+        recent.prune_missing();
+
+        assert_eq!(recent.source_files, vec![kept]);
+        assert_eq!(recent.log_folders, vec![dir.path().to_path_buf()]);
+    }
+}
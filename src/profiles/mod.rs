@@ -0,0 +1,393 @@
+// profiles
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::{logging::app_name, models::FileFormModel};
+
+/// Name of the folder holding profiles inside the app's config directory.
+pub const PROFILES_DIR: &str = "profiles";
+
+/// Extension of profile files.
+pub const PROFILE_EXTENSION: &str = "toml";
+
+/// Errors raised while reading or writing profiles.
+#[derive(Debug, Error)]
+pub enum ProfileError {
+    #[error("Profile name '{0}' must contain at least one letter or digit")]
+    InvalidName(String),
+
+    #[error("No profile named '{0}'")]
+    NotFound(String),
+
+    #[error("Profile name '{name}' would replace the profile '{existing}'; choose another name")]
+    NameTaken { name: String, existing: String },
+
+    #[error("Cannot access profile '{}': {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Cannot parse profile '{}': {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+
+    #[error("Cannot serialize profile: {0}")]
+    Serialize(#[from] toml::ser::Error),
+}
+
+/// A named, saved set of form values, such as
+/// "Weekly Timesheet → payroll.db".
+///
+/// Each profile is stored in its own TOML file so profiles can be shared
+/// and kept under version control.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Profile {
+    pub name: String,
+    pub form: FileFormModel,
+}
+
+impl Profile {
+    pub fn new(
+        name: impl Into<String>,
+        form: FileFormModel,
+    ) -> Self {
+        Self {
+            name: name.into().trim().to_string(),
+            form,
+        }
+    }
+
+    /// Reads a profile from a TOML file.
+    pub fn load(path: &Path) -> Result<Self, ProfileError> {
+        let text = fs::read_to_string(path).map_err(|source| ProfileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| ProfileError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+}
+
+/// Turns a profile name into a file stem: lowercase letters and digits
+/// separated by single dashes.
+///
+/// Different names can share a stem, such as "Weekly Timesheet" and
+/// "weekly-timesheet"; [`ProfileLibrary::save`] refuses the second one.
+/// Returns `None` for names without any letter or digit.
+pub fn file_stem(name: &str) -> Option<String> {
+    let stem = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+
+    (!stem.is_empty()).then_some(stem)
+}
+
+/// A folder of profile files.
+#[derive(Clone, Debug)]
+pub struct ProfileLibrary {
+    dir: PathBuf,
+}
+
+impl ProfileLibrary {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Returns `$XDG_CONFIG_HOME/<app_name>/profiles`, or the platform's
+    /// equivalent config directory.
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(app_name()).join(PROFILES_DIR))
+    }
+
+    /// Opens the library in [`ProfileLibrary::default_dir`], or `None` when
+    /// the platform has no config directory.
+    pub fn open_default() -> Option<Self> {
+        Self::default_dir().map(Self::new)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the file a profile called `name` is saved to.
+    pub fn path_for(
+        &self,
+        name: &str,
+    ) -> Result<PathBuf, ProfileError> {
+        let stem = file_stem(name).ok_or_else(|| ProfileError::InvalidName(name.to_string()))?;
+        Ok(self.dir.join(stem).with_extension(PROFILE_EXTENSION))
+    }
+
+    /// Loads every profile in the folder, sorted by name.
+    ///
+    /// A missing folder yields no profiles; files that cannot be read are
+    /// logged and skipped so one bad file does not hide the others.
+    pub fn list(&self) -> Vec<Profile> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(error) => {
+                if error.kind() != io::ErrorKind::NotFound {
+                    warn!(dir = %self.dir.display(), %error, "Cannot read profiles folder");
+                }
+                return Vec::new();
+            }
+        };
+
+        let mut profiles: Vec<Profile> = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(PROFILE_EXTENSION))
+            })
+            .filter_map(|path| {
+                Profile::load(&path)
+                    .inspect_err(|error| warn!(%error, "Skipping profile"))
+                    .ok()
+            })
+            .collect();
+        profiles.sort_by_key(|profile| profile.name.to_lowercase());
+        profiles
+    }
+
+    /// Finds the profile called `name`, ignoring case.
+    pub fn find(
+        &self,
+        name: &str,
+    ) -> Result<Profile, ProfileError> {
+        let path = self.path_for(name)?;
+        if path.is_file() {
+            let profile = Profile::load(&path)?;
+            if profile.name.eq_ignore_ascii_case(name.trim()) {
+                return Ok(profile);
+            }
+        }
+
+        // Hand-written files may not follow the naming scheme.
+        self.list()
+            .into_iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| ProfileError::NotFound(name.to_string()))
+    }
+
+    /// Writes `profile` to its file, replacing any profile of the same name.
+    ///
+    /// Fails with [`ProfileError::NameTaken`] rather than replace a profile
+    /// with another name saved in the same file. Returns the path written
+    /// to.
+    pub fn save(
+        &self,
+        profile: &Profile,
+    ) -> Result<PathBuf, ProfileError> {
+        let path = self.path_for(&profile.name)?;
+        if path.is_file() {
+            let existing = Profile::load(&path)?;
+            if !existing.name.eq_ignore_ascii_case(&profile.name) {
+                return Err(ProfileError::NameTaken {
+                    name: profile.name.clone(),
+                    existing: existing.name,
+                });
+            }
+        }
+        let io_error = |source| ProfileError::Io {
+            path: path.clone(),
+            source,
+        };
+
+        let text = toml::to_string_pretty(profile)?;
+        fs::create_dir_all(&self.dir).map_err(io_error)?;
+        let temp = path.with_extension("toml.tmp");
+        fs::write(&temp, text).map_err(io_error)?;
+        fs::rename(&temp, &path).map_err(io_error)?;
+
+        info!(name = %profile.name, path = %path.display(), "Saved profile");
+        Ok(path)
+    }
+}
+
+/// Loads the profile named by a `--profile` argument.
+///
+/// A value that points at an existing `.toml` file is read directly, so a
+/// profile checked into a repository can be used without installing it;
+/// anything else is looked up by name in `library`, usually
+/// [`ProfileLibrary::open_default`].
+pub fn resolve_profile(
+    spec: &str,
+    library: Option<&ProfileLibrary>,
+) -> Result<Profile, ProfileError> {
+    let path = Path::new(spec);
+    let is_file = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(PROFILE_EXTENSION))
+        && path.is_file();
+    if is_file {
+        return Profile::load(path);
+    }
+
+    library
+        .ok_or_else(|| ProfileError::NotFound(spec.to_string()))?
+        .find(spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{ColumnMapping, WriteMode},
+        schema::SqlType,
+    };
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn weekly() -> Profile {
+        Profile::new(
+            "Weekly Timesheet → payroll.db",
+            FileFormModel {
+                source_file: PathBuf::from("/data/in/hours.xlsx"),
+                database_file: PathBuf::from("/data/out/payroll.db"),
+                selected_sheet: Some("Hours".to_string()),
+                has_headers: true,
+                write_mode: WriteMode::TruncateAndReplace,
+                column_mappings: vec![
+                    ColumnMapping::new("Emp ID").to("employee_id"),
+                    ColumnMapping::new("Hours").with_type(SqlType::Real),
+                    ColumnMapping::new("Notes").excluded(),
+                ],
+                ..FileFormModel::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_file_stem_slugifies_name() {
+        assert_eq!(
+            file_stem("Weekly Timesheet → payroll.db").as_deref(),
+            Some("weekly-timesheet-payroll-db")
+        );
+        assert_eq!(file_stem(" → "), None);
+    }
+
+    #[test]
+    fn test_save_and_find_round_trip() {
+        let dir = TempDir::new().expect("temp dir");
+        let library = ProfileLibrary::new(dir.path().join(PROFILES_DIR));
+
+        let path = library.save(&weekly()).expect("save");
+
+        assert_eq!(path, library.dir().join("weekly-timesheet-payroll-db.toml"));
+        assert_eq!(
+            library.find("weekly timesheet → PAYROLL.db").expect("find"),
+            weekly()
+        );
+    }
+
+    #[test]
+    fn test_find_matches_name_inside_renamed_file() {
+        let dir = TempDir::new().expect("temp dir");
+        let library = ProfileLibrary::new(dir.path().to_path_buf());
+        let text = toml::to_string_pretty(&weekly()).expect("serialize");
+        fs::write(dir.path().join("payroll.toml"), text).expect("write");
+
+        assert_eq!(
+            library.find("Weekly Timesheet → payroll.db").expect("find"),
+            weekly()
+        );
+        assert!(matches!(
+            library.find("Monthly"),
+            Err(ProfileError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_list_sorts_and_skips_unreadable_files() {
+        let dir = TempDir::new().expect("temp dir");
+        let library = ProfileLibrary::new(dir.path().to_path_buf());
+        library.save(&weekly()).expect("save");
+        library
+            .save(&Profile::new("Ad hoc", FileFormModel::default()))
+            .expect("save");
+        fs::write(dir.path().join("broken.toml"), "name = ").expect("write");
+        fs::write(dir.path().join("notes.txt"), "not a profile").expect("write");
+
+        let names: Vec<String> = library
+            .list()
+            .into_iter()
+            .map(|profile| profile.name)
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "Ad hoc".to_string(),
+                "Weekly Timesheet → payroll.db".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_of_missing_folder_is_empty() {
+        let dir = TempDir::new().expect("temp dir");
+        let library = ProfileLibrary::new(dir.path().join("nope"));
+        assert!(library.list().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_profile_reads_file_path() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("shared.toml");
+        fs::write(&path, toml::to_string_pretty(&weekly()).expect("serialize")).expect("write");
+
+        let profile = resolve_profile(path.to_str().expect("utf-8 path"), None).expect("resolve");
+        assert_eq!(profile, weekly());
+    }
+
+    #[test]
+    fn test_save_refuses_name_with_same_file_stem() {
+        let dir = TempDir::new().expect("temp dir");
+        let library = ProfileLibrary::new(dir.path().to_path_buf());
+        library
+            .save(&Profile::new("Weekly Timesheet", FileFormModel::default()))
+            .expect("save");
+        library
+            .save(&Profile::new("weekly timesheet", FileFormModel::default()))
+            .expect("same name, other case");
+
+        let result = library.save(&Profile::new("weekly-timesheet", FileFormModel::default()));
+
+        assert!(
+            matches!(&result, Err(ProfileError::NameTaken { existing, .. }) if existing == "weekly timesheet"),
+            "expected NameTaken, got: {result:?}"
+        );
+        assert!(matches!(
+            library.find("weekly-timesheet"),
+            Err(ProfileError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_save_rejects_blank_name() {
+        let dir = TempDir::new().expect("temp dir");
+        let library = ProfileLibrary::new(dir.path().to_path_buf());
+        assert!(matches!(
+            library.save(&Profile::new("  ", FileFormModel::default())),
+            Err(ProfileError::InvalidName(_))
+        ));
+    }
+}
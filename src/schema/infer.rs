@@ -0,0 +1,460 @@
+// schema/infer.rs
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+
+use super::{ColumnSchema, SqlType, TableSchema};
+use crate::{
+    models::FileFormModel,
+    sources::{CellValue, Row},
+};
+
+/// Number of data rows sampled when no explicit sample size is configured.
+pub const DEFAULT_SAMPLE_ROWS: usize = 100;
+
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+];
+
+const DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d", "%Y/%m/%d",
+];
+
+/// Controls how column names and types are derived from raw rows.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InferenceOptions {
+    /// Whether row 1 supplies the column names.
+    pub has_headers: bool,
+    /// Number of data rows inspected per column; `0` inspects every row.
+    pub sample_rows: usize,
+}
+
+impl Default for InferenceOptions {
+    fn default() -> Self {
+        Self {
+            has_headers: false,
+            sample_rows: DEFAULT_SAMPLE_ROWS,
+        }
+    }
+}
+
+impl From<&FileFormModel> for InferenceOptions {
+    fn from(model: &FileFormModel) -> Self {
+        Self {
+            has_headers: model.has_headers,
+            sample_rows: model.sample_rows.unwrap_or(DEFAULT_SAMPLE_ROWS),
+        }
+    }
+}
+
+impl InferenceOptions {
+    /// Returns the rows that hold data, skipping the header row if present.
+    pub fn data_rows<'a>(
+        &self,
+        rows: &'a [Row],
+    ) -> &'a [Row] {
+        if self.has_headers && !rows.is_empty() {
+            &rows[1..]
+        } else {
+            rows
+        }
+    }
+}
+
+/// Infers column names and SQL types from raw source rows.
+///
+/// With [`InferenceOptions::has_headers`] set, row 1 supplies the names and
+/// the width of the table; blank names, and names repeated in any case, are
+/// replaced by `column_<n>`, with a further `_<n>` suffix should that name
+/// be taken too. Without headers the widest row sets the width. Each column's type
+/// is the narrowest [`SqlType`] that fits every non-blank sampled cell;
+/// integers widen to reals, dates widen to datetimes, and any other mix
+/// falls back to text.
+///
+/// # Examples
+///
+/// ```
+/// use gpui_demo::schema::{InferenceOptions, SqlType, infer_schema};
+/// use gpui_demo::sources::CellValue;
+///
+/// let rows = vec![
+///     vec![CellValue::Text("Hours".to_string())],
+///     vec![CellValue::Text("7".to_string())],
+///     vec![CellValue::Text("7.5".to_string())],
+/// ];
+/// let options = InferenceOptions { has_headers: true, ..InferenceOptions::default() };
+/// let schema = infer_schema(&rows, &options);
+///
+/// assert_eq!(schema.columns[0].name, "Hours");
+/// assert_eq!(schema.columns[0].sql_type, SqlType::Real);
+/// ```
+pub fn infer_schema(
+    rows: &[Row],
+    options: &InferenceOptions,
+) -> TableSchema {
+    let header = rows.first().filter(|_| options.has_headers);
+    let width = match header {
+        Some(header) => header.len(),
+        None => rows.iter().map(Vec::len).max().unwrap_or(0),
+    };
+    let names = column_names(header, width);
+
+    let data = options.data_rows(rows);
+    let sample = match options.sample_rows {
+        0 => data,
+        n => &data[..data.len().min(n)],
+    };
+
+    let columns = names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let cells = sample
+                .iter()
+                .map(|row| row.get(i).unwrap_or(&CellValue::Empty));
+            infer_column(name, cells)
+        })
+        .collect();
+
+    TableSchema::new(columns)
+}
+
+fn column_names(
+    header: Option<&Row>,
+    width: usize,
+) -> Vec<String> {
+    // SQLite matches column names without regard to ASCII case.
+    let is_taken =
+        |names: &[String], name: &str| names.iter().any(|other| other.eq_ignore_ascii_case(name));
+
+    let mut names: Vec<String> = Vec::with_capacity(width);
+    for i in 0..width {
+        let mut name = header
+            .and_then(|row| row.get(i))
+            .map(|cell| cell.to_string().trim().to_string())
+            .unwrap_or_default();
+        if name.is_empty() || is_taken(&names, &name) {
+            name = format!("column_{}", i + 1);
+        }
+        let base = name.clone();
+        let mut suffix = 2;
+        while is_taken(&names, &name) {
+            name = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        names.push(name);
+    }
+    names
+}
+
+fn infer_column<'a>(
+    name: String,
+    cells: impl Iterator<Item = &'a CellValue>,
+) -> ColumnSchema {
+    let mut sql_type: Option<SqlType> = None;
+    let mut nullable = false;
+
+    for cell in cells {
+        match parse_cell(cell).map(|typed| type_of(&typed)) {
+            None => nullable = true,
+            Some(found) => {
+                sql_type = Some(sql_type.map_or(found, |current| unify(current, found)));
+            }
+        }
+    }
+
+    ColumnSchema::new(name, sql_type.unwrap_or_default(), nullable)
+}
+
+/// Returns the narrowest type able to hold values of both `a` and `b`.
+fn unify(
+    a: SqlType,
+    b: SqlType,
+) -> SqlType {
+    match (a, b) {
+        (a, b) if a == b => a,
+        (SqlType::Integer, SqlType::Real) | (SqlType::Real, SqlType::Integer) => SqlType::Real,
+        (SqlType::Date, SqlType::DateTime) | (SqlType::DateTime, SqlType::Date) => {
+            SqlType::DateTime
+        }
+        _ => SqlType::Text,
+    }
+}
+
+fn type_of(cell: &CellValue) -> SqlType {
+    match cell {
+        CellValue::Int(_) => SqlType::Integer,
+        CellValue::Float(_) => SqlType::Real,
+        CellValue::Bool(_) => SqlType::Boolean,
+        CellValue::Date(_) => SqlType::Date,
+        CellValue::DateTime(_) => SqlType::DateTime,
+        CellValue::Duration(_) => SqlType::Duration,
+        CellValue::Empty | CellValue::Text(_) => SqlType::Text,
+    }
+}
+
+/// Interprets a cell as its most specific typed value.
+///
+/// Typed cells from workbooks are returned unchanged; text cells are parsed.
+/// Returns `None` for blank cells.
+pub(crate) fn parse_cell(cell: &CellValue) -> Option<CellValue> {
+    match cell {
+        _ if cell.is_blank() => None,
+        CellValue::Text(text) => Some(parse_text(text.trim())),
+        other => Some(other.clone()),
+    }
+}
+
+fn parse_text(text: &str) -> CellValue {
+    if text.eq_ignore_ascii_case("true") {
+        return CellValue::Bool(true);
+    }
+    if text.eq_ignore_ascii_case("false") {
+        return CellValue::Bool(false);
+    }
+    if is_numeric(text) {
+        if let Ok(value) = text.parse::<i64>() {
+            return CellValue::Int(value);
+        }
+        if let Ok(value) = text.parse::<f64>() {
+            return CellValue::Float(value);
+        }
+    }
+    if let Some(value) = DATETIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    {
+        return CellValue::DateTime(value);
+    }
+    if let Some(value) = DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+    {
+        return CellValue::Date(value);
+    }
+    if let Some(value) = parse_duration(text) {
+        return CellValue::Duration(value);
+    }
+    CellValue::Text(text.to_string())
+}
+
+/// Accepts plain decimal numbers only.
+///
+/// Values with leading zeros such as employee numbers (`"00123"`) are kept
+/// as text so the zeros survive the import.
+fn is_numeric(text: &str) -> bool {
+    let digits = text
+        .strip_prefix([
+            '-', '+',
+        ])
+        .unwrap_or(text);
+    let has_leading_zero = digits.len() > 1 && digits.starts_with('0') && !digits.starts_with("0.");
+    !digits.is_empty()
+        && !has_leading_zero
+        && digits.chars().any(|c| c.is_ascii_digit())
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+'))
+}
+
+/// Parses `H:MM` or `H:MM:SS` clock durations such as `"07:30"`.
+fn parse_duration(text: &str) -> Option<TimeDelta> {
+    let parts: Vec<&str> = text.split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    if parts
+        .iter()
+        .any(|part| part.is_empty() || !part.chars().all(|c| c.is_ascii_digit()))
+    {
+        return None;
+    }
+
+    let hours: i64 = parts[0].parse().ok()?;
+    let minutes: i64 = parts[1].parse().ok()?;
+    let seconds: i64 = match parts.get(2) {
+        Some(part) => part.parse().ok()?,
+        None => 0,
+    };
+    if parts[1].len() != 2 || minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+
+    Some(TimeDelta::seconds(hours * 3600 + minutes * 60 + seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn text(value: &str) -> CellValue {
+        CellValue::Text(value.to_string())
+    }
+
+    fn column_types(rows: &[Row]) -> Vec<SqlType> {
+        infer_schema(rows, &InferenceOptions::default())
+            .columns
+            .iter()
+            .map(|column| column.sql_type)
+            .collect()
+    }
+
+    #[test]
+    fn test_infer_schema_each_type_from_text() {
+        let rows = vec![
+            vec![
+                text("12"),
+                text("1.5"),
+                text("TRUE"),
+                text("2024-01-08"),
+                text("2024-01-08 09:30:00"),
+                text("07:30"),
+                text("Ada"),
+            ],
+        ];
+        assert_eq!(
+            column_types(&rows),
+            vec![
+                SqlType::Integer,
+                SqlType::Real,
+                SqlType::Boolean,
+                SqlType::Date,
+                SqlType::DateTime,
+                SqlType::Duration,
+                SqlType::Text,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer_schema_widens_mixed_columns() {
+        let rows = vec![
+            vec![
+                text("1"),
+                text("2024-01-08"),
+                text("1"),
+            ],
+            vec![
+                text("2.5"),
+                text("2024-01-08T10:00"),
+                text("yes"),
+            ],
+        ];
+        assert_eq!(
+            column_types(&rows),
+            vec![
+                SqlType::Real,
+                SqlType::DateTime,
+                SqlType::Text,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer_schema_keeps_leading_zeros_as_text() {
+        let rows = vec![vec![text("00123")]];
+        assert_eq!(column_types(&rows), vec![SqlType::Text]);
+    }
+
+    #[test]
+    fn test_infer_schema_respects_sample_size() {
+        let rows = vec![
+            vec![text("1")],
+            vec![text("not a number")],
+        ];
+        let options = InferenceOptions {
+            has_headers: false,
+            sample_rows: 1,
+        };
+        let schema = infer_schema(&rows, &options);
+        assert_eq!(schema.columns[0].sql_type, SqlType::Integer);
+    }
+
+    #[test]
+    fn test_infer_schema_headers_supply_names() {
+        let rows = vec![
+            vec![
+                text("Name"),
+                CellValue::Empty,
+                text("Name"),
+            ],
+            vec![
+                text("Ada"),
+                CellValue::Empty,
+                text("x"),
+            ],
+        ];
+        let options = InferenceOptions {
+            has_headers: true,
+            ..InferenceOptions::default()
+        };
+        let schema = infer_schema(&rows, &options);
+        assert_eq!(
+            schema.column_names(),
+            vec![
+                "Name", "column_2", "column_3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer_schema_renames_names_clashing_in_any_case() {
+        let rows = vec![
+            vec![
+                text("column_3"),
+                text("ID"),
+                text("id"),
+                CellValue::Empty,
+            ],
+        ];
+        let options = InferenceOptions {
+            has_headers: true,
+            ..InferenceOptions::default()
+        };
+        let schema = infer_schema(&rows, &options);
+        assert_eq!(
+            schema.column_names(),
+            vec![
+                "column_3",
+                "ID",
+                "column_3_2",
+                "column_4",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer_schema_without_headers_uses_positional_names() {
+        let rows = vec![
+            vec![
+                text("Name"),
+                text("Hours"),
+            ],
+        ];
+        let schema = infer_schema(&rows, &InferenceOptions::default());
+        assert_eq!(
+            schema.column_names(),
+            vec![
+                "column_1", "column_2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_infer_schema_blank_column_is_nullable_text() {
+        let rows = vec![vec![CellValue::Empty]];
+        let schema = infer_schema(&rows, &InferenceOptions::default());
+        assert_eq!(
+            schema.columns[0],
+            ColumnSchema::new("column_1", SqlType::Text, true)
+        );
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_bad_minutes() {
+        assert_eq!(parse_duration("7:75"), None);
+    }
+}
@@ -0,0 +1,211 @@
+// schema/mapping.rs
+
+use crate::{
+    models::ColumnMapping,
+    sources::{CellValue, Row},
+};
+
+use super::{ColumnSchema, TableSchema};
+
+/// An inferred schema with column mappings applied.
+///
+/// Keeps track of which source column feeds each output column so rows can
+/// be projected and coerced in one step.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MappedSchema {
+    /// The columns written to the target table.
+    pub schema: TableSchema,
+    /// Source column index for each column of `schema`.
+    sources: Vec<usize>,
+    /// Number of columns in the source schema.
+    source_width: usize,
+}
+
+impl MappedSchema {
+    /// Applies `mappings` to the inferred `source` schema.
+    ///
+    /// Mappings are matched to source columns by name, ignoring case.
+    /// Mappings for columns the source does not have are ignored, so one
+    /// set of mappings can serve sources that spell a header differently.
+    /// Fails with a message per clash if two output columns end up with the
+    /// same name.
+    pub fn new(
+        source: &TableSchema,
+        mappings: &[ColumnMapping],
+    ) -> Result<Self, Vec<String>> {
+        let mut errors = Vec::new();
+        let mut columns: Vec<ColumnSchema> = Vec::new();
+        let mut sources = Vec::new();
+        for (index, column) in source.columns.iter().enumerate() {
+            let mapping = mappings
+                .iter()
+                .find(|mapping| mapping.source.trim().eq_ignore_ascii_case(&column.name));
+            if mapping.is_some_and(|mapping| mapping.exclude) {
+                continue;
+            }
+
+            let name = mapping.map_or(column.name.as_str(), ColumnMapping::target_name);
+            if columns
+                .iter()
+                .any(|existing| existing.name.eq_ignore_ascii_case(name))
+            {
+                errors.push(format!(
+                    "More than one column would be imported as '{name}'."
+                ));
+                continue;
+            }
+
+            let sql_type = mapping
+                .and_then(|mapping| mapping.sql_type)
+                .unwrap_or(column.sql_type);
+            columns.push(ColumnSchema::new(name, sql_type, column.nullable));
+            sources.push(index);
+        }
+
+        if errors.is_empty() {
+            Ok(Self {
+                schema: TableSchema::new(columns),
+                sources,
+                source_width: source.columns.len(),
+            })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns `source`, the schema the mapping was applied to, with each
+    /// mapped column given the type it is imported as. Names and order stay
+    /// those of the source; excluded columns keep their type.
+    pub fn retype(
+        &self,
+        source: &TableSchema,
+    ) -> TableSchema {
+        let mut retyped = source.clone();
+        for (column, &index) in self.schema.columns.iter().zip(&self.sources) {
+            if let Some(source_column) = retyped.columns.get_mut(index) {
+                source_column.sql_type = column.sql_type;
+            }
+        }
+        retyped
+    }
+
+    /// Picks the mapped columns out of a source `row` and converts each to
+    /// its column's type.
+    ///
+    /// Rows wider than the source schema are rejected, as by
+    /// [`TableSchema::coerce_row`], even when the extra cells would be
+    /// dropped. Excluded cells are not checked.
+    pub fn coerce_row(
+        &self,
+        row: &Row,
+    ) -> Option<Row> {
+        if row.len() > self.source_width {
+            return None;
+        }
+        self.schema
+            .columns
+            .iter()
+            .zip(&self.sources)
+            .map(|(column, &index)| match row.get(index) {
+                Some(cell) => column.sql_type.coerce(cell),
+                None => Some(CellValue::Empty),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::SqlType;
+    use pretty_assertions::assert_eq;
+
+    fn source() -> TableSchema {
+        TableSchema::new(vec![
+            ColumnSchema::new("Emp ID", SqlType::Integer, false),
+            ColumnSchema::new("Notes", SqlType::Text, true),
+            ColumnSchema::new("Hours", SqlType::Integer, false),
+        ])
+    }
+
+    fn text(value: &str) -> CellValue {
+        CellValue::Text(value.to_string())
+    }
+
+    #[test]
+    fn test_mapping_renames_retypes_and_excludes() {
+        let mappings = vec![
+            ColumnMapping::new("emp id").to("employee_id"),
+            ColumnMapping::new("Notes").excluded(),
+            ColumnMapping::new("Hours").with_type(SqlType::Real),
+        ];
+
+        let mapped = MappedSchema::new(&source(), &mappings).expect("valid mapping");
+
+        assert_eq!(
+            mapped.schema,
+            TableSchema::new(vec![
+                ColumnSchema::new("employee_id", SqlType::Integer, false),
+                ColumnSchema::new("Hours", SqlType::Real, false),
+            ])
+        );
+        assert_eq!(
+            mapped.coerce_row(&vec![
+                text("7"),
+                text("not a number"),
+                text("8"),
+            ]),
+            Some(vec![
+                CellValue::Int(7),
+                CellValue::Float(8.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_mapping_without_entries_keeps_schema() {
+        let mapped = MappedSchema::new(&source(), &[]).expect("valid mapping");
+
+        assert_eq!(mapped.schema, source());
+        assert_eq!(
+            mapped.coerce_row(&vec![
+                text("1"),
+                text("a"),
+                text("2"),
+                text("extra"),
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_mapping_ignores_absent_sources() {
+        let mappings = vec![
+            ColumnMapping::new("Employee #").to("employee_id"),
+            ColumnMapping::new("Emp ID").to("employee_id"),
+        ];
+
+        let mapped = MappedSchema::new(&source(), &mappings).expect("valid mapping");
+
+        assert_eq!(
+            mapped.schema.column_names(),
+            vec![
+                "employee_id",
+                "Notes",
+                "Hours",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mapping_reports_clashing_columns() {
+        let mappings = vec![ColumnMapping::new("Notes").to("hours")];
+
+        let errors = MappedSchema::new(&source(), &mappings).expect_err("invalid mapping");
+
+        assert_eq!(
+            errors,
+            vec!["More than one column would be imported as 'Hours'.".to_string()]
+        );
+    }
+}
@@ -0,0 +1,266 @@
+// schema
+
+pub mod infer;
+pub mod mapping;
+
+use std::fmt;
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+
+use crate::sources::{CellValue, Row};
+
+pub use infer::{DEFAULT_SAMPLE_ROWS, InferenceOptions, infer_schema};
+pub use mapping::MappedSchema;
+
+/// SQL column type inferred for an imported column.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum SqlType {
+    Integer,
+    Real,
+    Boolean,
+    Date,
+    DateTime,
+    Duration,
+    #[default]
+    Text,
+}
+
+impl SqlType {
+    /// All types in the order they are offered in the form.
+    pub const ALL: [Self; 7] = [
+        Self::Integer,
+        Self::Real,
+        Self::Boolean,
+        Self::Date,
+        Self::DateTime,
+        Self::Duration,
+        Self::Text,
+    ];
+
+    /// Returns the type shown as `label` in the mapping panel, such as
+    /// `DateTime`, ignoring case.
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|sql_type| sql_type.to_string().eq_ignore_ascii_case(label))
+    }
+
+    /// Returns the type name used in `CREATE TABLE` statements.
+    pub fn sql_name(self) -> &'static str {
+        match self {
+            Self::Integer => "INTEGER",
+            Self::Real => "REAL",
+            Self::Boolean => "BOOLEAN",
+            Self::Date => "DATE",
+            Self::DateTime => "DATETIME",
+            Self::Duration => "TEXT",
+            Self::Text => "TEXT",
+        }
+    }
+
+    /// Converts `cell` into a value of this type.
+    ///
+    /// Blank cells convert to [`CellValue::Empty`]. Returns `None` when the
+    /// cell cannot be represented as this type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpui_demo::schema::SqlType;
+    /// use gpui_demo::sources::CellValue;
+    ///
+    /// let cell = CellValue::Text("42".to_string());
+    /// assert_eq!(SqlType::Integer.coerce(&cell), Some(CellValue::Int(42)));
+    /// assert_eq!(SqlType::Boolean.coerce(&cell), None);
+    /// ```
+    pub fn coerce(
+        self,
+        cell: &CellValue,
+    ) -> Option<CellValue> {
+        if cell.is_blank() {
+            return Some(CellValue::Empty);
+        }
+        if self == Self::Text {
+            return Some(CellValue::Text(cell.to_string()));
+        }
+
+        let typed = infer::parse_cell(cell)?;
+        match (self, typed) {
+            (Self::Integer, CellValue::Int(v)) => Some(CellValue::Int(v)),
+            (Self::Integer, CellValue::Float(v)) if v.fract() == 0.0 && v.abs() < 9.0e15 => {
+                Some(CellValue::Int(v as i64))
+            }
+            (Self::Real, CellValue::Int(v)) => Some(CellValue::Float(v as f64)),
+            (Self::Real, CellValue::Float(v)) => Some(CellValue::Float(v)),
+            (Self::Boolean, CellValue::Bool(v)) => Some(CellValue::Bool(v)),
+            (Self::Date, CellValue::Date(v)) => Some(CellValue::Date(v)),
+            (Self::DateTime, CellValue::Date(v)) => {
+                Some(CellValue::DateTime(v.and_time(NaiveTime::MIN)))
+            }
+            (Self::DateTime, CellValue::DateTime(v)) => Some(CellValue::DateTime(v)),
+            (Self::Duration, CellValue::Duration(v)) => Some(CellValue::Duration(v)),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<String> for SqlType {
+    type Error = String;
+
+    fn try_from(label: String) -> Result<Self, String> {
+        Self::from_label(&label).ok_or_else(|| format!("unknown column type '{label}'"))
+    }
+}
+
+impl From<SqlType> for String {
+    fn from(value: SqlType) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for SqlType {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let label = match self {
+            Self::Integer => "Integer",
+            Self::Real => "Real",
+            Self::Boolean => "Boolean",
+            Self::Date => "Date",
+            Self::DateTime => "DateTime",
+            Self::Duration => "Duration",
+            Self::Text => "Text",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// A named, typed column of an imported table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub sql_type: SqlType,
+    /// `true` if any sampled cell in the column was blank.
+    pub nullable: bool,
+}
+
+impl ColumnSchema {
+    pub fn new(
+        name: impl Into<String>,
+        sql_type: SqlType,
+        nullable: bool,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            sql_type,
+            nullable,
+        }
+    }
+}
+
+/// The inferred shape of an imported sheet or CSV file.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TableSchema {
+    pub columns: Vec<ColumnSchema>,
+}
+
+impl TableSchema {
+    pub fn new(columns: Vec<ColumnSchema>) -> Self {
+        Self { columns }
+    }
+
+    /// Returns the column names in order.
+    pub fn column_names(&self) -> Vec<&str> {
+        self.columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect()
+    }
+
+    /// Converts every cell of `row` to its column's type.
+    ///
+    /// Short rows are padded with [`CellValue::Empty`]. Returns `None` if the
+    /// row is wider than the schema or any cell fails to convert.
+    pub fn coerce_row(
+        &self,
+        row: &Row,
+    ) -> Option<Row> {
+        if row.len() > self.columns.len() {
+            return None;
+        }
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| match row.get(i) {
+                Some(cell) => column.sql_type.coerce(cell),
+                None => Some(CellValue::Empty),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+
+    fn schema() -> TableSchema {
+        TableSchema::new(vec![
+            ColumnSchema::new("name", SqlType::Text, false),
+            ColumnSchema::new("hours", SqlType::Real, true),
+        ])
+    }
+
+    #[test]
+    fn test_coerce_date_to_datetime() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 8).expect("valid date");
+        let coerced = SqlType::DateTime.coerce(&CellValue::Date(date));
+        assert_eq!(
+            coerced,
+            Some(CellValue::DateTime(
+                date.and_hms_opt(0, 0, 0).expect("valid time")
+            ))
+        );
+    }
+
+    #[test]
+    fn test_coerce_blank_is_empty() {
+        let coerced = SqlType::Integer.coerce(&CellValue::Text(" ".to_string()));
+        assert_eq!(coerced, Some(CellValue::Empty));
+    }
+
+    #[test]
+    fn test_coerce_row_pads_short_rows() {
+        let row = vec![CellValue::Text("Ada".to_string())];
+        assert_eq!(
+            schema().coerce_row(&row),
+            Some(vec![
+                CellValue::Text("Ada".to_string()),
+                CellValue::Empty,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_coerce_row_rejects_bad_cell() {
+        let row = vec![
+            CellValue::Text("Ada".to_string()),
+            CellValue::Text("lots".to_string()),
+        ];
+        assert_eq!(schema().coerce_row(&row), None);
+    }
+
+    #[test]
+    fn test_coerce_row_rejects_wide_row() {
+        let row = vec![
+            CellValue::Text("Ada".to_string()),
+            CellValue::Float(1.0),
+            CellValue::Int(3),
+        ];
+        assert_eq!(schema().coerce_row(&row), None);
+    }
+}
@@ -0,0 +1,657 @@
+// sources/archive.rs
+
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::SystemTime,
+};
+
+use flate2::read::MultiGzDecoder;
+use tempfile::NamedTempFile;
+use tracing::debug;
+use zip::{ZipArchive, result::ZipError};
+
+use super::{
+    SheetInfo, SheetVisibility, SourceError, SourceRows,
+    excel::{ZIP_MAGIC, is_workbook_header},
+    map_io_error,
+    registry::{ReadOptions, SourceFormat, SourceReader, registry},
+};
+
+/// Extensions handled by the zip reader, lower-case.
+pub const ZIP_EXTENSIONS: &[&str] = &["zip"];
+
+/// Extensions of gzip-compressed sources, such as `hours.csv.gz`, which
+/// are read as the file they hold.
+pub const GZIP_EXTENSIONS: &[&str] = &["gz"];
+
+/// Name of the gzip entry in the file dialog's type filter.
+pub const GZIP_LABEL: &str = "Gzip-compressed";
+
+/// Magic bytes of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [
+    0x1F, 0x8B,
+];
+
+/// Most bytes [`read_source_bytes`] reads, after decompression, so a small
+/// gzip file cannot expand to fill the memory.
+pub const MAX_SOURCE_BYTES: u64 = 1 << 30;
+
+/// Folder of resource forks that macOS adds to the zip archives it makes.
+const MACOS_METADATA: &str = "__MACOSX/";
+
+/// Most zip entries [`read_entry`] keeps extracted at once.
+const EXTRACTED_ENTRIES: usize = 4;
+
+/// Zip entries extracted by [`read_entry`], so the preview and the import
+/// that follows it extract an entry once.
+static EXTRACTED: Mutex<EntryCache> = Mutex::new(EntryCache::new());
+
+/// Reads the files in a zip archive, which are listed as its sheets.
+///
+/// The chosen file is extracted to a temporary file and read by the reader
+/// its contents, or else its extension, call for, so an archive may hold
+/// any mix of formats the other readers handle.
+pub struct ZipReader;
+
+impl SourceReader for ZipReader {
+    fn format(&self) -> SourceFormat {
+        SourceFormat::Zip
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        ZIP_EXTENSIONS
+    }
+
+    fn sniff(
+        &self,
+        header: &[u8],
+    ) -> bool {
+        header.starts_with(&ZIP_MAGIC) && !is_workbook_header(header)
+    }
+
+    fn list_sheets(
+        &self,
+        path: &Path,
+    ) -> Result<Vec<SheetInfo>, SourceError> {
+        list_entries(path)
+    }
+
+    fn read(
+        &self,
+        path: &Path,
+        sheet: Option<&str>,
+        options: &ReadOptions,
+    ) -> Result<SourceRows, SourceError> {
+        read_entry(path, sheet, options)
+    }
+}
+
+/// Opens the file at `path` for reading, decompressing it as it is read
+/// when it is gzip-compressed, so its first bytes can be looked at without
+/// decompressing the rest.
+pub fn open_source(path: &Path) -> Result<Box<dyn Read>, SourceError> {
+    let mut file = BufReader::new(File::open(path).map_err(|e| map_io_error(path, e))?);
+    if starts_with_gzip(path, &mut file)? {
+        Ok(Box::new(MultiGzDecoder::new(file)))
+    } else {
+        Ok(Box::new(file))
+    }
+}
+
+/// Reads all of the file at `path`, decompressed (see [`open_source`]).
+///
+/// Text sources are decoded whole, so this holds the file in memory; a
+/// file larger than [`MAX_SOURCE_BYTES`] once decompressed fails with
+/// [`SourceError::TooLarge`] rather than being read.
+pub fn read_source_bytes(path: &Path) -> Result<Vec<u8>, SourceError> {
+    read_bytes_up_to(path, MAX_SOURCE_BYTES)
+}
+
+/// Fails with [`SourceError::Compressed`] when the file at `path` is
+/// gzip-compressed, for readers that open the file itself rather than
+/// through [`open_source`].
+pub(crate) fn check_not_compressed(path: &Path) -> Result<(), SourceError> {
+    let mut file = BufReader::new(File::open(path).map_err(|e| map_io_error(path, e))?);
+    if starts_with_gzip(path, &mut file)? {
+        Err(SourceError::Compressed(path.to_path_buf()))
+    } else {
+        Ok(())
+    }
+}
+
+fn starts_with_gzip(
+    path: &Path,
+    file: &mut BufReader<File>,
+) -> Result<bool, SourceError> {
+    Ok(file
+        .fill_buf()
+        .map_err(|e| map_io_error(path, e))?
+        .starts_with(&GZIP_MAGIC))
+}
+
+fn read_bytes_up_to(
+    path: &Path,
+    limit: u64,
+) -> Result<Vec<u8>, SourceError> {
+    let mut data = Vec::new();
+    open_source(path)?
+        .take(limit + 1)
+        .read_to_end(&mut data)
+        .map_err(|e| map_io_error(path, e))?;
+    if data.len() as u64 > limit {
+        return Err(SourceError::TooLarge {
+            path: path.to_path_buf(),
+            limit,
+        });
+    }
+    Ok(data)
+}
+
+/// Returns `path` without a `.gz` extension: the name of the file a
+/// gzip-compressed source holds.
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use gpui_demo::sources::archive::uncompressed_path;
+///
+/// assert_eq!(uncompressed_path(Path::new("in/hours.csv.GZ")), Path::new("in/hours.csv"));
+/// assert_eq!(uncompressed_path(Path::new("hours.csv")), Path::new("hours.csv"));
+/// ```
+pub fn uncompressed_path(path: &Path) -> PathBuf {
+    let is_gzip = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| GZIP_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+    if is_gzip {
+        path.with_extension("")
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Lists the files in the zip archive at `path` that a reader handles, by
+/// their path in the archive and in archive order.
+///
+/// Folders, nested archives and the metadata macOS adds are left out.
+pub fn list_entries(path: &Path) -> Result<Vec<SheetInfo>, SourceError> {
+    let archive = open_zip(path)?;
+    Ok(importable_entries(&archive)
+        .into_iter()
+        .map(|name| SheetInfo::new(name, SheetVisibility::Visible))
+        .collect())
+}
+
+/// Reads `entry` of the zip archive at `path`, or its first importable
+/// file when `entry` is `None`, with the rows `options` select.
+///
+/// The entry is extracted to a temporary file named like it, which is kept
+/// while the archive is unchanged so the entry is not extracted again when
+/// read once more; an entry larger than [`MAX_SOURCE_BYTES`] fails with
+/// [`SourceError::TooLarge`], and one that is itself a zip archive with
+/// [`SourceError::Corrupt`]. Errors name the archive and the entry.
+pub fn read_entry(
+    path: &Path,
+    entry: Option<&str>,
+    options: &ReadOptions,
+) -> Result<SourceRows, SourceError> {
+    let (name, extracted) = EXTRACTED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .extract(path, entry, MAX_SOURCE_BYTES)?;
+    let reader = registry()
+        .detect_with(extracted.path(), options)
+        .map_err(|error| in_archive(error, path, &name))?;
+    // Archives are read one level deep, so a zip inside cannot recurse.
+    if reader.format() == SourceFormat::Zip {
+        return Err(SourceError::Corrupt {
+            path: path.to_path_buf(),
+            message: format!("'{name}' is a zip archive, which is not read inside another"),
+        });
+    }
+    reader
+        .read(extracted.path(), None, options)
+        .map_err(|error| in_archive(error, path, &name))
+}
+
+/// Removes the zip entries [`read_entry`] keeps extracted. A static is
+/// never dropped, so this is called once the entries are no longer needed,
+/// when the app quits or a headless import ends.
+pub fn clear_extracted_entries() {
+    EXTRACTED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entries
+        .clear();
+}
+
+/// Zip entries extracted to temporary files, least recently read first.
+struct EntryCache {
+    entries: Vec<ExtractedEntry>,
+}
+
+/// A zip entry extracted to a temporary file, with the size and
+/// modification time its archive had then.
+struct ExtractedEntry {
+    archive: PathBuf,
+    entry: String,
+    stamp: (u64, Option<SystemTime>),
+    file: Arc<NamedTempFile>,
+}
+
+impl EntryCache {
+    const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the name of `entry` of the zip archive at `path`, or of its
+    /// first importable file, and the temporary file it is extracted to,
+    /// extracting it unless it was while the archive was as it is now.
+    ///
+    /// Fails with [`SourceError::TooLarge`] if the entry holds more than
+    /// `limit` bytes, whatever size the archive claims for it.
+    fn extract(
+        &mut self,
+        path: &Path,
+        entry: Option<&str>,
+        limit: u64,
+    ) -> Result<(String, Arc<NamedTempFile>), SourceError> {
+        let corrupt = |message: String| SourceError::Corrupt {
+            path: path.to_path_buf(),
+            message,
+        };
+        let too_large = || SourceError::TooLarge {
+            path: path.to_path_buf(),
+            limit,
+        };
+
+        let mut archive = open_zip(path)?;
+        let name = match entry {
+            Some(name) => name.to_string(),
+            None => importable_entries(&archive)
+                .into_iter()
+                .next()
+                .ok_or_else(|| corrupt("the archive holds no files to import".to_string()))?,
+        };
+        let metadata = fs::metadata(path).map_err(|e| map_io_error(path, e))?;
+        let stamp = (metadata.len(), metadata.modified().ok());
+        if let Some(index) = self
+            .entries
+            .iter()
+            .position(|cached| cached.archive == path && cached.entry == name)
+        {
+            let cached = self.entries.remove(index);
+            if cached.stamp == stamp {
+                let file = Arc::clone(&cached.file);
+                self.entries.push(cached);
+                return Ok((name, file));
+            }
+        }
+
+        let file = archive.by_name(&name).map_err(|error| match error {
+            ZipError::FileNotFound => SourceError::SheetNotFound {
+                path: path.to_path_buf(),
+                sheet: name.clone(),
+            },
+            error => map_zip_error(path, error),
+        })?;
+        debug!(
+            archive = %path.display(),
+            entry = name,
+            size = file.size(),
+            "Extracting zip entry"
+        );
+        if file.size() > limit {
+            return Err(too_large());
+        }
+
+        let file_name = Path::new(&name)
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let mut extracted = tempfile::Builder::new()
+            .prefix("entry-")
+            .suffix(&format!("-{file_name}"))
+            .tempfile()
+            .map_err(|e| map_io_error(path, e))?;
+        let copied = io::copy(&mut file.take(limit + 1), &mut extracted)
+            .map_err(|e| map_io_error(path, e))?;
+        if copied > limit {
+            return Err(too_large());
+        }
+
+        let extracted = Arc::new(extracted);
+        if self.entries.len() == EXTRACTED_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.entries.push(ExtractedEntry {
+            archive: path.to_path_buf(),
+            entry: name.clone(),
+            stamp,
+            file: Arc::clone(&extracted),
+        });
+        Ok((name, extracted))
+    }
+}
+
+fn open_zip(path: &Path) -> Result<ZipArchive<File>, SourceError> {
+    check_not_compressed(path)?;
+    let file = File::open(path).map_err(|e| map_io_error(path, e))?;
+    ZipArchive::new(file).map_err(|e| map_zip_error(path, e))
+}
+
+/// Names of the files in `archive` that a reader other than the zip
+/// reader handles by extension.
+fn importable_entries(archive: &ZipArchive<File>) -> Vec<String> {
+    (0..archive.len())
+        .filter_map(|index| archive.name_for_index(index))
+        .filter(|name| !name.ends_with('/') && !name.starts_with(MACOS_METADATA))
+        .filter(|name| {
+            registry()
+                .by_extension(Path::new(name))
+                .is_some_and(|reader| reader.format() != SourceFormat::Zip)
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+fn map_zip_error(
+    path: &Path,
+    error: ZipError,
+) -> SourceError {
+    match error {
+        ZipError::Io(e) => map_io_error(path, e),
+        ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) | ZipError::InvalidPassword => {
+            SourceError::PasswordProtected(path.to_path_buf())
+        }
+        error => SourceError::Corrupt {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        },
+    }
+}
+
+/// Reports an error in the file extracted from `entry` against the archive
+/// at `path`, rather than the temporary file.
+fn in_archive(
+    error: SourceError,
+    path: &Path,
+    entry: &str,
+) -> SourceError {
+    match error {
+        SourceError::NotFound(_) => SourceError::NotFound(path.to_path_buf()),
+        SourceError::Io { source, .. } => SourceError::Io {
+            path: path.to_path_buf(),
+            source,
+        },
+        SourceError::UnsupportedFormat(_) => SourceError::Corrupt {
+            path: path.to_path_buf(),
+            message: format!("'{entry}' is not a supported source format"),
+        },
+        SourceError::PasswordProtected(_) => SourceError::PasswordProtected(path.to_path_buf()),
+        SourceError::TooLarge { limit, .. } => SourceError::TooLarge {
+            path: path.to_path_buf(),
+            limit,
+        },
+        SourceError::Corrupt { message, .. } => SourceError::Corrupt {
+            path: path.to_path_buf(),
+            message: format!("{entry}: {message}"),
+        },
+        error => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::CellValue;
+    use flate2::{Compression, write::GzEncoder};
+    use pretty_assertions::assert_eq;
+    use std::{fs, io::Write};
+    use tempfile::TempDir;
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    fn write_zip(
+        path: &Path,
+        entries: &[(&str, &str)],
+    ) {
+        let mut zip = ZipWriter::new(File::create(path).expect("create zip"));
+        for (name, contents) in entries {
+            if name.ends_with('/') {
+                zip.add_directory(*name, SimpleFileOptions::default())
+                    .expect("add folder");
+            } else {
+                zip.start_file(*name, SimpleFileOptions::default())
+                    .expect("start entry");
+                zip.write_all(contents.as_bytes()).expect("write entry");
+            }
+        }
+        zip.finish().expect("finish zip");
+    }
+
+    fn text(value: &str) -> CellValue {
+        CellValue::Text(value.to_string())
+    }
+
+    #[test]
+    fn test_list_entries_keeps_importable_files() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("timesheets.zip");
+        write_zip(
+            &path,
+            &[
+                ("week_1.csv", "Employee,Hours\nAda,7.5\n"),
+                ("2024/", ""),
+                ("2024/week_2.CSV", "Employee,Hours\nGrace,8\n"),
+                ("__MACOSX/._week_1.csv", "resource fork"),
+                ("readme.pdf", "%PDF"),
+                ("shifts.json", "[{\"id\": 1}]"),
+            ],
+        );
+
+        let names: Vec<String> = list_entries(&path)
+            .expect("zip should list")
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                "week_1.csv".to_string(),
+                "2024/week_2.CSV".to_string(),
+                "shifts.json".to_string(),
+            ]
+        );
+        assert_eq!(
+            registry().detect(&path).map(|reader| reader.format()).ok(),
+            Some(SourceFormat::Zip)
+        );
+    }
+
+    #[test]
+    fn test_read_entry_goes_by_entry_contents() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("timesheets.zip");
+        write_zip(
+            &path,
+            &[
+                ("week_1.csv", "Employee;Hours\nAda;7,5\n"),
+                ("shifts.json", "[{\"id\": 1}]"),
+            ],
+        );
+
+        let first = read_entry(&path, None, &ReadOptions::default()).expect("first entry");
+        let json =
+            read_entry(&path, Some("shifts.json"), &ReadOptions::default()).expect("json entry");
+
+        assert_eq!(
+            first.rows[1],
+            vec![
+                text("Ada"),
+                text("7,5"),
+            ]
+        );
+        assert!(json.header_in_source);
+        assert!(matches!(
+            read_entry(&path, Some("missing.csv"), &ReadOptions::default()),
+            Err(SourceError::SheetNotFound { sheet, .. }) if sheet == "missing.csv"
+        ));
+    }
+
+    #[test]
+    fn test_read_entry_rejects_nested_archives() {
+        let dir = TempDir::new().expect("temp dir");
+        let inner = dir.path().join("inner.zip");
+        write_zip(&inner, &[("week_1.csv", "Employee,Hours\nAda,7.5\n")]);
+        let path = dir.path().join("timesheets.zip");
+        let mut zip = ZipWriter::new(File::create(&path).expect("create zip"));
+        for name in [
+            "inner.zip",
+            "inner.csv",
+        ] {
+            zip.start_file(name, SimpleFileOptions::default())
+                .expect("start entry");
+            zip.write_all(&fs::read(&inner).expect("read inner zip"))
+                .expect("write entry");
+        }
+        zip.finish().expect("finish zip");
+
+        for name in [
+            "inner.zip",
+            "inner.csv",
+        ] {
+            let result = read_entry(&path, Some(name), &ReadOptions::default());
+            assert!(
+                matches!(
+                    &result,
+                    Err(SourceError::Corrupt { message, .. }) if message.contains("is a zip archive")
+                ),
+                "expected Corrupt for {name}, got: {result:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_extracted_entries_are_capped_and_kept() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("timesheets.zip");
+        let big = "0".repeat(4096);
+        write_zip(
+            &path,
+            &[
+                ("week_1.csv", "Employee,Hours\nAda,7.5\n"),
+                ("bomb.csv", &big),
+            ],
+        );
+        let mut cache = EntryCache::new();
+
+        let (name, first) = cache.extract(&path, None, 1024).expect("first entry");
+        let (_, again) = cache
+            .extract(&path, Some("week_1.csv"), 1024)
+            .expect("cached entry");
+
+        assert_eq!(name, "week_1.csv");
+        assert!(Arc::ptr_eq(&first, &again));
+        assert!(matches!(
+            cache.extract(&path, Some("bomb.csv"), 1024),
+            Err(SourceError::TooLarge { limit: 1024, .. })
+        ));
+
+        write_zip(&path, &[("week_1.csv", "Employee,Hours\nGrace,8\n")]);
+        let (_, changed) = cache
+            .extract(&path, Some("week_1.csv"), 1024)
+            .expect("changed entry");
+        assert!(!Arc::ptr_eq(&first, &changed));
+        assert_eq!(
+            fs::read_to_string(changed.path()).expect("read entry"),
+            "Employee,Hours\nGrace,8\n"
+        );
+    }
+
+    #[test]
+    fn test_gzip_is_read_transparently() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("hours.csv.gz");
+        let mut encoder = GzEncoder::new(
+            File::create(&path).expect("create gz"),
+            Compression::default(),
+        );
+        encoder
+            .write_all(b"Employee,Hours\nAda,7.5\n")
+            .expect("write gz");
+        encoder.finish().expect("finish gz");
+        let not_gzip = dir.path().join("notes.gz");
+        fs::write(&not_gzip, b"\x1F\x8B but not deflate").expect("write text");
+
+        assert_eq!(
+            read_source_bytes(&path).expect("gzip should read"),
+            b"Employee,Hours\nAda,7.5\n"
+        );
+        assert_eq!(
+            registry().detect(&path).map(|reader| reader.format()).ok(),
+            Some(SourceFormat::Csv)
+        );
+        let rows = registry()
+            .detect(&path)
+            .and_then(|reader| reader.read(&path, None, &ReadOptions::default()))
+            .expect("csv should read");
+        assert_eq!(rows.rows.len(), 2);
+        assert!(matches!(
+            read_source_bytes(&not_gzip),
+            Err(SourceError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_gzip_is_only_read_by_text_readers() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("timesheet.xlsx.gz");
+        let mut encoder = GzEncoder::new(
+            File::create(&path).expect("create gz"),
+            Compression::default(),
+        );
+        let workbook = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/timesheet.xlsx");
+        encoder
+            .write_all(&fs::read(workbook).expect("read workbook"))
+            .expect("write gz");
+        encoder.finish().expect("finish gz");
+
+        let result = registry()
+            .detect(&path)
+            .and_then(|reader| reader.list_sheets(&path));
+
+        assert!(
+            matches!(result, Err(SourceError::Compressed(_))),
+            "got: {result:?}"
+        );
+        assert!(!registry().handles_extension(Path::new("hours.xlsx.gz")));
+        assert!(!registry().handles_extension(Path::new("timesheets.zip.gz")));
+        assert!(registry().handles_extension(Path::new("shifts.json.gz")));
+    }
+
+    #[test]
+    fn test_read_source_bytes_caps_decompressed_size() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("bomb.csv.gz");
+        let mut encoder =
+            GzEncoder::new(File::create(&path).expect("create gz"), Compression::best());
+        encoder.write_all(&[b'0'; 4096]).expect("write gz");
+        encoder.finish().expect("finish gz");
+
+        assert_eq!(
+            read_bytes_up_to(&path, 4096).map(|data| data.len()).ok(),
+            Some(4096)
+        );
+        assert!(matches!(
+            read_bytes_up_to(&path, 1024),
+            Err(SourceError::TooLarge { limit: 1024, .. })
+        ));
+    }
+}
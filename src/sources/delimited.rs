@@ -0,0 +1,789 @@
+// sources/delimited.rs
+
+use std::{collections::HashMap, fmt, io::Read, path::Path};
+
+use csv::{Position, ReaderBuilder};
+use tracing::debug;
+
+use super::{
+    CellValue, MalformedRow, Row, SourceError, SourceLayout, SourceRows,
+    archive::{open_source, read_source_bytes},
+    encoding::{Decoded, decode},
+    map_io_error,
+    registry::{ReadOptions, SourceFormat, SourceReader},
+};
+use crate::models::{CsvDelimiter, CsvQuote, FileFormModel, TextEncoding};
+
+/// Extensions handled by the CSV reader, lower-case, including those of
+/// tab-separated files.
+pub const CSV_EXTENSIONS: &[&str] = &[
+    "csv", "tsv", "tab",
+];
+
+/// Bytes from the start of a file looked at when sniffing its dialect.
+const SNIFF_BYTES: usize = 64 * 1024;
+
+/// Records compared when sniffing the delimiter.
+const SNIFF_RECORDS: usize = 50;
+
+/// Delimiters the sniffer chooses from, preferred in this order on a tie.
+const DELIMITERS: [u8; 4] = [
+    b',', b';', b'\t', b'|',
+];
+
+/// Reads delimited text files.
+pub struct CsvReader;
+
+impl SourceReader for CsvReader {
+    fn format(&self) -> SourceFormat {
+        SourceFormat::Csv
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        CSV_EXTENSIONS
+    }
+
+    fn reads_gzip(&self) -> bool {
+        true
+    }
+
+    fn sniff(
+        &self,
+        header: &[u8],
+    ) -> bool {
+        looks_delimited(header)
+    }
+
+    fn read(
+        &self,
+        path: &Path,
+        _sheet: Option<&str>,
+        options: &ReadOptions,
+    ) -> Result<SourceRows, SourceError> {
+        read_csv(path, &options.csv, &options.layout)
+    }
+}
+
+/// Line ending used by a CSV file. Records may end with any of them; the
+/// detected one is only reported.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+    Cr,
+}
+
+impl fmt::Display for LineEnding {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let label = match self {
+            Self::Lf => "LF",
+            Self::CrLf => "CRLF",
+            Self::Cr => "CR",
+        };
+        write!(f, "{label}")
+    }
+}
+
+/// The encoding, delimiter and quoting a CSV file is read with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    /// `None` reads quote characters as plain text.
+    pub quote: Option<u8>,
+    pub line_ending: LineEnding,
+    /// Name of the character encoding, such as `UTF-8` or `windows-1252`.
+    pub encoding: &'static str,
+    /// Whether the file starts with a byte order mark.
+    pub has_bom: bool,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: Some(b'"'),
+            line_ending: LineEnding::Lf,
+            encoding: "UTF-8",
+            has_bom: false,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Sniffs the dialect of `data`, the start of a CSV file, keeping the
+    /// encoding, delimiter and quote set in `options`.
+    ///
+    /// The delimiter is the one of comma, semicolon, tab and pipe that
+    /// splits the most records into the same number of fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpui_demo::sources::{CsvDialect, CsvOptions, LineEnding};
+    ///
+    /// let dialect = CsvDialect::sniff(b"Name;Hours\r\nAda;7,5\r\n", &CsvOptions::default());
+    /// assert_eq!(dialect.delimiter, b';');
+    /// assert_eq!(dialect.line_ending, LineEnding::CrLf);
+    /// ```
+    pub fn sniff(
+        data: &[u8],
+        options: &CsvOptions,
+    ) -> Self {
+        let sample = &data[..data.len().min(SNIFF_BYTES)];
+        Self::sniff_decoded(
+            &decode(sample, options.encoding),
+            options,
+            data.len() > sample.len(),
+        )
+    }
+
+    /// Sniffs the dialect of already decoded text; `truncated` tells that
+    /// the text is only the start of the file.
+    fn sniff_decoded(
+        decoded: &Decoded,
+        options: &CsvOptions,
+        truncated: bool,
+    ) -> Self {
+        let text = decoded.text.as_bytes();
+        let sample = &text[..text.len().min(SNIFF_BYTES)];
+        let truncated = truncated || text.len() > sample.len();
+
+        let quote = match options.quote {
+            CsvQuote::Auto => Some(sniff_quote(sample)),
+            CsvQuote::DoubleQuote => Some(b'"'),
+            CsvQuote::SingleQuote => Some(b'\''),
+            CsvQuote::NoQuoting => None,
+        };
+        let delimiter = options
+            .delimiter
+            .byte()
+            .unwrap_or_else(|| sniff_delimiter(sample, quote, truncated));
+
+        Self {
+            delimiter,
+            quote,
+            line_ending: sniff_line_ending(sample),
+            encoding: decoded.encoding,
+            has_bom: decoded.has_bom,
+        }
+    }
+
+    fn reader_builder(&self) -> ReaderBuilder {
+        let mut builder = ReaderBuilder::new();
+        builder
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(self.delimiter);
+        match self.quote {
+            Some(quote) => builder.quote(quote),
+            None => builder.quoting(false),
+        };
+        builder
+    }
+}
+
+impl fmt::Display for CsvDialect {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        let delimiter = match self.delimiter {
+            b',' => "comma".to_string(),
+            b';' => "semicolon".to_string(),
+            b'\t' => "tab".to_string(),
+            b'|' => "pipe".to_string(),
+            other => format!("'{}'", other as char),
+        };
+        let quote = match self.quote {
+            Some(b'"') => "double quotes".to_string(),
+            Some(b'\'') => "single quotes".to_string(),
+            Some(other) => format!("'{}' quotes", other as char),
+            None => "no quoting".to_string(),
+        };
+        write!(
+            f,
+            "{delimiter}-separated, {quote}, {} line endings, {}",
+            self.line_ending, self.encoding
+        )?;
+        if self.has_bom {
+            write!(f, " with BOM")?;
+        }
+        Ok(())
+    }
+}
+
+/// Dialect settings from the form; anything left on auto is sniffed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CsvOptions {
+    pub delimiter: CsvDelimiter,
+    pub quote: CsvQuote,
+    pub encoding: TextEncoding,
+}
+
+impl From<&FileFormModel> for CsvOptions {
+    fn from(model: &FileFormModel) -> Self {
+        Self {
+            delimiter: model.csv_delimiter,
+            quote: model.csv_quote,
+            encoding: model.encoding,
+        }
+    }
+}
+
+/// Sniffs the dialect of the CSV file at `path` from its first bytes.
+pub fn detect_dialect(
+    path: &Path,
+    options: &CsvOptions,
+) -> Result<CsvDialect, SourceError> {
+    let mut sample = Vec::new();
+    open_source(path)?
+        .take(SNIFF_BYTES as u64 + 1)
+        .read_to_end(&mut sample)
+        .map_err(|e| map_io_error(path, e))?;
+    Ok(CsvDialect::sniff(&sample, options))
+}
+
+/// Reads the records of a CSV file that `layout` selects as rows of text
+/// cells.
+///
+/// Records may have differing lengths; the caller decides what to do with
+/// rows that do not match the header width. Empty fields become
+/// [`CellValue::Empty`]. Row numbers in the layout count records, so a
+/// quoted field spanning several lines is still one row.
+///
+/// The file is transcoded to UTF-8 first (see [`decode`]). Malformed
+/// records are reported by line instead of failing the read: records
+/// holding bytes that are not valid in the file's encoding become empty
+/// rows, which keep the layout's row numbers in place and which the import
+/// skips as blank, while records whose field count differs from most
+/// others, and a quoted field that is never closed, are kept.
+pub fn read_csv(
+    path: &Path,
+    options: &CsvOptions,
+    layout: &SourceLayout,
+) -> Result<SourceRows, SourceError> {
+    let data = read_source_bytes(path)?;
+    let decoded = decode(&data, options.encoding);
+    let dialect = CsvDialect::sniff_decoded(&decoded, options, false);
+    debug!(path = %path.display(), %dialect, "Reading CSV");
+    let data = decoded.text.as_bytes();
+
+    let mut rows: Vec<Row> = Vec::new();
+    let mut offsets = Vec::new();
+    let mut reader = dialect.reader_builder().from_reader(data);
+    let limit = layout.rows_to_read(0).unwrap_or(usize::MAX);
+    for record in reader.records().take(limit) {
+        let record = record.map_err(|e| SourceError::Corrupt {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        offsets.push(record.position().map_or(0, Position::byte));
+        rows.push(record.iter().map(text_cell).collect());
+    }
+    // Text past the rows the layout needs is left unread.
+    let read_end = reader.position().byte() as usize;
+
+    // Each undecodable byte sequence spoils the record it falls in.
+    let mut malformed = Vec::new();
+    let mut spoiled = vec![false; rows.len()];
+    for error in decoded
+        .errors
+        .iter()
+        .filter(|error| error.text_offset < read_end)
+    {
+        let Some(index) = offsets
+            .partition_point(|&offset| offset as usize <= error.text_offset)
+            .checked_sub(1)
+        else {
+            continue;
+        };
+        if !spoiled[index] {
+            spoiled[index] = true;
+            malformed.push(MalformedRow {
+                line: line_at(data, offsets[index]),
+                message: format!(
+                    "{error} is not valid {}; the row was skipped",
+                    decoded.encoding
+                ),
+            });
+        }
+    }
+    let rows: Vec<Row> = rows
+        .into_iter()
+        .zip(spoiled)
+        .map(|(row, spoiled)| if spoiled { Vec::new() } else { row })
+        .collect();
+
+    // Only the part of each row the layout keeps must fit the common
+    // width, so a banner line or cells outside the range are no matter.
+    let kept = layout.select(&rows, (0, 0));
+    let rows = layout.apply(rows, (0, 0));
+    let checked: Vec<(&Row, u64)> = rows
+        .iter()
+        .zip(&kept)
+        .filter_map(|(row, index)| Some((row, offsets[(*index)?])))
+        .filter(|(row, _)| !row.iter().all(CellValue::is_blank))
+        .collect();
+    let widths: Vec<usize> = checked.iter().map(|(row, _)| row.len()).collect();
+    if let Some((width, _)) = most_common(&widths) {
+        for &(row, offset) in &checked {
+            if row.len() != width {
+                malformed.push(MalformedRow {
+                    line: line_at(data, offset),
+                    message: format!("has {} fields; most rows have {width}", row.len()),
+                });
+            }
+        }
+    }
+    if let Some(offset) = unclosed_quote(data, &dialect).filter(|&offset| offset < read_end) {
+        malformed.push(MalformedRow {
+            line: line_at(data, offset as u64),
+            message: "quoted field is never closed, so the rest of the file was read into it"
+                .to_string(),
+        });
+    }
+    malformed.sort_by_key(|row| row.line);
+
+    Ok(SourceRows {
+        rows,
+        malformed,
+        dialect: Some(dialect),
+        ..SourceRows::default()
+    })
+}
+
+/// Returns `true` if `header`, the start of a file, is text whose first
+/// line holds one of the [`DELIMITERS`].
+///
+/// Text with control characters other than tabs and line breaks is taken
+/// for binary data, and a single column of text is left for the file's
+/// extension to decide.
+///
+/// # Examples
+///
+/// ```
+/// use gpui_demo::sources::delimited::looks_delimited;
+///
+/// assert!(looks_delimited(b"Employee;Hours\r\nAda;7,5\r\n"));
+/// assert!(!looks_delimited(b"Employee\nAda\n"));
+/// assert!(!looks_delimited(b"PK\x03\x04\x14\x00,"));
+/// ```
+pub fn looks_delimited(header: &[u8]) -> bool {
+    let decoded = decode(header, TextEncoding::Auto);
+    let is_binary = decoded
+        .text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\t' | '\r' | '\n'));
+    let first_line = decoded
+        .text
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default();
+
+    !is_binary && first_line.bytes().any(|byte| DELIMITERS.contains(&byte))
+}
+
+fn text_cell(field: &str) -> CellValue {
+    if field.is_empty() {
+        CellValue::Empty
+    } else {
+        CellValue::Text(field.to_string())
+    }
+}
+
+/// Picks the quote character that opens the most fields.
+fn sniff_quote(sample: &[u8]) -> u8 {
+    let (mut double, mut single) = (0, 0);
+    let mut field_start = true;
+    for &byte in sample {
+        if field_start {
+            match byte {
+                b'"' => double += 1,
+                b'\'' => single += 1,
+                _ => {}
+            }
+        }
+        field_start = matches!(byte, b'\n' | b'\r') || DELIMITERS.contains(&byte);
+    }
+    if single > double { b'\'' } else { b'"' }
+}
+
+/// Picks the delimiter that splits the most records of `sample` into the
+/// same number of fields, falling back to a comma.
+///
+/// A `truncated` sample ends in a partial record, which is left out.
+fn sniff_delimiter(
+    sample: &[u8],
+    quote: Option<u8>,
+    truncated: bool,
+) -> u8 {
+    let sample = match sample.iter().rposition(|&byte| byte == b'\n') {
+        Some(end) if truncated => &sample[..end],
+        _ => sample,
+    };
+
+    DELIMITERS
+        .iter()
+        .rev()
+        .filter_map(|&delimiter| {
+            let dialect = CsvDialect {
+                delimiter,
+                quote,
+                ..CsvDialect::default()
+            };
+            let widths: Vec<usize> = dialect
+                .reader_builder()
+                .from_reader(sample)
+                .byte_records()
+                .take(SNIFF_RECORDS)
+                .filter_map(Result::ok)
+                .map(|record| record.len())
+                .collect();
+            let (width, consistent) = most_common(&widths)?;
+            (width > 1).then_some((consistent, width, delimiter))
+        })
+        .max_by_key(|&(consistent, width, _)| (consistent, width))
+        .map_or(b',', |(_, _, delimiter)| delimiter)
+}
+
+fn sniff_line_ending(sample: &[u8]) -> LineEnding {
+    match sample
+        .iter()
+        .position(|&byte| byte == b'\n' || byte == b'\r')
+    {
+        Some(index) if sample[index] == b'\n' => LineEnding::Lf,
+        Some(index) if sample.get(index + 1) == Some(&b'\n') => LineEnding::CrLf,
+        Some(_) => LineEnding::Cr,
+        None => LineEnding::Lf,
+    }
+}
+
+/// Returns the most common value and how often it occurs, preferring the
+/// larger value on a tie.
+fn most_common(values: &[usize]) -> Option<(usize, usize)> {
+    let mut counts: HashMap<usize, usize> = HashMap::new();
+    for &value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(value, count)| (count, value))
+}
+
+/// Returns the offset of the quote opening a quoted field that runs to
+/// the end of `data`, if there is one.
+fn unclosed_quote(
+    data: &[u8],
+    dialect: &CsvDialect,
+) -> Option<usize> {
+    let quote = dialect.quote?;
+    let mut opened_at = None;
+    let mut field_start = true;
+    let mut bytes = data.iter().enumerate().peekable();
+    while let Some((offset, &byte)) = bytes.next() {
+        match opened_at {
+            // A doubled quote inside a quoted field is an escaped quote.
+            Some(_) if byte == quote && bytes.peek().is_some_and(|&(_, &next)| next == quote) => {
+                bytes.next();
+            }
+            Some(_) if byte == quote => opened_at = None,
+            None if field_start && byte == quote => opened_at = Some(offset),
+            _ => {}
+        }
+        field_start =
+            opened_at.is_none() && (byte == dialect.delimiter || byte == b'\n' || byte == b'\r');
+    }
+    opened_at
+}
+
+/// Returns the one-based line of the record the CSV reader places at
+/// `offset`, counting LF, CRLF and a lone CR as one line break each.
+///
+/// The reader may place a record on the line feed ending the previous one,
+/// so line break bytes at `offset` are skipped first.
+fn line_at(
+    data: &[u8],
+    offset: u64,
+) -> u64 {
+    let mut start = (offset as usize).min(data.len());
+    while data
+        .get(start)
+        .is_some_and(|byte| matches!(byte, b'\n' | b'\r'))
+    {
+        start += 1;
+    }
+
+    let before = &data[..start];
+    let breaks = before
+        .iter()
+        .enumerate()
+        .filter(|&(index, &byte)| {
+            byte == b'\n' || (byte == b'\r' && before.get(index + 1) != Some(&b'\n'))
+        })
+        .count();
+    breaks as u64 + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::registry;
+    use pretty_assertions::assert_eq;
+    use std::{fs, path::PathBuf};
+    use tempfile::TempDir;
+
+    fn write_csv(
+        dir: &TempDir,
+        contents: &[u8],
+    ) -> PathBuf {
+        let path = dir.path().join("hours.csv");
+        fs::write(&path, contents).expect("write csv");
+        path
+    }
+
+    #[test]
+    fn test_read_csv_keeps_ragged_rows() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = write_csv(&dir, b"Employee,Hours\nAda,7.5\nGrace,,extra\n");
+
+        let read = read_csv(&path, &CsvOptions::default(), &SourceLayout::default())
+            .expect("csv should parse");
+        let expected = vec![
+            vec![
+                CellValue::Text("Employee".to_string()),
+                CellValue::Text("Hours".to_string()),
+            ],
+            vec![
+                CellValue::Text("Ada".to_string()),
+                CellValue::Text("7.5".to_string()),
+            ],
+            vec![
+                CellValue::Text("Grace".to_string()),
+                CellValue::Empty,
+                CellValue::Text("extra".to_string()),
+            ],
+        ];
+        assert_eq!(read.rows, expected);
+        assert_eq!(
+            read.malformed,
+            vec![
+                MalformedRow {
+                    line: 3,
+                    message: "has 3 fields; most rows have 2".to_string(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_csv_honours_layout() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = write_csv(
+            &dir,
+            b"Timesheet\n,,\nEmployee,Hours,Notes\nAda,7.5,\"late,\nleft early\"\nGrace,8\nTotal,7.5,,,\n",
+        );
+        let layout = SourceLayout {
+            has_headers: true,
+            header_row: Some(3),
+            skip_trailing_rows: 1,
+            range: Some("A3:B10".parse().expect("valid range")),
+            ..SourceLayout::default()
+        };
+
+        let read = read_csv(&path, &CsvOptions::default(), &layout).expect("csv should parse");
+        let expected = vec![
+            vec![
+                CellValue::Text("Employee".to_string()),
+                CellValue::Text("Hours".to_string()),
+            ],
+            vec![
+                CellValue::Text("Ada".to_string()),
+                CellValue::Text("7.5".to_string()),
+            ],
+            vec![
+                CellValue::Text("Grace".to_string()),
+                CellValue::Text("8".to_string()),
+            ],
+        ];
+        assert_eq!(read.rows, expected);
+        // The banner, the footer and the missing notes lie outside the
+        // range, so no row is malformed.
+        assert!(read.malformed.is_empty());
+    }
+
+    #[test]
+    fn test_read_csv_missing_file() {
+        let result = read_csv(
+            Path::new("/definitely/not/here.csv"),
+            &CsvOptions::default(),
+            &SourceLayout::default(),
+        );
+        assert!(
+            matches!(result, Err(SourceError::NotFound(_))),
+            "expected NotFound, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_sniff_dialect() {
+        let auto = CsvOptions::default();
+
+        assert_eq!(
+            CsvDialect::sniff(
+                b"\xEF\xBB\xBFName;Hours;Note\r\nAda;7,5;\"a;b\"\r\nGrace;8,25;\r\n",
+                &auto
+            ),
+            CsvDialect {
+                delimiter: b';',
+                quote: Some(b'"'),
+                line_ending: LineEnding::CrLf,
+                encoding: "UTF-8",
+                has_bom: true,
+            }
+        );
+        assert_eq!(
+            CsvDialect::sniff(b"Name\tHours\nAda\t7.5\n", &auto).delimiter,
+            b'\t'
+        );
+        assert_eq!(
+            CsvDialect::sniff(b"'Name'|'Hours'\r'Ada, Jr'|'7.5'\r", &auto),
+            CsvDialect {
+                delimiter: b'|',
+                quote: Some(b'\''),
+                line_ending: LineEnding::Cr,
+                encoding: "UTF-8",
+                has_bom: false,
+            }
+        );
+        assert_eq!(CsvDialect::sniff(b"Name\nAda\n", &auto).delimiter, b',');
+    }
+
+    #[test]
+    fn test_sniff_keeps_overrides() {
+        let options = CsvOptions {
+            delimiter: CsvDelimiter::Comma,
+            quote: CsvQuote::NoQuoting,
+            encoding: TextEncoding::Auto,
+        };
+
+        let dialect = CsvDialect::sniff(b"Name;Hours\nAda;7,5\n", &options);
+
+        assert_eq!((dialect.delimiter, dialect.quote), (b',', None));
+    }
+
+    #[test]
+    fn test_read_csv_reports_malformed_rows() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = write_csv(
+            &dir,
+            b"Employee;Notes\nAda;\"first\nsecond\"\nGr\xFFce;x\nLinus;y;z\nKen;\"open\nrest\n",
+        );
+
+        let options = CsvOptions {
+            encoding: TextEncoding::Utf8,
+            ..CsvOptions::default()
+        };
+
+        let read = read_csv(&path, &options, &SourceLayout::default()).expect("csv should parse");
+
+        assert_eq!(read.rows.len(), 5);
+        assert_eq!(
+            read.rows[1][1],
+            CellValue::Text("first\nsecond".to_string())
+        );
+        assert!(read.rows[2].is_empty());
+        let lines: Vec<u64> = read.malformed.iter().map(|row| row.line).collect();
+        assert_eq!(
+            lines,
+            vec![
+                4, 5, 6
+            ]
+        );
+        assert_eq!(
+            read.malformed[0].message,
+            "byte offset 36 is not valid UTF-8; the row was skipped"
+        );
+        assert!(read.malformed[2].message.contains("never closed"));
+    }
+
+    #[test]
+    fn test_read_csv_keeps_row_numbers_past_spoiled_rows() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = write_csv(&dir, b"Timesheet f\xFCr M\xE4rz\nEmployee,Hours\nAda,7.5\n");
+        let options = CsvOptions {
+            encoding: TextEncoding::Utf8,
+            ..CsvOptions::default()
+        };
+        let layout = SourceLayout {
+            has_headers: true,
+            header_row: Some(2),
+            ..SourceLayout::default()
+        };
+
+        let read = read_csv(&path, &options, &layout).expect("csv should parse");
+
+        assert_eq!(
+            read.rows,
+            vec![
+                vec![
+                    CellValue::Text("Employee".to_string()),
+                    CellValue::Text("Hours".to_string()),
+                ],
+                vec![
+                    CellValue::Text("Ada".to_string()),
+                    CellValue::Text("7.5".to_string()),
+                ],
+            ]
+        );
+        let lines: Vec<u64> = read.malformed.iter().map(|row| row.line).collect();
+        assert_eq!(lines, vec![1]);
+    }
+
+    #[test]
+    fn test_read_csv_transcodes_utf16() {
+        let dir = TempDir::new().expect("temp dir");
+        let text = "Employee;Hours\r\nJosé Núñez;7,5\r\nRenée;8\r\n";
+        let mut data = vec![
+            0xFF, 0xFE,
+        ];
+        data.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        let path = write_csv(&dir, &data);
+
+        let read = read_csv(&path, &CsvOptions::default(), &SourceLayout::default())
+            .expect("csv should parse");
+
+        assert_eq!(read.rows[1][0], CellValue::Text("José Núñez".to_string()));
+        assert_eq!(read.rows[2][1], CellValue::Text("8".to_string()));
+        assert_eq!(
+            read.dialect.map(|dialect| dialect.to_string()).as_deref(),
+            Some("semicolon-separated, double quotes, CRLF line endings, UTF-16LE with BOM")
+        );
+    }
+
+    #[test]
+    fn test_read_tsv_transcodes_utf16() {
+        let dir = TempDir::new().expect("temp dir");
+        let text = "Employee\tHours\r\nJosé Núñez\t7.5\r\n";
+        let mut data = vec![
+            0xFF, 0xFE,
+        ];
+        data.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        let path = dir.path().join("hours.TSV");
+        fs::write(&path, &data).expect("write tsv");
+
+        let reader = registry().detect(&path).expect("tsv should be detected");
+        let read = reader
+            .read(&path, None, &ReadOptions::default())
+            .expect("tsv should parse");
+
+        assert_eq!(reader.format(), SourceFormat::Csv);
+        assert_eq!(read.rows[1][0], CellValue::Text("José Núñez".to_string()));
+        assert_eq!(read.rows[1][1], CellValue::Text("7.5".to_string()));
+        assert_eq!(read.dialect.map(|dialect| dialect.delimiter), Some(b'\t'));
+        assert!(registry().handles_extension(Path::new("hours.tab")));
+    }
+}
@@ -0,0 +1,227 @@
+// sources/encoding.rs
+
+use std::{fmt, str};
+
+use chardetng::EncodingDetector;
+use encoding_rs::{DecoderResult, Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252, mem};
+
+use crate::models::TextEncoding;
+
+/// Bytes looked at when checking for UTF-16 without a byte order mark.
+const UTF16_SNIFF_BYTES: usize = 4096;
+
+/// A byte sequence that is not valid in the encoding it was decoded from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DecodeError {
+    /// Offset of the first bad byte in the source file.
+    pub offset: usize,
+    /// Offset in the decoded text of the replacement character standing in
+    /// for the bad bytes.
+    pub text_offset: usize,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "byte offset {}", self.offset)
+    }
+}
+
+/// A text source transcoded to UTF-8.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Decoded {
+    /// The text, with U+FFFD in place of each byte sequence in `errors`.
+    pub text: String,
+    /// Name of the encoding the text was decoded from, such as
+    /// `windows-1252`.
+    pub encoding: &'static str,
+    /// Whether the source started with a byte order mark, which is not part
+    /// of `text`.
+    pub has_bom: bool,
+    pub errors: Vec<DecodeError>,
+}
+
+/// Decodes the bytes of a text source as `encoding`, detecting it first
+/// for [`TextEncoding::Auto`].
+///
+/// # Examples
+///
+/// ```
+/// use gpui_demo::{models::TextEncoding, sources::encoding::decode};
+///
+/// let decoded = decode(b"\xFF\xFEJ\x00o\x00s\x00\xE9\x00", TextEncoding::Auto);
+/// assert_eq!((decoded.text.as_str(), decoded.encoding), ("José", "UTF-16LE"));
+///
+/// let decoded = decode(b"Jos\xC3", TextEncoding::Utf8);
+/// assert_eq!(decoded.errors[0].offset, 3);
+/// ```
+pub fn decode(
+    data: &[u8],
+    encoding: TextEncoding,
+) -> Decoded {
+    let encoding = match encoding {
+        TextEncoding::Auto => detect_encoding(data),
+        TextEncoding::Utf8 => UTF_8,
+        TextEncoding::Utf16Le => UTF_16LE,
+        TextEncoding::Utf16Be => UTF_16BE,
+        TextEncoding::Windows1252 => WINDOWS_1252,
+        TextEncoding::Latin1 => {
+            return Decoded {
+                text: mem::decode_latin1(data).into_owned(),
+                encoding: "ISO-8859-1",
+                ..Decoded::default()
+            };
+        }
+    };
+
+    let bom_length = match Encoding::for_bom(data) {
+        Some((bom_encoding, length)) if bom_encoding == encoding => length,
+        _ => 0,
+    };
+    let input = &data[bom_length..];
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut text = String::new();
+    let mut errors = Vec::new();
+    let mut read = 0;
+    loop {
+        let needed = decoder
+            .max_utf8_buffer_length_without_replacement(input.len() - read)
+            .unwrap_or(input.len() - read);
+        text.reserve(needed);
+        let (result, consumed) =
+            decoder.decode_to_string_without_replacement(&input[read..], &mut text, true);
+        read += consumed;
+        match result {
+            DecoderResult::InputEmpty => break,
+            DecoderResult::OutputFull => {}
+            DecoderResult::Malformed(bad, extra) => {
+                errors.push(DecodeError {
+                    offset: bom_length + read - extra as usize - bad as usize,
+                    text_offset: text.len(),
+                });
+                text.push(char::REPLACEMENT_CHARACTER);
+            }
+        }
+    }
+
+    Decoded {
+        text,
+        encoding: encoding.name(),
+        has_bom: bom_length > 0,
+        errors,
+    }
+}
+
+/// Detects the encoding of `data` from its byte order mark, or guesses it
+/// from the contents: UTF-16 when every other byte of mostly ASCII text is
+/// zero, UTF-8 when the bytes are valid UTF-8, and otherwise the legacy
+/// encoding the text looks most like, usually Windows-1252 for Western
+/// languages.
+pub fn detect_encoding(data: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(data) {
+        return encoding;
+    }
+    if let Some(encoding) = detect_utf16(data) {
+        return encoding;
+    }
+    match str::from_utf8(data) {
+        // Also accept a sample cut off inside a multi-byte character.
+        Ok(_) => return UTF_8,
+        Err(error) if error.error_len().is_none() => return UTF_8,
+        Err(_) => {}
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(data, true);
+    detector.guess(None, false)
+}
+
+/// Recognizes UTF-16 without a byte order mark by the zero high bytes of
+/// ASCII characters.
+fn detect_utf16(data: &[u8]) -> Option<&'static Encoding> {
+    let sample = &data[..data.len().min(UTF16_SNIFF_BYTES)];
+    if sample.len() < 2 {
+        return None;
+    }
+    let zeros_at = |parity: usize| {
+        sample
+            .iter()
+            .skip(parity)
+            .step_by(2)
+            .filter(|&&byte| byte == 0)
+            .count()
+    };
+    let (even, odd) = (zeros_at(0), zeros_at(1));
+    let pairs = sample.len() / 2;
+
+    if odd * 2 > pairs && even * 10 < odd {
+        Some(UTF_16LE)
+    } else if even * 2 > pairs && odd * 10 < even {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    }
+
+    #[test]
+    fn test_detects_windows_tool_encodings() {
+        let mut with_bom = vec![
+            0xFF, 0xFE,
+        ];
+        with_bom.extend(utf16le("Employee,Hours\r\nJosé Núñez,7.5\r\n"));
+        let decoded = decode(&with_bom, TextEncoding::Auto);
+        assert_eq!(decoded.text, "Employee,Hours\r\nJosé Núñez,7.5\r\n");
+        assert_eq!((decoded.encoding, decoded.has_bom), ("UTF-16LE", true));
+
+        let without_bom = utf16le("Employee,Hours\nRenée,8\n");
+        assert_eq!(
+            decode(&without_bom, TextEncoding::Auto).text,
+            "Employee,Hours\nRenée,8\n"
+        );
+
+        let legacy = b"Employee;Hours\r\nJos\xE9 N\xFA\xF1ez;7,5\r\nFran\xE7oise M\xFCller;8\r\n";
+        let decoded = decode(legacy, TextEncoding::Auto);
+        assert_eq!(decoded.encoding, "windows-1252");
+        assert!(decoded.text.contains("José Núñez") && decoded.text.contains("Françoise Müller"));
+        assert!(decoded.errors.is_empty());
+    }
+
+    #[test]
+    fn test_override_reports_bad_bytes() {
+        let decoded = decode(b"\xEF\xBB\xBFAda\nGr\xFFce\nLinus\n", TextEncoding::Utf8);
+
+        assert_eq!(decoded.text, "Ada\nGr\u{FFFD}ce\nLinus\n");
+        assert!(decoded.has_bom);
+        assert_eq!(
+            decoded.errors,
+            vec![
+                DecodeError {
+                    offset: 9,
+                    text_offset: 6,
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_latin1_keeps_every_byte() {
+        let decoded = decode(b"caf\xE9 \x80", TextEncoding::Latin1);
+
+        assert_eq!(decoded.text, "café \u{80}");
+        assert_eq!(
+            decode(b"caf\xE9 \x80", TextEncoding::Windows1252).text,
+            "café €"
+        );
+    }
+}
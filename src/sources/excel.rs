@@ -0,0 +1,544 @@
+// sources/excel.rs
+
+use std::{fs::File, io::BufReader, path::Path};
+
+use calamine::{
+    Data, DataType, Error as CalamineError, OdsError, Reader, SheetVisible, Sheets, XlsError,
+    open_workbook, open_workbook_auto,
+};
+use chrono::{NaiveDateTime, TimeDelta};
+
+use super::{
+    CellValue, Row, SheetInfo, SheetVisibility, SourceError, SourceLayout, SourceRows,
+    archive::check_not_compressed,
+    map_io_error,
+    registry::{ReadOptions, SourceFormat, SourceReader, has_extension, read_header},
+};
+
+/// Extensions handled by the workbook reader, lower-case.
+pub const WORKBOOK_EXTENSIONS: &[&str] = &[
+    "xlsx", "xlsm", "xlsb", "xls", "ods",
+];
+
+/// Magic bytes of an OLE compound file. Legacy `.xls` workbooks use this
+/// container, and so do password-encrypted OOXML workbooks.
+const CFB_MAGIC: [u8; 8] = [
+    0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1,
+];
+
+/// Magic bytes of a zip archive, the container of `.xlsx`, `.xlsb` and
+/// `.ods` workbooks.
+pub(crate) const ZIP_MAGIC: [u8; 4] = [
+    0x50, 0x4B, 0x03, 0x04,
+];
+
+/// Names that the first entry of a zip archive holding a workbook starts
+/// with: the parts Office Open XML and OpenDocument writers put first.
+const WORKBOOK_PARTS: &[&[u8]] = &[
+    b"[Content_Types].xml",
+    b"_rels/",
+    b"docProps/",
+    b"xl/",
+    b"customXml/",
+    b"mimetype",
+    b"META-INF/",
+    b"content.xml",
+];
+
+/// Media type an OpenDocument spreadsheet stores as its first zip entry.
+const ODS_MEDIA_TYPE: &[u8] = b"application/vnd.oasis.opendocument.spreadsheet";
+
+/// Reads the workbook formats calamine supports.
+pub struct WorkbookReader;
+
+impl SourceReader for WorkbookReader {
+    fn format(&self) -> SourceFormat {
+        SourceFormat::Workbook
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        WORKBOOK_EXTENSIONS
+    }
+
+    fn sniff(
+        &self,
+        header: &[u8],
+    ) -> bool {
+        is_workbook_header(header)
+    }
+
+    fn list_sheets(
+        &self,
+        path: &Path,
+    ) -> Result<Vec<SheetInfo>, SourceError> {
+        list_sheets(path)
+    }
+
+    fn read(
+        &self,
+        path: &Path,
+        sheet: Option<&str>,
+        options: &ReadOptions,
+    ) -> Result<SourceRows, SourceError> {
+        read_sheet(path, sheet, &options.layout).map(SourceRows::from)
+    }
+}
+
+/// Opens the workbook at `path` and lists its sheets in workbook order.
+///
+/// Hidden and very-hidden sheets are included and marked through
+/// [`SheetInfo::visibility`].
+pub fn list_sheets(path: &Path) -> Result<Vec<SheetInfo>, SourceError> {
+    let workbook = open(path)?;
+    let sheets = workbook
+        .sheets_metadata()
+        .iter()
+        .map(|sheet| SheetInfo::new(sheet.name.clone(), map_visibility(sheet.visible)))
+        .collect();
+
+    Ok(sheets)
+}
+
+/// Reads the rows of `sheet` that `layout` selects from the workbook at
+/// `path`.
+///
+/// When `sheet` is `None` the first sheet in workbook order is read. With
+/// the default layout, rows start at the first non-empty cell of the sheet
+/// and are padded to a common width with [`CellValue::Empty`]; layout row
+/// numbers and ranges refer to the sheet's own row and column labels.
+pub fn read_sheet(
+    path: &Path,
+    sheet: Option<&str>,
+    layout: &SourceLayout,
+) -> Result<Vec<Row>, SourceError> {
+    let mut workbook = open(path)?;
+    let name = match sheet {
+        Some(name) => name.to_string(),
+        None => workbook
+            .sheet_names()
+            .first()
+            .cloned()
+            .ok_or_else(|| SourceError::Corrupt {
+                path: path.to_path_buf(),
+                message: "workbook has no sheets".to_string(),
+            })?,
+    };
+    if !workbook.sheet_names().contains(&name) {
+        return Err(SourceError::SheetNotFound {
+            path: path.to_path_buf(),
+            sheet: name,
+        });
+    }
+
+    let range = workbook
+        .worksheet_range(&name)
+        .map_err(|e| map_calamine_error(path, e))?;
+    let origin = range
+        .start()
+        .map_or((0, 0), |(row, column)| (row as usize, column as usize));
+    let rows = range
+        .rows()
+        .take(layout.rows_to_read(origin.0).unwrap_or(usize::MAX))
+        .map(|row| row.iter().map(convert_cell).collect())
+        .collect();
+
+    Ok(layout.apply(rows, origin))
+}
+
+fn convert_cell(cell: &Data) -> CellValue {
+    match cell {
+        Data::Empty => CellValue::Empty,
+        Data::Int(value) => CellValue::Int(*value),
+        Data::Float(value) => CellValue::Float(*value),
+        Data::Bool(value) => CellValue::Bool(*value),
+        Data::String(value) => CellValue::Text(value.clone()),
+        Data::DateTime(value) if value.is_duration() => value
+            .as_duration()
+            .map_or(CellValue::Float(value.as_f64()), CellValue::Duration),
+        Data::DateTime(value) => value
+            .as_datetime()
+            .map_or(CellValue::Float(value.as_f64()), datetime_cell),
+        // OpenDocument stores dates and times as ISO 8601 text.
+        Data::DateTimeIso(value) => cell
+            .as_datetime()
+            .map(datetime_cell)
+            .or_else(|| cell.as_date().map(CellValue::Date))
+            .unwrap_or_else(|| CellValue::Text(value.clone())),
+        Data::DurationIso(value) => parse_iso_duration(value)
+            .map_or_else(|| CellValue::Text(value.clone()), CellValue::Duration),
+        Data::Error(error) => CellValue::Text(format!("#{error}")),
+    }
+}
+
+/// Collapses midnight timestamps to plain dates, which is how workbooks
+/// store date-only cells.
+fn datetime_cell(value: NaiveDateTime) -> CellValue {
+    if value.time() == NaiveDateTime::MIN.time() {
+        CellValue::Date(value.date())
+    } else {
+        CellValue::DateTime(value)
+    }
+}
+
+/// Parses an ISO 8601 duration such as `PT07H30M00S` or `P1DT2H`, the
+/// form OpenDocument stores time cells in. Hours may exceed a day; years
+/// and months are not accepted, since their length varies.
+fn parse_iso_duration(text: &str) -> Option<TimeDelta> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let text = text.strip_prefix('P')?;
+    let (date, time) = text.split_once('T').unwrap_or((text, ""));
+
+    let mut seconds = 0.0;
+    let mut found = false;
+    for (part, units) in [
+        (date, &[('D', 86_400.0)][..]),
+        (
+            time,
+            &[
+                ('H', 3_600.0),
+                ('M', 60.0),
+                ('S', 1.0),
+            ][..],
+        ),
+    ] {
+        let mut rest = part;
+        for &(unit, scale) in units {
+            if let Some((value, after)) = rest.split_once(unit) {
+                seconds += value.parse::<f64>().ok()? * scale;
+                found = true;
+                rest = after;
+            }
+        }
+        if !rest.is_empty() {
+            return None;
+        }
+    }
+    if !found {
+        return None;
+    }
+
+    let duration = TimeDelta::milliseconds((seconds * 1000.0).round() as i64);
+    Some(if negative { -duration } else { duration })
+}
+
+fn map_visibility(visible: SheetVisible) -> SheetVisibility {
+    match visible {
+        SheetVisible::Visible => SheetVisibility::Visible,
+        SheetVisible::Hidden => SheetVisibility::Hidden,
+        SheetVisible::VeryHidden => SheetVisibility::VeryHidden,
+    }
+}
+
+/// Opens the workbook at `path` by the container its first bytes show, so
+/// a workbook saved under another extension still opens.
+///
+/// Fails with [`SourceError::UnsupportedFormat`] when neither the contents
+/// nor the extension say the file is a workbook, and with
+/// [`SourceError::Compressed`] for a gzip-compressed workbook, which
+/// calamine cannot open.
+fn open(path: &Path) -> Result<Sheets<BufReader<File>>, SourceError> {
+    let by_extension = has_extension(path, WORKBOOK_EXTENSIONS);
+    let header = match read_header(path) {
+        Ok(header) if by_extension || is_workbook_header(&header) => header,
+        Err(error) if by_extension => return Err(error),
+        _ => return Err(SourceError::UnsupportedFormat(path.to_path_buf())),
+    };
+    check_not_compressed(path)?;
+    check_not_encrypted(path, &header)?;
+
+    let opened = if header.starts_with(&CFB_MAGIC) {
+        open_workbook(path)
+            .map(Sheets::Xls)
+            .map_err(CalamineError::Xls)
+    } else if is_ods_header(&header) {
+        open_workbook(path)
+            .map(Sheets::Ods)
+            .map_err(CalamineError::Ods)
+    } else if header.starts_with(&ZIP_MAGIC) && has_extension(path, &["xlsb"]) {
+        open_workbook(path)
+            .map(Sheets::Xlsb)
+            .map_err(CalamineError::Xlsb)
+    } else if header.starts_with(&ZIP_MAGIC) {
+        open_workbook(path)
+            .map(Sheets::Xlsx)
+            .map_err(CalamineError::Xlsx)
+            .or_else(|error| open_workbook(path).map(Sheets::Xlsb).map_err(|_| error))
+    } else {
+        open_workbook_auto(path)
+    };
+    opened.map_err(|e| map_calamine_error(path, e))
+}
+
+/// Returns `true` if `header` starts a workbook container: a compound
+/// document, or a zip archive whose first entry is a workbook part rather
+/// than, say, a CSV file.
+pub(crate) fn is_workbook_header(header: &[u8]) -> bool {
+    if header.starts_with(&CFB_MAGIC) {
+        return true;
+    }
+    if !header.starts_with(&ZIP_MAGIC) || header.len() < 30 {
+        return false;
+    }
+    let name_length = u16::from_le_bytes([
+        header[26], header[27],
+    ]) as usize;
+    header
+        .get(30..30 + name_length)
+        .is_some_and(|name| WORKBOOK_PARTS.iter().any(|part| name.starts_with(part)))
+}
+
+/// Returns `true` if `header` starts an OpenDocument spreadsheet, whose
+/// first zip entry is an uncompressed `mimetype` file naming its type.
+fn is_ods_header(header: &[u8]) -> bool {
+    if !header.starts_with(&ZIP_MAGIC) || header.len() < 30 {
+        return false;
+    }
+    let name_length = u16::from_le_bytes([
+        header[26], header[27],
+    ]) as usize;
+    let extra_length = u16::from_le_bytes([
+        header[28], header[29],
+    ]) as usize;
+    let data = 30 + name_length + extra_length;
+    header.get(30..30 + name_length) == Some(b"mimetype".as_slice())
+        && header
+            .get(data..)
+            .is_some_and(|rest| rest.starts_with(ODS_MEDIA_TYPE))
+}
+
+/// Rejects OOXML workbooks that are wrapped in an encrypted OLE container.
+///
+/// Excel stores password-protected `.xlsx`/`.xlsm`/`.xlsb` files as compound
+/// documents instead of zip archives, which calamine would otherwise report
+/// as a generic zip error.
+fn check_not_encrypted(
+    path: &Path,
+    header: &[u8],
+) -> Result<(), SourceError> {
+    let is_ooxml = has_extension(
+        path,
+        &[
+            "xlsx", "xlsm", "xlsb",
+        ],
+    );
+    if is_ooxml && header.starts_with(&CFB_MAGIC) {
+        return Err(SourceError::PasswordProtected(path.to_path_buf()));
+    }
+    Ok(())
+}
+
+fn map_calamine_error(
+    path: &Path,
+    error: CalamineError,
+) -> SourceError {
+    match error {
+        CalamineError::Io(e) => map_io_error(path, e),
+        CalamineError::Xls(XlsError::Password) | CalamineError::Ods(OdsError::Password) => {
+            SourceError::PasswordProtected(path.to_path_buf())
+        }
+        other => SourceError::Corrupt {
+            path: path.to_path_buf(),
+            message: other.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn test_list_sheets_xlsx_in_workbook_order() {
+        let sheets = list_sheets(&fixture("timesheet.xlsx")).expect("fixture should open");
+        let expected = vec![
+            SheetInfo::new("Hours", SheetVisibility::Visible),
+            SheetInfo::new("Archive", SheetVisibility::Hidden),
+            SheetInfo::new("Lookup", SheetVisibility::VeryHidden),
+        ];
+        assert_eq!(sheets, expected);
+    }
+
+    #[test]
+    fn test_read_sheet_converts_cell_types() {
+        let rows = read_sheet(
+            &fixture("timesheet.xlsx"),
+            Some("Hours"),
+            &SourceLayout::default(),
+        )
+        .expect("sheet");
+        let date = NaiveDate::from_ymd_opt(2024, 1, 8).expect("valid date");
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[1],
+            vec![
+                CellValue::Text("Ada".to_string()),
+                CellValue::Date(date),
+                CellValue::Float(7.5),
+                CellValue::Bool(true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_sheet_defaults_to_first_sheet() {
+        let rows =
+            read_sheet(&fixture("timesheet.xlsx"), None, &SourceLayout::default()).expect("sheet");
+        assert_eq!(rows[0][0], CellValue::Text("Employee".to_string()));
+    }
+
+    #[test]
+    fn test_read_sheet_honours_layout() {
+        // The sheet starts at B2 with a title banner and ends with a totals
+        // row below a blank row.
+        let layout = SourceLayout {
+            has_headers: true,
+            header_row: Some(4),
+            skip_trailing_rows: 1,
+            range: Some("B1:C100".parse().expect("valid range")),
+            ..SourceLayout::default()
+        };
+
+        let rows = read_sheet(&fixture("banner.xlsx"), None, &layout).expect("sheet");
+
+        assert_eq!(rows.len(), 5, "got: {rows:?}");
+        assert_eq!(
+            rows[0],
+            vec![
+                CellValue::Text("Employee".to_string()),
+                CellValue::Text("Hours".to_string()),
+            ]
+        );
+        assert_eq!(
+            rows[3],
+            vec![
+                CellValue::Text("Linus".to_string()),
+                CellValue::Float(6.25),
+            ]
+        );
+        assert_eq!(rows[4], vec![CellValue::Empty; 2]);
+    }
+
+    #[test]
+    fn test_read_sheet_unknown_sheet() {
+        let result = read_sheet(
+            &fixture("timesheet.xlsx"),
+            Some("Missing"),
+            &SourceLayout::default(),
+        );
+        assert!(
+            matches!(result, Err(SourceError::SheetNotFound { ref sheet, .. }) if sheet == "Missing"),
+            "expected SheetNotFound, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_list_sheets_ods_marks_hidden() {
+        let sheets = list_sheets(&fixture("timesheet.ods")).expect("fixture should open");
+        let expected = vec![
+            SheetInfo::new("Hours", SheetVisibility::Visible),
+            SheetInfo::new("Notes", SheetVisibility::Hidden),
+        ];
+        assert_eq!(sheets, expected);
+    }
+
+    #[test]
+    fn test_list_sheets_missing_file() {
+        let result = list_sheets(&fixture("does-not-exist.xlsx"));
+        assert!(
+            matches!(result, Err(SourceError::NotFound(_))),
+            "expected NotFound, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_list_sheets_corrupt_file() {
+        let result = list_sheets(&fixture("corrupt.xlsx"));
+        assert!(
+            matches!(result, Err(SourceError::Corrupt { .. })),
+            "expected Corrupt, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_list_sheets_encrypted_file() {
+        let result = list_sheets(&fixture("encrypted.xlsx"));
+        assert!(
+            matches!(result, Err(SourceError::PasswordProtected(_))),
+            "expected PasswordProtected, got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_read_sheet_ods_types_and_repeats() {
+        let rows =
+            read_sheet(&fixture("timesheet.ods"), None, &SourceLayout::default()).expect("sheet");
+        let date = NaiveDate::from_ymd_opt(2024, 1, 8).expect("valid date");
+
+        // The last row is stored once with a repeat count of two.
+        assert_eq!(rows.len(), 5, "got: {rows:?}");
+        assert_eq!(
+            rows[1],
+            vec![
+                CellValue::Text("Ada".to_string()),
+                CellValue::Date(date),
+                CellValue::Duration(TimeDelta::minutes(450)),
+                CellValue::Float(0.75),
+            ]
+        );
+        assert_eq!(rows[3], rows[4]);
+    }
+
+    #[test]
+    fn test_parse_iso_duration() {
+        assert_eq!(
+            parse_iso_duration("PT31H00M00S"),
+            Some(TimeDelta::hours(31))
+        );
+        assert_eq!(
+            parse_iso_duration("P1DT2H30M"),
+            Some(TimeDelta::minutes(26 * 60 + 30))
+        );
+        assert_eq!(
+            parse_iso_duration("-PT0H15M7.5S"),
+            Some(-TimeDelta::milliseconds(907_500))
+        );
+        assert_eq!(parse_iso_duration("P1Y"), None);
+        assert_eq!(parse_iso_duration("PT"), None);
+    }
+
+    #[test]
+    fn test_list_sheets_goes_by_contents() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let renamed = dir.path().join("timesheet.csv");
+        std::fs::copy(fixture("timesheet.ods"), &renamed).expect("copy workbook");
+
+        let sheets = list_sheets(&renamed).expect("renamed workbook should open");
+
+        assert_eq!(sheets[0], SheetInfo::new("Hours", SheetVisibility::Visible));
+        assert!(is_ods_header(&read_header(&renamed).expect("header")));
+        assert!(!is_ods_header(
+            &read_header(&fixture("timesheet.xlsx")).expect("header")
+        ));
+    }
+
+    #[test]
+    fn test_list_sheets_rejects_csv() {
+        let result = list_sheets(Path::new("input.csv"));
+        assert!(
+            matches!(result, Err(SourceError::UnsupportedFormat(_))),
+            "expected UnsupportedFormat, got: {result:?}"
+        );
+    }
+}
@@ -0,0 +1,316 @@
+// sources/fixed_width.rs
+
+use std::path::Path;
+
+use tracing::debug;
+
+use super::{
+    CellValue, MalformedRow, Row, SourceError, SourceLayout, SourceRows,
+    archive::read_source_bytes,
+    encoding::decode,
+    registry::{ReadOptions, SourceFormat, SourceReader},
+};
+use crate::models::{FixedWidthColumn, MAX_POSITION, TextEncoding};
+
+/// Extensions handled by the fixed-width reader, lower-case.
+pub const FIXED_WIDTH_EXTENSIONS: &[&str] = &[
+    "txt", "dat", "prn",
+];
+
+/// Reads fixed-width text, cutting each line into the columns of the
+/// form's layout.
+///
+/// Fixed-width text has nothing to recognize it by, so a text file is read
+/// this way when the form defines a layout, or else by its extension.
+pub struct FixedWidthReader;
+
+impl SourceReader for FixedWidthReader {
+    fn format(&self) -> SourceFormat {
+        SourceFormat::FixedWidth
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        FIXED_WIDTH_EXTENSIONS
+    }
+
+    fn reads_gzip(&self) -> bool {
+        true
+    }
+
+    fn sniff(
+        &self,
+        _header: &[u8],
+    ) -> bool {
+        false
+    }
+
+    fn claims(
+        &self,
+        options: &ReadOptions,
+    ) -> bool {
+        !options.fixed_width.is_empty()
+    }
+
+    fn read(
+        &self,
+        path: &Path,
+        _sheet: Option<&str>,
+        options: &ReadOptions,
+    ) -> Result<SourceRows, SourceError> {
+        read_fixed_width(
+            path,
+            &options.fixed_width,
+            options.csv.encoding,
+            &options.layout,
+        )
+    }
+}
+
+/// Reads the lines of the fixed-width text file at `path` as rows of the
+/// `columns` of a layout, then keeps the rows `layout` selects.
+///
+/// The file is transcoded to UTF-8 first (see [`decode`]). Each cell is the
+/// text of its column with the padding trimmed. The first row always holds
+/// the layout's column names, so [`SourceRows::header_in_source`] is set;
+/// with [`SourceLayout::has_headers`] the file's own header line is read
+/// and dropped in their favour. Lines holding bytes that are not valid in
+/// the file's encoding are reported and read as empty rows, so the layout's
+/// row numbers still count them and the import skips them as blank. A read
+/// with a row limit also returns the lines it read in
+/// [`SourceRows::raw_lines`].
+pub fn read_fixed_width(
+    path: &Path,
+    columns: &[FixedWidthColumn],
+    encoding: TextEncoding,
+    layout: &SourceLayout,
+) -> Result<SourceRows, SourceError> {
+    if columns.is_empty() {
+        return Err(SourceError::Corrupt {
+            path: path.to_path_buf(),
+            message: "fixed-width text needs a layout of columns to read".to_string(),
+        });
+    }
+
+    let data = read_source_bytes(path)?;
+    let decoded = decode(&data, encoding);
+    debug!(
+        path = %path.display(),
+        encoding = decoded.encoding,
+        columns = columns.len(),
+        "Reading fixed-width text"
+    );
+
+    let mut rows: Vec<Row> = Vec::new();
+    let mut raw_lines = Vec::new();
+    let mut malformed = Vec::new();
+    let mut errors = decoded.errors.iter().peekable();
+    let mut offset = 0;
+    let lines = decoded.text.split_inclusive('\n').enumerate();
+    for (index, line) in lines.take(layout.rows_to_read(0).unwrap_or(usize::MAX)) {
+        let end = offset + line.len();
+        let mut bad = false;
+        while let Some(error) = errors.next_if(|error| error.text_offset < end) {
+            if !bad {
+                malformed.push(MalformedRow {
+                    line: index as u64 + 1,
+                    message: format!(
+                        "{error} is not valid {}; the line was skipped",
+                        decoded.encoding
+                    ),
+                });
+            }
+            bad = true;
+        }
+        offset = end;
+
+        let line = line.trim_end_matches([
+            '\r', '\n',
+        ]);
+        if layout.row_limit.is_some() {
+            raw_lines.push(line.to_string());
+        }
+        rows.push(if bad {
+            Vec::new()
+        } else {
+            columns
+                .iter()
+                .map(|column| text_cell(column.cut(line)))
+                .collect()
+        });
+    }
+
+    let mut rows = layout.apply(rows, (0, 0));
+    if layout.has_headers && !rows.is_empty() {
+        rows.remove(0);
+    }
+    let header: Row = columns
+        .iter()
+        .map(|column| CellValue::Text(column.name.trim().to_string()))
+        .collect();
+    rows.insert(0, header);
+
+    Ok(SourceRows {
+        rows,
+        malformed,
+        header_in_source: true,
+        raw_lines,
+        ..SourceRows::default()
+    })
+}
+
+/// Draws the columns of a layout as a line to show above the text it cuts:
+/// each column starts with `|` followed by as much of its name as fits.
+///
+/// # Examples
+///
+/// ```
+/// use gpui_demo::{models::FixedWidthColumn, sources::fixed_width::ruler};
+///
+/// let columns = [
+///     FixedWidthColumn::new("id", 1, 5),
+///     FixedWidthColumn::new("hours", 9, 4),
+/// ];
+/// assert_eq!(ruler(&columns), "|id     |hou");
+/// ```
+pub fn ruler(columns: &[FixedWidthColumn]) -> String {
+    let width = columns
+        .iter()
+        .map(|column| column.span().end)
+        .max()
+        .unwrap_or(0)
+        .min(MAX_POSITION);
+    let mut marks = vec![' '; width];
+    for column in columns {
+        let span = column.span();
+        if span.is_empty() || span.start >= width {
+            continue;
+        }
+        marks[span.start] = '|';
+        for (mark, c) in marks[span.start + 1..span.end.min(width)]
+            .iter_mut()
+            .zip(column.name.trim().chars())
+        {
+            *mark = c;
+        }
+    }
+    marks.into_iter().collect::<String>().trim_end().to_string()
+}
+
+fn text_cell(field: &str) -> CellValue {
+    if field.is_empty() {
+        CellValue::Empty
+    } else {
+        CellValue::Text(field.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn text(value: &str) -> CellValue {
+        CellValue::Text(value.to_string())
+    }
+
+    fn punch_clock() -> Vec<FixedWidthColumn> {
+        vec![
+            FixedWidthColumn::new("employee", 1, 5),
+            FixedWidthColumn::new("name", 6, 8),
+            FixedWidthColumn::new("hours", 14, 5),
+        ]
+    }
+
+    #[test]
+    fn test_read_fixed_width_cuts_columns() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("punches.txt");
+        fs::write(
+            &path,
+            b"EMPNONAME    HOURS\r\n00417Ren\xE9e   07.50\r\n00418Fran\xE7ois 8\r\n00419\r\n",
+        )
+        .expect("write punches");
+        let layout = SourceLayout {
+            has_headers: true,
+            ..SourceLayout::default()
+        };
+
+        let read = read_fixed_width(&path, &punch_clock(), TextEncoding::Windows1252, &layout)
+            .expect("fixed-width text should read");
+
+        assert!(read.header_in_source);
+        assert!(read.malformed.is_empty());
+        assert_eq!(
+            read.rows,
+            vec![
+                vec![
+                    text("employee"),
+                    text("name"),
+                    text("hours"),
+                ],
+                vec![
+                    text("00417"),
+                    text("Renée"),
+                    text("07.50"),
+                ],
+                vec![
+                    text("00418"),
+                    text("François"),
+                    text("8"),
+                ],
+                vec![
+                    text("00419"),
+                    CellValue::Empty,
+                    CellValue::Empty,
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_fixed_width_skips_undecodable_lines() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("punches.txt");
+        fs::write(
+            &path,
+            b"00417Ada     7.5\n00418Gr\xFFce   8\n00419Linus   6\n",
+        )
+        .expect("write punches");
+
+        let read = read_fixed_width(
+            &path,
+            &punch_clock(),
+            TextEncoding::Utf8,
+            &SourceLayout::default(),
+        )
+        .expect("fixed-width text should read");
+
+        assert_eq!(read.rows.len(), 4);
+        assert!(read.rows[2].is_empty());
+        assert_eq!(
+            read.malformed,
+            vec![
+                MalformedRow {
+                    line: 2,
+                    message: "byte offset 24 is not valid UTF-8; the line was skipped".to_string(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ruler_marks_column_starts() {
+        assert_eq!(ruler(&punch_clock()), "|empl|name   |hour");
+        assert_eq!(ruler(&[]), "");
+        assert_eq!(
+            ruler(&[
+                FixedWidthColumn::new("id", 1, 2),
+                FixedWidthColumn::new("note", 3, usize::MAX),
+                FixedWidthColumn::new("far", usize::MAX, 1),
+            ]),
+            "|i|note"
+        );
+    }
+}
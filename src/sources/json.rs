@@ -0,0 +1,509 @@
+// sources/json.rs
+
+use std::{collections::HashMap, iter, path::Path};
+
+use serde_json::{Deserializer, Map, Value, error::Category};
+use tracing::debug;
+
+use super::{
+    CellValue, MalformedRow, Row, SheetInfo, SheetVisibility, SourceError, SourceLayout,
+    SourceRows,
+    archive::read_source_bytes,
+    encoding::decode,
+    registry::{ReadOptions, SourceFormat, SourceReader},
+};
+use crate::models::TextEncoding;
+
+/// Extensions handled by the JSON reader, lower-case.
+pub const JSON_EXTENSIONS: &[&str] = &[
+    "json", "jsonl", "ndjson",
+];
+
+/// Column holding array items that are not objects.
+pub const VALUE_COLUMN: &str = "value";
+
+/// Reads JSON documents and JSON Lines files.
+pub struct JsonReader;
+
+impl SourceReader for JsonReader {
+    fn format(&self) -> SourceFormat {
+        SourceFormat::Json
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        JSON_EXTENSIONS
+    }
+
+    fn reads_gzip(&self) -> bool {
+        true
+    }
+
+    fn sniff(
+        &self,
+        header: &[u8],
+    ) -> bool {
+        looks_like_json(header)
+    }
+
+    fn list_sheets(
+        &self,
+        path: &Path,
+    ) -> Result<Vec<SheetInfo>, SourceError> {
+        list_arrays(path)
+    }
+
+    fn read(
+        &self,
+        path: &Path,
+        sheet: Option<&str>,
+        options: &ReadOptions,
+    ) -> Result<SourceRows, SourceError> {
+        read_json(path, sheet, &options.layout)
+    }
+}
+
+/// A parsed JSON source.
+enum Document {
+    /// A single JSON value.
+    Single(Value),
+    /// One value per line; lines that did not parse are already reported.
+    Lines(Vec<Value>, Vec<MalformedRow>),
+}
+
+/// Lists the arrays at the top level of the JSON document at `path`, which
+/// can be imported like the sheets of a workbook.
+///
+/// A document that is itself an array, and a JSON Lines file, have none.
+pub fn list_arrays(path: &Path) -> Result<Vec<SheetInfo>, SourceError> {
+    let arrays = match parse(path)? {
+        Document::Single(Value::Object(members)) => members
+            .iter()
+            .filter(|(_, value)| value.is_array())
+            .map(|(name, _)| SheetInfo::new(name.clone(), SheetVisibility::Visible))
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok(arrays)
+}
+
+/// Reads the records of a JSON document or JSON Lines file as rows that
+/// `layout` selects.
+///
+/// The records are the items of `sheet`, a top-level array of the
+/// document; without one they are the first such array, the items of a
+/// document that is an array, the object a document consists of, or the
+/// lines of a JSON Lines file. Nested objects are flattened into dotted
+/// column names such as `address.city`, with a suffix such as `_2` for a
+/// name that clashes, and arrays inside a record are kept as JSON text.
+///
+/// The first row always holds the column names, in the order they first
+/// appear, so [`SourceRows::header_in_source`] is set. Lines of a JSON
+/// Lines file that are not valid JSON are reported and skipped.
+pub fn read_json(
+    path: &Path,
+    sheet: Option<&str>,
+    layout: &SourceLayout,
+) -> Result<SourceRows, SourceError> {
+    let (records, malformed) = match parse(path)? {
+        Document::Lines(records, malformed) => (records, malformed),
+        Document::Single(Value::Array(items)) => (items, Vec::new()),
+        Document::Single(Value::Object(mut members)) => {
+            let name = match sheet {
+                Some(name) => Some(name.to_string()),
+                None => members
+                    .iter()
+                    .find(|(_, value)| value.is_array())
+                    .map(|(name, _)| name.clone()),
+            };
+            match name {
+                Some(name) => match members.remove(&name) {
+                    Some(Value::Array(items)) => (items, Vec::new()),
+                    _ => {
+                        return Err(SourceError::SheetNotFound {
+                            path: path.to_path_buf(),
+                            sheet: name,
+                        });
+                    }
+                },
+                None => (vec![Value::Object(members)], Vec::new()),
+            }
+        }
+        Document::Single(_) => {
+            return Err(SourceError::Corrupt {
+                path: path.to_path_buf(),
+                message: "the document holds no records".to_string(),
+            });
+        }
+    };
+    debug!(
+        path = %path.display(),
+        records = records.len(),
+        "Reading JSON"
+    );
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    let mut flattened = Vec::with_capacity(records.len());
+    for record in records {
+        let mut cells = Vec::new();
+        match record {
+            Value::Object(members) => flatten("", members, &mut cells),
+            other => cells.push((VALUE_COLUMN.to_string(), cell(other))),
+        }
+        let row: Vec<(usize, CellValue)> = cells
+            .into_iter()
+            .map(|(name, value)| {
+                let position = *positions.entry(name.clone()).or_insert_with(|| {
+                    columns.push(name);
+                    columns.len() - 1
+                });
+                (position, value)
+            })
+            .collect();
+        flattened.push(row);
+    }
+
+    let header: Row = columns.into_iter().map(CellValue::Text).collect();
+    let width = header.len();
+    let rows = iter::once(header)
+        .chain(flattened.into_iter().map(|cells| {
+            let mut row = vec![CellValue::Empty; width];
+            for (position, value) in cells {
+                row[position] = value;
+            }
+            row
+        }))
+        .collect();
+
+    // Row numbers in the layout count the header row, as in a sheet.
+    let layout = SourceLayout {
+        has_headers: true,
+        ..layout.clone()
+    };
+    Ok(SourceRows {
+        rows: layout.apply(rows, (0, 0)),
+        malformed,
+        header_in_source: true,
+        ..SourceRows::default()
+    })
+}
+
+/// Returns `true` if `header`, the start of a file, opens a JSON object or
+/// array that is valid as far as it goes.
+///
+/// # Examples
+///
+/// ```
+/// use gpui_demo::sources::json::looks_like_json;
+///
+/// assert!(looks_like_json(br#"[{"Employee": "Ada", "Hours": 7.5}, {"Empl"#));
+/// assert!(looks_like_json(b"{\"Employee\": \"Ada\"}\n{\"Employee\": \"Grace\"}\n"));
+/// assert!(!looks_like_json(b"[Employee],Hours\nAda,7.5\n"));
+/// ```
+pub fn looks_like_json(header: &[u8]) -> bool {
+    let decoded = decode(header, TextEncoding::Auto);
+    let text = decoded.text.trim_start();
+    if !text.starts_with([
+        '{', '[',
+    ]) {
+        return false;
+    }
+    match Deserializer::from_str(text).into_iter::<Value>().next() {
+        Some(Ok(_)) => true,
+        Some(Err(error)) => error.classify() == Category::Eof,
+        None => false,
+    }
+}
+
+/// Reads and parses the JSON source at `path`.
+///
+/// The source is JSON Lines when it is not one JSON value but its first
+/// non-blank line is; each non-blank line is then parsed on its own.
+/// Otherwise it is a document, and any syntax error in it is reported.
+fn parse(path: &Path) -> Result<Document, SourceError> {
+    let data = read_source_bytes(path)?;
+    let decoded = decode(&data, TextEncoding::Auto);
+    if let Some(error) = decoded.errors.first() {
+        return Err(SourceError::Corrupt {
+            path: path.to_path_buf(),
+            message: format!("{error} is not valid {}", decoded.encoding),
+        });
+    }
+    let text = decoded.text.as_str();
+    if text.trim().is_empty() {
+        return Ok(Document::Single(Value::Array(Vec::new())));
+    }
+
+    let error = match serde_json::from_str(text) {
+        Ok(value) => return Ok(Document::Single(value)),
+        Err(error) => error,
+    };
+    let first_line = text
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or_default();
+    if serde_json::from_str::<Value>(first_line).is_err() {
+        return Err(SourceError::Corrupt {
+            path: path.to_path_buf(),
+            message: error.to_string(),
+        });
+    }
+
+    let mut records = Vec::new();
+    let mut malformed = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(value) => records.push(value),
+            Err(error) => malformed.push(MalformedRow {
+                line: index as u64 + 1,
+                message: format!(
+                    "is not valid JSON at column {}; the line was skipped",
+                    error.column()
+                ),
+            }),
+        }
+    }
+    Ok(Document::Lines(records, malformed))
+}
+
+/// Appends the members of `object` to `cells`, naming the members of
+/// nested objects `parent.child`.
+///
+/// A name already taken in `cells`, as when a member called `a.b` sits
+/// beside an object `a` with a member `b`, gets the first free suffix of
+/// `_2`, `_3` and so on, so neither value is lost.
+fn flatten(
+    prefix: &str,
+    object: Map<String, Value>,
+    cells: &mut Vec<(String, CellValue)>,
+) {
+    for (key, value) in object {
+        let name = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Object(members) => flatten(&name, members, cells),
+            other => {
+                let mut unique = name.clone();
+                let mut suffix = 2;
+                while cells.iter().any(|(taken, _)| *taken == unique) {
+                    unique = format!("{name}_{suffix}");
+                    suffix += 1;
+                }
+                cells.push((unique, cell(other)));
+            }
+        }
+    }
+}
+
+/// Converts a JSON value other than an object into a cell.
+fn cell(value: Value) -> CellValue {
+    match value {
+        Value::Null => CellValue::Empty,
+        Value::Bool(value) => CellValue::Bool(value),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => CellValue::Int(value),
+            None => number.as_f64().map_or(CellValue::Empty, CellValue::Float),
+        },
+        Value::String(text) => CellValue::Text(text),
+        other => CellValue::Text(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::{fs, path::PathBuf};
+    use tempfile::TempDir;
+
+    fn write_json(
+        dir: &TempDir,
+        name: &str,
+        contents: &str,
+    ) -> PathBuf {
+        let path = dir.path().join(name);
+        fs::write(&path, contents).expect("write json");
+        path
+    }
+
+    fn text(value: &str) -> CellValue {
+        CellValue::Text(value.to_string())
+    }
+
+    #[test]
+    fn test_read_json_flattens_records() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = write_json(
+            &dir,
+            "shifts.json",
+            r#"[
+                {"employee": {"name": "Ada", "site": {"city": "Leeds"}}, "hours": 7.5,
+                 "tags": ["early"]},
+                {"employee": {"name": "Grace"}, "hours": 8, "overtime": true, "note": null}
+            ]"#,
+        );
+
+        let read = read_json(&path, None, &SourceLayout::default()).expect("json should parse");
+
+        assert!(read.header_in_source);
+        assert_eq!(
+            read.rows,
+            vec![
+                vec![
+                    text("employee.name"),
+                    text("employee.site.city"),
+                    text("hours"),
+                    text("tags"),
+                    text("overtime"),
+                    text("note"),
+                ],
+                vec![
+                    text("Ada"),
+                    text("Leeds"),
+                    CellValue::Float(7.5),
+                    text(r#"["early"]"#),
+                    CellValue::Empty,
+                    CellValue::Empty,
+                ],
+                vec![
+                    text("Grace"),
+                    CellValue::Empty,
+                    CellValue::Int(8),
+                    CellValue::Empty,
+                    CellValue::Bool(true),
+                    CellValue::Empty,
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_level_arrays_are_sheets() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = write_json(
+            &dir,
+            "schedule.json",
+            r#"{"exported": "2024-01-08", "shifts": [{"id": 1}, {"id": 2}], "staff": [{"name": "Ada"}]}"#,
+        );
+
+        let sheets = list_arrays(&path).expect("json should parse");
+        let staff = read_json(&path, Some("staff"), &SourceLayout::default()).expect("staff");
+        let first = read_json(&path, None, &SourceLayout::default()).expect("first array");
+        let missing = read_json(&path, Some("exported"), &SourceLayout::default());
+
+        assert_eq!(
+            sheets,
+            vec![
+                SheetInfo::new("shifts", SheetVisibility::Visible),
+                SheetInfo::new("staff", SheetVisibility::Visible),
+            ]
+        );
+        assert_eq!(
+            staff.rows,
+            vec![
+                vec![text("name")],
+                vec![text("Ada")]
+            ]
+        );
+        assert_eq!(first.rows.len(), 3);
+        assert!(
+            matches!(missing, Err(SourceError::SheetNotFound { ref sheet, .. }) if sheet == "exported"),
+            "got: {missing:?}"
+        );
+    }
+
+    #[test]
+    fn test_read_json_lines_reports_bad_lines() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = write_json(
+            &dir,
+            "shifts.ndjson",
+            "{\"name\": \"Ada\", \"hours\": 7.5}\n\n{\"name\": \"Grace\", hours: 8}\n\"Linus\"\n",
+        );
+
+        let read = read_json(&path, None, &SourceLayout::default()).expect("lines should parse");
+
+        assert_eq!(
+            read.rows,
+            vec![
+                vec![
+                    text("name"),
+                    text("hours"),
+                    text(VALUE_COLUMN),
+                ],
+                vec![
+                    text("Ada"),
+                    CellValue::Float(7.5),
+                    CellValue::Empty,
+                ],
+                vec![
+                    CellValue::Empty,
+                    CellValue::Empty,
+                    text("Linus"),
+                ],
+            ]
+        );
+        assert_eq!(
+            read.malformed,
+            vec![
+                MalformedRow {
+                    line: 3,
+                    message: "is not valid JSON at column 19; the line was skipped".to_string(),
+                }
+            ]
+        );
+        assert!(list_arrays(&path).expect("lines").is_empty());
+    }
+
+    #[test]
+    fn test_document_with_bad_first_line_is_not_json_lines() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = write_json(
+            &dir,
+            "shifts.json",
+            "[{\"name\": \"Ada\",, \"hours\": 7.5},\n {\"name\": \"Grace\"}]\n",
+        );
+
+        let result = read_json(&path, None, &SourceLayout::default());
+
+        assert!(
+            matches!(
+                result,
+                Err(SourceError::Corrupt { ref message, .. }) if message.contains("line 1")
+            ),
+            "got: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_flatten_keeps_clashing_names() {
+        let dir = TempDir::new().expect("temp dir");
+        let path = write_json(
+            &dir,
+            "shifts.json",
+            r#"[{"site": {"city": "Leeds"}, "site.city": "York"}]"#,
+        );
+
+        let read = read_json(&path, None, &SourceLayout::default()).expect("json should parse");
+
+        assert_eq!(
+            read.rows,
+            vec![
+                vec![
+                    text("site.city"),
+                    text("site.city_2"),
+                ],
+                vec![
+                    text("Leeds"),
+                    text("York"),
+                ],
+            ]
+        );
+    }
+}
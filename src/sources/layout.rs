@@ -0,0 +1,478 @@
+// sources/layout.rs
+
+use std::{fmt, str::FromStr};
+
+use super::{CellValue, Row, SourceError};
+use crate::models::FileFormModel;
+
+/// A rectangular block of cells in A1 notation, such as `B4:K200`.
+///
+/// Positions are zero-based. A range given as a single cell (`B4`) runs to
+/// the last row and column of the sheet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CellRange {
+    pub first_row: usize,
+    pub first_column: usize,
+    pub last_row: Option<usize>,
+    pub last_column: Option<usize>,
+}
+
+impl FromStr for CellRange {
+    type Err = SourceError;
+
+    /// Parses `B4:K200` or `B4`, ignoring case and surrounding spaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpui_demo::sources::CellRange;
+    ///
+    /// let range: CellRange = "b4:K200".parse().expect("valid range");
+    /// assert_eq!((range.first_row, range.first_column), (3, 1));
+    /// assert_eq!((range.last_row, range.last_column), (Some(199), Some(10)));
+    /// assert!("K200:B4".parse::<CellRange>().is_err());
+    /// ```
+    fn from_str(text: &str) -> Result<Self, SourceError> {
+        let invalid = || SourceError::InvalidRange(text.to_string());
+        let (start, end) = match text.trim().split_once(':') {
+            Some((start, end)) => (start, Some(end)),
+            None => (text.trim(), None),
+        };
+
+        let (first_row, first_column) = parse_cell(start).ok_or_else(invalid)?;
+        let (last_row, last_column) = match end {
+            Some(end) => {
+                let (row, column) = parse_cell(end).ok_or_else(invalid)?;
+                if row < first_row || column < first_column {
+                    return Err(invalid());
+                }
+                (Some(row), Some(column))
+            }
+            None => (None, None),
+        };
+
+        Ok(Self {
+            first_row,
+            first_column,
+            last_row,
+            last_column,
+        })
+    }
+}
+
+impl fmt::Display for CellRange {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            column_letters(self.first_column),
+            self.first_row + 1
+        )?;
+        if let (Some(row), Some(column)) = (self.last_row, self.last_column) {
+            write!(f, ":{}{}", column_letters(column), row + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a single A1 cell reference into a zero-based (row, column).
+fn parse_cell(text: &str) -> Option<(usize, usize)> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_alphabetic())?;
+    let (letters, digits) = text.split_at(split);
+    if letters.is_empty() || letters.len() > 3 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let column = letters.chars().fold(0, |acc, c| {
+        acc * 26 + (c.to_ascii_uppercase() as usize - 'A' as usize + 1)
+    });
+    let row: usize = digits.parse().ok()?;
+    (row > 0).then(|| (row - 1, column - 1))
+}
+
+/// Returns the A1 letters of a zero-based column index.
+fn column_letters(mut column: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push((b'A' + (column % 26) as u8) as char);
+        if column < 26 {
+            break;
+        }
+        column = column / 26 - 1;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Where the table sits inside a sheet or CSV file.
+///
+/// Row numbers are one-based, as shown by spreadsheet programs, and count
+/// from the top of the sheet (or the first record of a CSV file) whatever
+/// the range. The default layout reads every row as it is.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceLayout {
+    /// Whether a header row supplies the column names.
+    pub has_headers: bool,
+    /// Row holding the column names; `None` uses the first row of the
+    /// range, or of the sheet.
+    pub header_row: Option<usize>,
+    /// First data row; `None` starts right below the header.
+    pub data_start_row: Option<usize>,
+    /// Number of rows dropped from the end, such as a totals row. Blank
+    /// rows at the very end are dropped first and not counted.
+    pub skip_trailing_rows: usize,
+    pub range: Option<CellRange>,
+    /// Number of data rows kept at most, such as the rows of a preview;
+    /// `None` keeps them all.
+    pub row_limit: Option<usize>,
+}
+
+impl SourceLayout {
+    /// Reads the layout fields of `model`, failing on a malformed range.
+    pub fn from_model(model: &FileFormModel) -> Result<Self, SourceError> {
+        let range = model
+            .cell_range
+            .as_deref()
+            .map(str::trim)
+            .filter(|range| !range.is_empty())
+            .map(str::parse)
+            .transpose()?;
+
+        Ok(Self {
+            has_headers: model.has_headers,
+            header_row: model.header_row,
+            data_start_row: model.data_start_row,
+            skip_trailing_rows: model.skip_trailing_rows,
+            range,
+            row_limit: None,
+        })
+    }
+
+    /// Cuts the table out of `rows`.
+    ///
+    /// `origin` is the zero-based (row, column) of `rows[0][0]` in the
+    /// sheet, since workbook readers start at the first used cell. Returns
+    /// the header row (when [`SourceLayout::has_headers`] is set) followed
+    /// by the data rows, ready for schema inference.
+    pub fn apply(
+        &self,
+        rows: Vec<Row>,
+        origin: (usize, usize),
+    ) -> Vec<Row> {
+        if self.header_row.is_none()
+            && self.data_start_row.is_none()
+            && self.skip_trailing_rows == 0
+            && self.range.is_none()
+            && self.row_limit.is_none()
+        {
+            return rows;
+        }
+
+        self.select(&rows, origin)
+            .into_iter()
+            .map(|index| {
+                index
+                    .map(|index| self.crop(&rows[index], origin.1))
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Returns, for each row [`SourceLayout::apply`] cuts out of `rows`,
+    /// the index of the source row it comes from; `None` for a row outside
+    /// `rows`, which is cut out empty.
+    pub fn select(
+        &self,
+        rows: &[Row],
+        origin: (usize, usize),
+    ) -> Vec<Option<usize>> {
+        let (origin_row, origin_column) = origin;
+        let index_of = |row: usize| {
+            row.checked_sub(origin_row)
+                .filter(|&index| index < rows.len())
+        };
+        let is_blank = |index: &Option<usize>| {
+            index.is_none_or(|index| {
+                self.crop(&rows[index], origin_column)
+                    .iter()
+                    .all(CellValue::is_blank)
+            })
+        };
+
+        let (header, data_start) = self.start_rows(origin_row);
+        let end = origin_row + rows.len();
+        let last = self
+            .range
+            .and_then(|range| range.last_row)
+            .map_or(end, |last| end.min(last.saturating_add(1)));
+
+        let mut data: Vec<Option<usize>> = (data_start..last).map(index_of).collect();
+        if self.skip_trailing_rows > 0 {
+            while data.last().is_some_and(is_blank) {
+                data.pop();
+            }
+            data.truncate(data.len().saturating_sub(self.skip_trailing_rows));
+        }
+        if let Some(limit) = self.row_limit {
+            data.truncate(limit);
+        }
+
+        header.map(index_of).into_iter().chain(data).collect()
+    }
+
+    /// Number of rows, counting from the one at `origin_row`, that hold the
+    /// header and the first [`SourceLayout::row_limit`] data rows along
+    /// with the rows skipped at the end, so a reader can stop there.
+    /// Returns `None` when every row is needed.
+    pub fn rows_to_read(
+        &self,
+        origin_row: usize,
+    ) -> Option<usize> {
+        let limit = self.row_limit?;
+        let (_, data_start) = self.start_rows(origin_row);
+        // Row numbers come from the form unchecked, so the sum saturates.
+        let end = data_start
+            .saturating_add(limit)
+            .saturating_add(self.skip_trailing_rows);
+        Some(end.saturating_sub(origin_row))
+    }
+
+    /// Returns the zero-based sheet rows of the header, if any, and of the
+    /// first data row, for rows read from `origin_row` on.
+    fn start_rows(
+        &self,
+        origin_row: usize,
+    ) -> (Option<usize>, usize) {
+        let first = self.range.map_or(origin_row, |range| range.first_row);
+        let header = self
+            .has_headers
+            .then(|| self.header_row.map_or(first, |row| row.saturating_sub(1)));
+        // The header row is never read as data, whatever the data start.
+        let data_start = self
+            .data_start_row
+            .map(|row| row.saturating_sub(1))
+            .or(header.map(|row| row.saturating_add(1)))
+            .unwrap_or(first)
+            .max(first)
+            .max(header.map_or(0, |row| row.saturating_add(1)));
+        (header, data_start)
+    }
+
+    /// Keeps the cells of `row` that fall inside the range's columns.
+    fn crop(
+        &self,
+        row: &Row,
+        origin_column: usize,
+    ) -> Row {
+        let Some(range) = self.range else {
+            return row.clone();
+        };
+
+        let end = origin_column + row.len();
+        let last = range
+            .last_column
+            .map_or(end, |last| end.min(last.saturating_add(1)));
+        (range.first_column..last)
+            .map(|column| {
+                column
+                    .checked_sub(origin_column)
+                    .and_then(|index| row.get(index))
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn text_rows(rows: &[&[&str]]) -> Vec<Row> {
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| {
+                        if cell.is_empty() {
+                            CellValue::Empty
+                        } else {
+                            CellValue::Text(cell.to_string())
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn timesheet() -> Vec<Row> {
+        text_rows(&[
+            &["Weekly Timesheet"],
+            &[],
+            &[
+                "Employee", "Hours",
+            ],
+            &[
+                "Ada", "7.5",
+            ],
+            &[
+                "Grace", "8",
+            ],
+            &[
+                "Total", "15.5",
+            ],
+            &[],
+        ])
+    }
+
+    #[test]
+    fn test_parse_range_round_trips() {
+        for text in [
+            "B4:K200",
+            "A1",
+            "AA10:AZ12",
+        ] {
+            let range: CellRange = text.parse().expect("valid range");
+            assert_eq!(range.to_string(), text);
+        }
+        for text in [
+            "", "4B", "B0", "B4:", "B4:A9", "ABCD1",
+        ] {
+            assert!(
+                text.parse::<CellRange>().is_err(),
+                "expected '{text}' to be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_layout_keeps_rows() {
+        assert_eq!(
+            SourceLayout::default().apply(timesheet(), (0, 0)),
+            timesheet()
+        );
+    }
+
+    #[test]
+    fn test_header_row_and_trailing_rows() {
+        let layout = SourceLayout {
+            has_headers: true,
+            header_row: Some(3),
+            skip_trailing_rows: 1,
+            ..SourceLayout::default()
+        };
+
+        assert_eq!(
+            layout.apply(timesheet(), (0, 0)),
+            text_rows(&[
+                &[
+                    "Employee", "Hours",
+                ],
+                &[
+                    "Ada", "7.5",
+                ],
+                &[
+                    "Grace", "8",
+                ],
+            ])
+        );
+    }
+
+    #[test]
+    fn test_data_start_row_without_headers() {
+        let layout = SourceLayout {
+            data_start_row: Some(5),
+            ..SourceLayout::default()
+        };
+
+        assert_eq!(
+            layout.apply(timesheet(), (0, 0)),
+            text_rows(&[
+                &[
+                    "Grace", "8",
+                ],
+                &[
+                    "Total", "15.5",
+                ],
+                &[],
+            ])
+        );
+    }
+
+    #[test]
+    fn test_row_limit_keeps_first_data_rows() {
+        let layout = SourceLayout {
+            has_headers: true,
+            header_row: Some(3),
+            row_limit: Some(1),
+            ..SourceLayout::default()
+        };
+
+        assert_eq!(layout.rows_to_read(0), Some(4));
+        assert_eq!(layout.rows_to_read(2), Some(2));
+        assert_eq!(SourceLayout::default().rows_to_read(0), None);
+        let huge = SourceLayout {
+            row_limit: Some(usize::MAX),
+            skip_trailing_rows: 2,
+            ..layout.clone()
+        };
+        assert_eq!(huge.rows_to_read(0), Some(usize::MAX));
+        assert_eq!(
+            layout.apply(timesheet(), (0, 0)),
+            text_rows(&[
+                &[
+                    "Employee", "Hours",
+                ],
+                &[
+                    "Ada", "7.5",
+                ],
+            ])
+        );
+    }
+
+    #[test]
+    fn test_data_start_row_never_repeats_header() {
+        let layout = SourceLayout {
+            has_headers: true,
+            data_start_row: Some(1),
+            range: Some("A3:B5".parse().expect("valid range")),
+            ..SourceLayout::default()
+        };
+
+        assert_eq!(
+            layout.apply(timesheet(), (0, 0)),
+            text_rows(&[
+                &[
+                    "Employee", "Hours",
+                ],
+                &[
+                    "Ada", "7.5",
+                ],
+                &[
+                    "Grace", "8",
+                ],
+            ])
+        );
+    }
+
+    #[test]
+    fn test_range_is_relative_to_sheet_origin() {
+        let layout = SourceLayout {
+            has_headers: true,
+            range: Some("C5:C6".parse().expect("valid range")),
+            ..SourceLayout::default()
+        };
+
+        // The rows were read starting at B3.
+        assert_eq!(
+            layout.apply(timesheet(), (2, 1)),
+            text_rows(&[
+                &["Hours"],
+                &["7.5"],
+            ])
+        );
+    }
+}
@@ -0,0 +1,261 @@
+// sources
+
+pub mod archive;
+pub mod delimited;
+pub mod encoding;
+pub mod excel;
+pub mod fixed_width;
+pub mod json;
+pub mod layout;
+pub mod registry;
+
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use serde::Serialize;
+use thiserror::Error;
+
+pub use delimited::{CsvDialect, CsvOptions, LineEnding, detect_dialect, read_csv};
+pub use excel::{list_sheets, read_sheet};
+pub use layout::{CellRange, SourceLayout};
+pub use registry::{
+    ReadOptions, SourceFormat, SourceReader, SourceRegistry, install_registry, registry,
+};
+
+/// A row of cells as read from a source, before any schema is applied.
+pub type Row = Vec<CellValue>;
+
+/// A source row that could not be read cleanly, by its line in the file.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct MalformedRow {
+    /// One-based line the row starts on.
+    pub line: u64,
+    pub message: String,
+}
+
+impl fmt::Display for MalformedRow {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// The rows read from a source, with the rows that were malformed.
+///
+/// Malformed rows are reported rather than failing the whole read; those
+/// that could still be split into cells are kept in `rows`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SourceRows {
+    pub rows: Vec<Row>,
+    pub malformed: Vec<MalformedRow>,
+    /// How a CSV source was read; `None` for other formats.
+    pub dialect: Option<CsvDialect>,
+    /// Whether `rows` start with column names the source itself supplies,
+    /// such as JSON keys, whatever the form's header setting.
+    pub header_in_source: bool,
+    /// The lines of a fixed-width source as they are in the file, without
+    /// line endings. Only reads with a [row limit](SourceLayout::row_limit),
+    /// such as the preview's, keep them; empty otherwise.
+    pub raw_lines: Vec<String>,
+}
+
+impl From<Vec<Row>> for SourceRows {
+    fn from(rows: Vec<Row>) -> Self {
+        Self {
+            rows,
+            ..Self::default()
+        }
+    }
+}
+
+/// Errors raised while opening or reading a source file.
+///
+/// The messages are written for display in the form, so each variant names
+/// the offending file.
+#[derive(Debug, Error)]
+pub enum SourceError {
+    #[error("Source file '{}' does not exist.", .0.display())]
+    NotFound(PathBuf),
+
+    #[error("Cannot read '{}': {source}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("'{}' is not a supported source format.", .0.display())]
+    UnsupportedFormat(PathBuf),
+
+    #[error("'{}' is password protected.", .0.display())]
+    PasswordProtected(PathBuf),
+
+    #[error("'{}' is corrupt or unreadable: {message}", path.display())]
+    Corrupt { path: PathBuf, message: String },
+
+    #[error("'{}' has no sheet named '{sheet}'.", path.display())]
+    SheetNotFound { path: PathBuf, sheet: String },
+
+    #[error(
+        "'{}' is gzip-compressed, which only text sources such as CSV may be; decompress it first.",
+        .0.display()
+    )]
+    Compressed(PathBuf),
+
+    #[error(
+        "'{}' holds more than {} MiB once decompressed, too much to read.",
+        path.display(),
+        limit >> 20
+    )]
+    TooLarge { path: PathBuf, limit: u64 },
+
+    #[error("'{0}' is not a cell range such as B4:K200.")]
+    InvalidRange(String),
+}
+
+/// A single cell value read from a source file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum CellValue {
+    #[default]
+    Empty,
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Text(String),
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    Duration(TimeDelta),
+}
+
+impl CellValue {
+    /// Returns `true` for empty cells and for text that is only whitespace.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpui_demo::sources::CellValue;
+    ///
+    /// assert!(CellValue::Empty.is_blank());
+    /// assert!(CellValue::Text("  ".to_string()).is_blank());
+    /// assert!(!CellValue::Int(0).is_blank());
+    /// ```
+    pub fn is_blank(&self) -> bool {
+        match self {
+            Self::Empty => true,
+            Self::Text(text) => text.trim().is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for CellValue {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self {
+            Self::Empty => Ok(()),
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::Bool(value) => write!(f, "{value}"),
+            Self::Text(value) => write!(f, "{value}"),
+            Self::Date(value) => write!(f, "{}", value.format("%Y-%m-%d")),
+            Self::DateTime(value) => write!(f, "{}", value.format("%Y-%m-%d %H:%M:%S")),
+            Self::Duration(value) => {
+                let seconds = value.num_seconds();
+                let sign = if seconds < 0 { "-" } else { "" };
+                let seconds = seconds.abs();
+                write!(
+                    f,
+                    "{sign}{:02}:{:02}:{:02}",
+                    seconds / 3600,
+                    (seconds % 3600) / 60,
+                    seconds % 60
+                )
+            }
+        }
+    }
+}
+
+/// Visibility of a sheet as recorded in the workbook.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SheetVisibility {
+    #[default]
+    Visible,
+    Hidden,
+    /// Hidden and only revealable through VBA (Excel formats only).
+    VeryHidden,
+}
+
+/// A sheet name together with its visibility, in workbook order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SheetInfo {
+    pub name: String,
+    pub visibility: SheetVisibility,
+}
+
+impl SheetInfo {
+    pub fn new(
+        name: impl Into<String>,
+        visibility: SheetVisibility,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            visibility,
+        }
+    }
+
+    /// Returns `true` if the sheet is shown in the workbook's tab bar.
+    pub fn is_visible(&self) -> bool {
+        self.visibility == SheetVisibility::Visible
+    }
+
+    /// Returns the label shown in the sheet dropdown.
+    ///
+    /// Hidden sheets carry a suffix so they can be told apart from visible ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use gpui_demo::sources::{SheetInfo, SheetVisibility};
+    ///
+    /// assert_eq!(SheetInfo::new("Hours", SheetVisibility::Visible).label(), "Hours");
+    /// assert_eq!(SheetInfo::new("Old", SheetVisibility::Hidden).label(), "Old (hidden)");
+    /// ```
+    pub fn label(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for SheetInfo {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        match self.visibility {
+            SheetVisibility::Visible => write!(f, "{}", self.name),
+            SheetVisibility::Hidden => write!(f, "{} (hidden)", self.name),
+            SheetVisibility::VeryHidden => write!(f, "{} (very hidden)", self.name),
+        }
+    }
+}
+
+/// Converts an IO error into a [`SourceError`], keeping "file not found"
+/// distinct so the form can say so plainly.
+pub(crate) fn map_io_error(
+    path: &Path,
+    error: io::Error,
+) -> SourceError {
+    match error.kind() {
+        io::ErrorKind::NotFound => SourceError::NotFound(path.to_path_buf()),
+        _ => SourceError::Io {
+            path: path.to_path_buf(),
+            source: error,
+        },
+    }
+}
@@ -0,0 +1,593 @@
+// sources/registry.rs
+
+use std::{fmt, io::Read, iter, path::Path, sync::OnceLock};
+
+use super::{
+    CsvOptions, SheetInfo, SourceError, SourceLayout, SourceRows,
+    archive::{GZIP_EXTENSIONS, GZIP_LABEL, ZipReader, open_source, uncompressed_path},
+    delimited::CsvReader,
+    excel::WorkbookReader,
+    fixed_width::FixedWidthReader,
+    json::JsonReader,
+    map_io_error,
+};
+use crate::models::{FileFormModel, FixedWidthColumn};
+
+/// Bytes read from the start of a file to recognize its format.
+pub const HEADER_BYTES: usize = 8 * 1024;
+
+/// The readers shared by the window, the CLI and the pipeline; the
+/// built-in ones unless [`install_registry`] put others in place first.
+static REGISTRY: OnceLock<SourceRegistry> = OnceLock::new();
+
+/// Kind of source a [`SourceReader`] handles.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SourceFormat {
+    /// A spreadsheet with sheets, such as `.xlsx`, `.xls` or `.ods`.
+    Workbook,
+    /// A JSON document or JSON Lines file.
+    Json,
+    /// Delimited text.
+    Csv,
+    /// Text with columns at fixed positions on each line.
+    FixedWidth,
+    /// A zip archive of other sources.
+    Zip,
+    /// A format read by a reader added outside this crate.
+    Other {
+        /// Name of the format, such as `Parquet`.
+        label: &'static str,
+        /// Whether sources of the format may hold several sheets.
+        has_sheets: bool,
+    },
+}
+
+impl SourceFormat {
+    /// Name of the format in the file dialog's type filter.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Workbook => "Spreadsheet",
+            Self::Json => "JSON",
+            Self::Csv => "CSV",
+            Self::FixedWidth => "Fixed width",
+            Self::Zip => "Zip archive",
+            Self::Other { label, .. } => label,
+        }
+    }
+
+    /// Returns `true` if sources of this format are plain text, which a
+    /// fixed-width layout may cut into columns.
+    pub fn is_text(self) -> bool {
+        matches!(self, Self::Csv | Self::FixedWidth)
+    }
+
+    /// Returns `true` if sources of this format may hold several tables,
+    /// listed as sheets: the sheets of a workbook, the top-level arrays of
+    /// a JSON document or the files in a zip archive.
+    pub fn has_sheets(self) -> bool {
+        match self {
+            Self::Workbook | Self::Json | Self::Zip => true,
+            Self::Csv | Self::FixedWidth => false,
+            Self::Other { has_sheets, .. } => has_sheets,
+        }
+    }
+}
+
+impl fmt::Display for SourceFormat {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+    ) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// Settings from the form that a reader may need besides the file itself.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadOptions {
+    pub layout: SourceLayout,
+    pub csv: CsvOptions,
+    /// Columns of a fixed-width text source; empty unless the form defines
+    /// a layout.
+    pub fixed_width: Vec<FixedWidthColumn>,
+}
+
+impl ReadOptions {
+    /// Builds the read options from the form's layout and dialect settings.
+    ///
+    /// Fails only when the form's cell range is not valid A1 notation.
+    pub fn from_model(model: &FileFormModel) -> Result<Self, SourceError> {
+        Ok(Self {
+            layout: SourceLayout::from_model(model)?,
+            csv: CsvOptions::from(model),
+            fixed_width: model.fixed_width_columns.clone(),
+        })
+    }
+}
+
+/// Reads one source format into rows of cells.
+///
+/// Readers are asked in registration order whether a file's first bytes
+/// look like their format, so a reader recognizing magic bytes should be
+/// registered before one that only sniffs text.
+pub trait SourceReader: Send + Sync {
+    /// The format this reader handles; a reader for a format the crate
+    /// does not know returns [`SourceFormat::Other`].
+    fn format(&self) -> SourceFormat;
+
+    /// Lower-case extensions of the format, offered by the file dialog and
+    /// used when the contents of a file do not settle its format.
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Returns `true` if `header`, the first [`HEADER_BYTES`] of a file (or
+    /// all of a shorter one), look like this format.
+    fn sniff(
+        &self,
+        header: &[u8],
+    ) -> bool;
+
+    /// Returns `true` if the reader opens files through [`open_source`],
+    /// which decompresses them, so a gzip-compressed file such as
+    /// `hours.csv.gz` can be matched by the extension inside. Readers that
+    /// open the file themselves keep the default.
+    fn reads_gzip(&self) -> bool {
+        false
+    }
+
+    /// Returns `true` if `options` ask for this format for any text file,
+    /// as a fixed-width layout does for text that would otherwise pass for
+    /// CSV. Files whose contents another reader recognizes as something
+    /// other than text are left to that reader.
+    fn claims(
+        &self,
+        _options: &ReadOptions,
+    ) -> bool {
+        false
+    }
+
+    /// Lists the sheets of the source at `path` in source order; formats
+    /// without sheets have none.
+    fn list_sheets(
+        &self,
+        _path: &Path,
+    ) -> Result<Vec<SheetInfo>, SourceError> {
+        Ok(Vec::new())
+    }
+
+    /// Reads the rows of `sheet` that the layout in `options` selects.
+    /// Sheetless formats ignore `sheet`; workbooks read their first sheet
+    /// when it is `None`.
+    fn read(
+        &self,
+        path: &Path,
+        sheet: Option<&str>,
+        options: &ReadOptions,
+    ) -> Result<SourceRows, SourceError>;
+}
+
+/// The source readers the import knows, and how a file is matched to one.
+///
+/// A file is matched by its contents first, so a workbook saved as `.csv`
+/// or a CSV export saved as `.xls` is still read correctly; its extension
+/// only decides when no reader recognizes the contents. A reader that
+/// [claims](SourceReader::claims) the read options goes before both, but
+/// only for text (see [`SourceFormat::is_text`]).
+pub struct SourceRegistry {
+    readers: Vec<Box<dyn SourceReader>>,
+}
+
+impl Default for SourceRegistry {
+    /// The built-in readers: workbooks, zip archives, JSON, then CSV, which
+    /// would also take JSON for delimited text, and fixed-width text, which
+    /// is only picked by its layout or extension.
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry.register(WorkbookReader);
+        registry.register(ZipReader);
+        registry.register(JsonReader);
+        registry.register(CsvReader);
+        registry.register(FixedWidthReader);
+        registry
+    }
+}
+
+impl SourceRegistry {
+    /// A registry without any reader.
+    pub fn empty() -> Self {
+        Self {
+            readers: Vec::new(),
+        }
+    }
+
+    /// Adds `reader` after the readers already registered.
+    pub fn register(
+        &mut self,
+        reader: impl SourceReader + 'static,
+    ) {
+        self.readers.push(Box::new(reader));
+    }
+
+    /// The registered readers, in the order they are asked about a file.
+    pub fn readers(&self) -> impl Iterator<Item = &dyn SourceReader> {
+        self.readers.iter().map(|reader| reader.as_ref())
+    }
+
+    /// Returns the reader whose extensions include that of `path`, or of
+    /// the file it holds when it is gzip-compressed and the reader
+    /// [reads gzip](SourceReader::reads_gzip).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    /// use gpui_demo::sources::{SourceFormat, registry};
+    ///
+    /// let format = |name: &str| registry().by_extension(Path::new(name)).map(|r| r.format());
+    /// assert_eq!(format("hours.XLSX"), Some(SourceFormat::Workbook));
+    /// assert_eq!(format("hours.ods"), Some(SourceFormat::Workbook));
+    /// assert_eq!(format("hours.csv"), Some(SourceFormat::Csv));
+    /// assert_eq!(format("hours.csv.gz"), Some(SourceFormat::Csv));
+    /// assert_eq!(format("hours.xlsx.gz"), None);
+    /// assert_eq!(format("timesheets.zip"), Some(SourceFormat::Zip));
+    /// assert_eq!(format("hours.pdf"), None);
+    /// ```
+    pub fn by_extension(
+        &self,
+        path: &Path,
+    ) -> Option<&dyn SourceReader> {
+        let compressed = uncompressed_path(path) != path;
+        self.readers().find(|reader| {
+            (reader.reads_gzip() || !compressed) && has_extension(path, reader.extensions())
+        })
+    }
+
+    /// Returns the first reader that recognizes `header`, the start of a
+    /// file.
+    pub fn by_contents(
+        &self,
+        header: &[u8],
+    ) -> Option<&dyn SourceReader> {
+        if header.is_empty() {
+            return None;
+        }
+        self.readers().find(|reader| reader.sniff(header))
+    }
+
+    /// Returns the first reader that [claims](SourceReader::claims)
+    /// `options`.
+    pub fn by_options(
+        &self,
+        options: &ReadOptions,
+    ) -> Option<&dyn SourceReader> {
+        self.readers().find(|reader| reader.claims(options))
+    }
+
+    /// Picks the reader for the file at `path` from its contents, falling
+    /// back to its extension.
+    ///
+    /// Fails when the file cannot be read or no reader fits it.
+    pub fn detect(
+        &self,
+        path: &Path,
+    ) -> Result<&dyn SourceReader, SourceError> {
+        self.detect_with(path, &ReadOptions::default())
+    }
+
+    /// Like [`SourceRegistry::detect`], but lets a reader that claims
+    /// `options` read the file when it holds text.
+    pub fn detect_with(
+        &self,
+        path: &Path,
+        options: &ReadOptions,
+    ) -> Result<&dyn SourceReader, SourceError> {
+        let header = read_header(path)?;
+        self.pick(path, &header, options)
+            .ok_or_else(|| SourceError::UnsupportedFormat(path.to_path_buf()))
+    }
+
+    /// Like [`SourceRegistry::detect`], but goes by the extension alone
+    /// when the file cannot be read, such as a path still being typed.
+    pub fn guess(
+        &self,
+        path: &Path,
+    ) -> Option<&dyn SourceReader> {
+        self.guess_with(path, &ReadOptions::default())
+    }
+
+    /// Like [`SourceRegistry::guess`], but lets a reader that claims
+    /// `options` have the file when it holds text.
+    pub fn guess_with(
+        &self,
+        path: &Path,
+        options: &ReadOptions,
+    ) -> Option<&dyn SourceReader> {
+        let header = read_header(path).unwrap_or_default();
+        self.pick(path, &header, options)
+    }
+
+    /// Picks the reader for the file at `path` starting with `header`: one
+    /// that claims `options` if the file holds text, going by its contents
+    /// or else its extension, then one that recognizes the contents, then
+    /// one that handles the extension.
+    fn pick(
+        &self,
+        path: &Path,
+        header: &[u8],
+        options: &ReadOptions,
+    ) -> Option<&dyn SourceReader> {
+        let known = if header.is_empty() {
+            self.by_extension(path)
+        } else {
+            self.by_contents(header)
+        };
+        let is_text = known.is_none_or(|reader| reader.format().is_text());
+        self.by_options(options)
+            .filter(|_| is_text)
+            .or_else(|| self.by_contents(header))
+            .or_else(|| self.by_extension(path))
+    }
+
+    /// Returns `true` if `path` has an extension one of the readers handles.
+    pub fn handles_extension(
+        &self,
+        path: &Path,
+    ) -> bool {
+        self.by_extension(path).is_some()
+    }
+
+    /// Type filters for the source file dialog, one per reader and one for
+    /// gzip-compressed text sources, for use with [`owned_filters`].
+    ///
+    /// [`owned_filters`]: crate::components::owned_filters
+    pub fn file_filters(&self) -> Vec<(&'static str, &'static [&'static str])> {
+        self.readers()
+            .map(|reader| (reader.format().label(), reader.extensions()))
+            .chain(iter::once((GZIP_LABEL, GZIP_EXTENSIONS)))
+            .collect()
+    }
+}
+
+/// Returns the registry of source readers: the one given to
+/// [`install_registry`], or else the built-in readers.
+pub fn registry() -> &'static SourceRegistry {
+    REGISTRY.get_or_init(SourceRegistry::default)
+}
+
+/// Makes `registry` the one [`registry`] returns, so an application can add
+/// readers for formats of its own to the built-in ones.
+///
+/// Must be called before the registry is first used; fails, handing
+/// `registry` back, once it has been.
+///
+/// # Examples
+///
+/// ```
+/// use gpui_demo::sources::{SourceRegistry, install_registry, registry};
+///
+/// let installed = install_registry(SourceRegistry::default());
+/// assert!(installed.is_ok());
+/// assert!(install_registry(SourceRegistry::empty()).is_err());
+/// assert_eq!(registry().readers().count(), 5);
+/// ```
+pub fn install_registry(registry: SourceRegistry) -> Result<(), SourceRegistry> {
+    REGISTRY.set(registry)
+}
+
+/// Returns `true` if the extension of `path`, ignoring a `.gz` extension
+/// after it, is one of `extensions`, which must be lower-case.
+pub(crate) fn has_extension(
+    path: &Path,
+    extensions: &[&str],
+) -> bool {
+    uncompressed_path(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .is_some_and(|ext| extensions.contains(&ext.as_str()))
+}
+
+/// Reads up to [`HEADER_BYTES`] from the start of the file at `path`, or
+/// of the file it holds when it is gzip-compressed.
+pub(crate) fn read_header(path: &Path) -> Result<Vec<u8>, SourceError> {
+    let mut header = Vec::new();
+    open_source(path)?
+        .take(HEADER_BYTES as u64)
+        .read_to_end(&mut header)
+        .map_err(|e| map_io_error(path, e))?;
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::{
+        archive::ZIP_EXTENSIONS, delimited::CSV_EXTENSIONS, excel::WORKBOOK_EXTENSIONS,
+        fixed_width::FIXED_WIDTH_EXTENSIONS, json::JSON_EXTENSIONS,
+    };
+    use pretty_assertions::assert_eq;
+    use std::{fs, path::PathBuf};
+    use tempfile::TempDir;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name)
+    }
+
+    fn detected(path: &Path) -> Result<SourceFormat, SourceError> {
+        registry().detect(path).map(|reader| reader.format())
+    }
+
+    #[test]
+    fn test_detect_ignores_misleading_extensions() {
+        let dir = TempDir::new().expect("temp dir");
+        let workbook_as_csv = dir.path().join("hours.csv");
+        fs::copy(fixture("timesheet.xlsx"), &workbook_as_csv).expect("copy workbook");
+        let csv_as_workbook = dir.path().join("export.xls");
+        fs::write(&csv_as_workbook, "Employee\tHours\nAda\t7.5\n").expect("write csv");
+        let not_a_workbook = dir.path().join("notes.xlsx");
+        fs::write(&not_a_workbook, "not a workbook").expect("write text");
+        let json_as_csv = dir.path().join("shifts.csv");
+        fs::write(&json_as_csv, "{\"id\": 1, \"hours\": 7.5}\n").expect("write json");
+
+        assert_eq!(
+            detected(&workbook_as_csv).ok(),
+            Some(SourceFormat::Workbook)
+        );
+        assert_eq!(
+            detected(&fixture("timesheet.ods")).ok(),
+            Some(SourceFormat::Workbook)
+        );
+        assert_eq!(detected(&csv_as_workbook).ok(), Some(SourceFormat::Csv));
+        assert_eq!(detected(&json_as_csv).ok(), Some(SourceFormat::Json));
+        // Text without a delimiter is left to the extension.
+        assert_eq!(detected(&not_a_workbook).ok(), Some(SourceFormat::Workbook));
+
+        let rows = registry()
+            .detect(&csv_as_workbook)
+            .and_then(|reader| reader.read(&csv_as_workbook, None, &ReadOptions::default()))
+            .expect("csv should read");
+        assert_eq!(rows.rows.len(), 2);
+        let sheets = registry()
+            .detect(&workbook_as_csv)
+            .and_then(|reader| reader.list_sheets(&workbook_as_csv))
+            .expect("workbook should open");
+        assert_eq!(sheets[0].name, "Hours");
+    }
+
+    #[test]
+    fn test_detect_unknown_and_missing_files() {
+        let dir = TempDir::new().expect("temp dir");
+        let binary = dir.path().join("scan.pdf");
+        fs::write(&binary, b"%PDF-1.7\n\x00\x01\x02\xFF").expect("write pdf");
+
+        assert!(matches!(
+            detected(&binary),
+            Err(SourceError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            detected(&dir.path().join("missing.csv")),
+            Err(SourceError::NotFound(_))
+        ));
+        assert_eq!(
+            registry()
+                .guess(&dir.path().join("missing.csv"))
+                .map(|reader| reader.format()),
+            Some(SourceFormat::Csv)
+        );
+    }
+
+    #[test]
+    fn test_layout_claims_text_files() {
+        let dir = TempDir::new().expect("temp dir");
+        let punches = dir.path().join("punches.csv");
+        fs::write(&punches, "00417Smith, A  07.50\n").expect("write punches");
+        let options = ReadOptions {
+            fixed_width: vec![FixedWidthColumn::new("employee", 1, 5)],
+            ..ReadOptions::default()
+        };
+
+        assert_eq!(detected(&punches).ok(), Some(SourceFormat::Csv));
+        assert_eq!(
+            registry()
+                .detect_with(&punches, &options)
+                .map(|reader| reader.format())
+                .ok(),
+            Some(SourceFormat::FixedWidth)
+        );
+        assert_eq!(
+            registry()
+                .guess(&dir.path().join("punches.txt"))
+                .map(|reader| reader.format()),
+            Some(SourceFormat::FixedWidth)
+        );
+
+        let with_layout = |path: &Path| {
+            registry()
+                .guess_with(path, &options)
+                .map(|reader| reader.format())
+        };
+        let shifts = dir.path().join("shifts.txt");
+        fs::write(&shifts, "[{\"id\": 1}]").expect("write json");
+        assert_eq!(
+            with_layout(&fixture("timesheet.xlsx")),
+            Some(SourceFormat::Workbook)
+        );
+        assert_eq!(with_layout(&shifts), Some(SourceFormat::Json));
+        assert_eq!(
+            with_layout(&dir.path().join("missing.xlsx")),
+            Some(SourceFormat::Workbook)
+        );
+        assert_eq!(
+            with_layout(&dir.path().join("missing.dat")),
+            Some(SourceFormat::FixedWidth)
+        );
+    }
+
+    #[test]
+    fn test_file_filters_follow_readers() {
+        assert_eq!(
+            registry().file_filters(),
+            vec![
+                ("Spreadsheet", WORKBOOK_EXTENSIONS),
+                ("Zip archive", ZIP_EXTENSIONS),
+                ("JSON", JSON_EXTENSIONS),
+                ("CSV", CSV_EXTENSIONS),
+                ("Fixed width", FIXED_WIDTH_EXTENSIONS),
+                ("Gzip-compressed", GZIP_EXTENSIONS),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_readers_of_other_formats_are_matched() {
+        struct ParquetReader;
+
+        impl SourceReader for ParquetReader {
+            fn format(&self) -> SourceFormat {
+                SourceFormat::Other {
+                    label: "Parquet",
+                    has_sheets: false,
+                }
+            }
+
+            fn extensions(&self) -> &'static [&'static str] {
+                &["parquet"]
+            }
+
+            fn sniff(
+                &self,
+                header: &[u8],
+            ) -> bool {
+                header.starts_with(b"PAR1")
+            }
+
+            fn read(
+                &self,
+                _path: &Path,
+                _sheet: Option<&str>,
+                _options: &ReadOptions,
+            ) -> Result<SourceRows, SourceError> {
+                Ok(SourceRows::default())
+            }
+        }
+
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("hours.bin");
+        fs::write(&path, b"PAR1\x15\x04").expect("write parquet");
+        let mut custom = SourceRegistry::default();
+        custom.register(ParquetReader);
+
+        let format = custom.detect(&path).map(|reader| reader.format()).ok();
+
+        assert_eq!(
+            format,
+            Some(SourceFormat::Other {
+                label: "Parquet",
+                has_sheets: false,
+            })
+        );
+        assert_eq!(format.map(SourceFormat::label), Some("Parquet"));
+        assert!(custom.handles_extension(Path::new("hours.parquet")));
+    }
+}